@@ -0,0 +1,473 @@
+use {
+    raytracing_rust::{BVHBuildMethod, RenderSettings},
+    std::{fmt, str::FromStr},
+};
+
+pub const USAGE: &str = "\
+Usage:
+    raytracing-rust render <scene-file> [options]
+    raytracing-rust demo <name> [options]
+    raytracing-rust list-demos
+    raytracing-rust export <scene-file> -o <mesh-file>
+    raytracing-rust bvh-report <mesh-file> [bvh-options]
+    raytracing-rust help
+
+Options:
+    -o, --output <file>   Write the image to <file> instead of the scene's output
+    --width <pixels>      Override the image width
+    --height <pixels>     Override the image height
+    --preset <name>       Start from the `preview` or `final` (default) render settings
+    --spp <samples>       Number of samples (ray paths) per pixel
+    --max-depth <depth>   Maximum number of bounces of a ray path
+    --threads <count>     Number of rendering threads (defaults to all cores)
+    --seed <seed>         Seed the random number generators for reproducible renders
+    --benchmark <runs>    Render <runs> times and report the timings
+    --bvh-builder <name>  Build mesh BVHs with `sah` (default) or `midpoint`
+    --bvh-cache           Cache mesh BVHs next to their mesh files
+
+The preset replaces the default settings, and the [render] section of the
+scene file is applied over it. Every other option overrides the scene file.
+
+BVH options:
+    --bvh-leaf-size <triangles>  Maximum amount of triangles in a leaf
+    --bvh-traversal-cost <cost>  Cost of visiting a node, relative to a triangle
+
+Exit codes:
+    0    Success
+    1    The scene could not be loaded or the image could not be written
+    2    Invalid command line";
+
+pub enum Command {
+    Render {
+        scene_file: String,
+        options: RenderOptions,
+    },
+    Demo {
+        name: String,
+        options: RenderOptions,
+    },
+    ListDemos,
+    Export {
+        scene_file: String,
+        output: String,
+    },
+    BVHReport {
+        mesh_file: String,
+        options: BVHReportOptions,
+    },
+    Help,
+}
+
+#[derive(Default)]
+pub struct RenderOptions {
+    pub output: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub preset: Option<RenderSettings>,
+    pub samples_per_pixel: Option<u32>,
+    pub max_recursion_depth: Option<i32>,
+    pub threads: Option<usize>,
+    pub seed: Option<u64>,
+    pub benchmark_runs: Option<u32>,
+    pub bvh_build_method: Option<BVHBuildMethod>,
+    pub bvh_cache: bool,
+}
+
+impl RenderOptions {
+    /// Apply the settings given on the command line, which take precedence
+    /// over the `[render]` section of scene files.
+    pub fn apply_overrides(&self, settings: &mut RenderSettings) {
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            settings.with_samples_per_pixel(samples_per_pixel);
+        }
+        if let Some(max_recursion_depth) = self.max_recursion_depth {
+            settings.with_max_recursion_depth(max_recursion_depth);
+        }
+        if let Some(seed) = self.seed {
+            settings.with_seed(seed);
+        }
+        if let Some(bvh_build_method) = self.bvh_build_method {
+            settings.with_bvh_build_method(bvh_build_method);
+        }
+        if self.bvh_cache {
+            settings.with_bvh_cache(true);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BVHReportOptions {
+    pub leaf_size: Option<usize>,
+    pub traversal_cost: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, UsageError> {
+    let command = args
+        .next()
+        .ok_or_else(|| UsageError("missing command".to_string()))?;
+
+    match command.as_str() {
+        "render" => {
+            let scene_file = args
+                .next()
+                .ok_or_else(|| UsageError("`render` expects a scene file".to_string()))?;
+            let options = parse_render_options(args)?;
+            Ok(Command::Render {
+                scene_file,
+                options,
+            })
+        }
+        "demo" => {
+            let name = args
+                .next()
+                .ok_or_else(|| UsageError("`demo` expects a demo name".to_string()))?;
+            let options = parse_render_options(args)?;
+            Ok(Command::Demo { name, options })
+        }
+        "list-demos" => expect_no_more_args(args, Command::ListDemos),
+        "export" => {
+            let scene_file = args
+                .next()
+                .ok_or_else(|| UsageError("`export` expects a scene file".to_string()))?;
+            let output = match args.next().as_deref() {
+                Some("-o" | "--output") => args
+                    .next()
+                    .ok_or_else(|| UsageError("`--output` expects a value".to_string()))?,
+                _ => return Err(UsageError("`export` expects `-o <mesh-file>`".to_string())),
+            };
+            expect_no_more_args(args, Command::Export { scene_file, output })
+        }
+        "bvh-report" => {
+            let mesh_file = args
+                .next()
+                .ok_or_else(|| UsageError("`bvh-report` expects a mesh file".to_string()))?;
+            let options = parse_bvh_report_options(args)?;
+            Ok(Command::BVHReport { mesh_file, options })
+        }
+        "help" | "-h" | "--help" => expect_no_more_args(args, Command::Help),
+        _ => Err(UsageError(format!("unknown command `{}`", command))),
+    }
+}
+
+fn expect_no_more_args<I: Iterator<Item = String>>(
+    mut args: I,
+    command: Command,
+) -> Result<Command, UsageError> {
+    match args.next() {
+        Some(arg) => Err(UsageError(format!("unexpected argument `{}`", arg))),
+        None => Ok(command),
+    }
+}
+
+fn parse_render_options<I: Iterator<Item = String>>(
+    mut args: I,
+) -> Result<RenderOptions, UsageError> {
+    let mut options = RenderOptions::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| UsageError(format!("`{}` expects a value", arg)))
+        };
+        match arg.as_str() {
+            "-o" | "--output" => options.output = Some(value()?),
+            "--width" => options.width = Some(parse_positive(&arg, &value()?)?),
+            "--height" => options.height = Some(parse_positive(&arg, &value()?)?),
+            "--preset" => {
+                let name = value()?;
+                options.preset = Some(RenderSettings::from_preset_name(&name).ok_or_else(|| {
+                    UsageError(format!(
+                        "unknown preset `{}`, expected `preview` or `final`",
+                        name
+                    ))
+                })?)
+            }
+            "--spp" => options.samples_per_pixel = Some(parse_positive(&arg, &value()?)?),
+            "--max-depth" => options.max_recursion_depth = Some(parse_positive(&arg, &value()?)?),
+            "--threads" => options.threads = Some(parse_positive(&arg, &value()?)?),
+            "--seed" => options.seed = Some(parse_value(&arg, &value()?)?),
+            "--benchmark" => options.benchmark_runs = Some(parse_positive(&arg, &value()?)?),
+            "--bvh-builder" => {
+                let name = value()?;
+                options.bvh_build_method =
+                    Some(BVHBuildMethod::from_name(&name).ok_or_else(|| {
+                        UsageError(format!(
+                            "unknown BVH builder `{}`, expected `midpoint` or `sah`",
+                            name
+                        ))
+                    })?)
+            }
+            "--bvh-cache" => options.bvh_cache = true,
+            _ => return Err(UsageError(format!("unknown option `{}`", arg))),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_bvh_report_options<I: Iterator<Item = String>>(
+    mut args: I,
+) -> Result<BVHReportOptions, UsageError> {
+    let mut options = BVHReportOptions::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| UsageError(format!("`{}` expects a value", arg)))
+        };
+        match arg.as_str() {
+            "--bvh-leaf-size" => options.leaf_size = Some(parse_positive(&arg, &value()?)?),
+            "--bvh-traversal-cost" => {
+                options.traversal_cost = Some(parse_non_negative(&arg, &value()?)?)
+            }
+            _ => return Err(UsageError(format!("unknown option `{}`", arg))),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &str) -> Result<T, UsageError> {
+    value
+        .parse::<T>()
+        .map_err(|_| UsageError(format!("invalid value `{}` for `{}`", value, option)))
+}
+
+fn parse_non_negative<T: FromStr + PartialOrd + Default>(
+    option: &str,
+    value: &str,
+) -> Result<T, UsageError> {
+    let parsed: T = parse_value(option, value)?;
+    if parsed >= T::default() {
+        Ok(parsed)
+    } else {
+        Err(UsageError(format!(
+            "`{}` expects a non-negative number, found `{}`",
+            option, value
+        )))
+    }
+}
+
+fn parse_positive<T: FromStr + PartialOrd + Default>(
+    option: &str,
+    value: &str,
+) -> Result<T, UsageError> {
+    let parsed: T = parse_value(option, value)?;
+    if parsed > T::default() {
+        Ok(parsed)
+    } else {
+        Err(UsageError(format!(
+            "`{}` expects a positive number, found `{}`",
+            option, value
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, UsageError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_error(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => panic!("expected {:?} to be rejected", args),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_render_commands() {
+        match parse(&[
+            "render",
+            "scenes/basic_spheres.scene",
+            "-o",
+            "out.png",
+            "--width",
+            "320",
+            "--height",
+            "200",
+            "--preset",
+            "preview",
+            "--spp",
+            "8",
+            "--max-depth",
+            "4",
+            "--threads",
+            "2",
+            "--seed",
+            "0",
+            "--benchmark",
+            "3",
+            "--bvh-builder",
+            "midpoint",
+            "--bvh-cache",
+        ]) {
+            Ok(Command::Render {
+                scene_file,
+                options,
+            }) => {
+                assert_eq!(scene_file, "scenes/basic_spheres.scene");
+                assert_eq!(options.output.as_deref(), Some("out.png"));
+                assert_eq!((options.width, options.height), (Some(320), Some(200)));
+                assert_eq!(
+                    options.preset.map(|preset| preset.get_samples_per_pixel()),
+                    Some(RenderSettings::preview().get_samples_per_pixel())
+                );
+                assert_eq!(options.samples_per_pixel, Some(8));
+                assert_eq!(options.max_recursion_depth, Some(4));
+                assert_eq!(options.threads, Some(2));
+                assert_eq!(options.seed, Some(0));
+                assert_eq!(options.benchmark_runs, Some(3));
+                assert_eq!(options.bvh_build_method, Some(BVHBuildMethod::Midpoint));
+                assert!(options.bvh_cache);
+            }
+            _ => panic!("expected a render command"),
+        }
+
+        match parse(&["render", "scene.scene"]) {
+            Ok(Command::Render { options, .. }) => {
+                assert!(options.output.is_none() && options.samples_per_pixel.is_none());
+                assert!(!options.bvh_cache);
+            }
+            _ => panic!("expected a render command"),
+        }
+    }
+
+    #[test]
+    fn overrides_render_settings() {
+        let Ok(Command::Render { options, .. }) = parse(&[
+            "render",
+            "scene.scene",
+            "--spp",
+            "8",
+            "--seed",
+            "3",
+            "--bvh-builder",
+            "midpoint",
+            "--bvh-cache",
+        ]) else {
+            panic!("expected a render command");
+        };
+
+        let mut settings = RenderSettings::preview();
+        settings
+            .with_max_recursion_depth(7)
+            .with_bvh_build_method(BVHBuildMethod::SurfaceAreaHeuristic);
+        options.apply_overrides(&mut settings);
+        assert_eq!(settings.get_samples_per_pixel(), 8);
+        assert_eq!(settings.get_max_recursion_depth(), 7);
+        assert_eq!(settings.get_seed(), Some(3));
+        assert_eq!(settings.get_bvh_build_method(), BVHBuildMethod::Midpoint);
+        assert!(settings.is_bvh_cache_enabled());
+    }
+
+    #[test]
+    fn parses_demo_commands() {
+        match parse(&["demo", "cacodemon", "--spp", "4"]) {
+            Ok(Command::Demo { name, options }) => {
+                assert_eq!(name, "cacodemon");
+                assert_eq!(options.samples_per_pixel, Some(4));
+            }
+            _ => panic!("expected a demo command"),
+        }
+        assert!(matches!(parse(&["list-demos"]), Ok(Command::ListDemos)));
+        for help in ["help", "-h", "--help"] {
+            assert!(matches!(parse(&[help]), Ok(Command::Help)));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_options() {
+        assert_eq!(parse_error(&[]), "missing command");
+        assert_eq!(parse_error(&["draw"]), "unknown command `draw`");
+        assert_eq!(
+            parse_error(&["render", "scene.scene", "--fast"]),
+            "unknown option `--fast`"
+        );
+        assert_eq!(
+            parse_error(&["list-demos", "extra"]),
+            "unexpected argument `extra`"
+        );
+        assert_eq!(
+            parse_error(&["render", "scene.scene", "--preset", "draft"]),
+            "unknown preset `draft`, expected `preview` or `final`"
+        );
+        assert_eq!(
+            parse_error(&["demo", "cat", "--bvh-builder", "octree"]),
+            "unknown BVH builder `octree`, expected `midpoint` or `sah`"
+        );
+        assert_eq!(
+            parse_error(&["bvh-report", "mesh.obj", "--spp", "4"]),
+            "unknown option `--spp`"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_numeric_values() {
+        for (option, value) in [
+            ("--width", "0"),
+            ("--height", "0"),
+            ("--spp", "0"),
+            ("--max-depth", "-1"),
+            ("--threads", "0"),
+            ("--benchmark", "0"),
+        ] {
+            assert_eq!(
+                parse_error(&["render", "scene.scene", option, value]),
+                format!("`{}` expects a positive number, found `{}`", option, value)
+            );
+        }
+        for (option, value) in [
+            ("--width", "wide"),
+            ("--spp", "1.5"),
+            ("--seed", "-1"),
+            ("--height", "-5"),
+        ] {
+            assert_eq!(
+                parse_error(&["render", "scene.scene", option, value]),
+                format!("invalid value `{}` for `{}`", value, option)
+            );
+        }
+        assert_eq!(
+            parse_error(&["bvh-report", "mesh.obj", "--bvh-traversal-cost", "-1"]),
+            "`--bvh-traversal-cost` expects a non-negative number, found `-1`"
+        );
+        match parse(&["bvh-report", "mesh.obj", "--bvh-traversal-cost", "0"]) {
+            Ok(Command::BVHReport { options, .. }) => assert_eq!(options.traversal_cost, Some(0.)),
+            _ => panic!("expected a bvh-report command"),
+        }
+    }
+
+    #[test]
+    fn rejects_missing_arguments() {
+        assert_eq!(parse_error(&["render"]), "`render` expects a scene file");
+        assert_eq!(parse_error(&["demo"]), "`demo` expects a demo name");
+        assert_eq!(parse_error(&["export"]), "`export` expects a scene file");
+        assert_eq!(
+            parse_error(&["export", "scene.scene"]),
+            "`export` expects `-o <mesh-file>`"
+        );
+        assert_eq!(
+            parse_error(&["bvh-report"]),
+            "`bvh-report` expects a mesh file"
+        );
+        for option in ["-o", "--width", "--spp", "--preset", "--seed"] {
+            assert_eq!(
+                parse_error(&["render", "scene.scene", option]),
+                format!("`{}` expects a value", option)
+            );
+        }
+    }
+}
@@ -1,14 +1,136 @@
 use crate::{
     core::{Intersectable, Intersection, Mesh, Ray},
-    utils::Vector3,
+    utils::{Transform, Vector3},
 };
 
+/// Relative widening of the exit distance of slab tests, covering the
+/// rounding errors of the three operations computing it. Without it, rays
+/// grazing a vertex on a face of the box can miss the box.
+const SLAB_EXIT_SCALE: f64 = 1. + 4. * f64::EPSILON;
+
+/// Axis-aligned box, used to bound objects and parts of meshes.
+#[derive(Clone, Copy)]
 pub struct BoundingBox {
     min: Vector3,
     max: Vector3,
 }
 
 impl BoundingBox {
+    pub fn new(min: Vector3, max: Vector3) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    /// The smallest box containing all the points, or a single point at the
+    /// origin if there are none.
+    pub fn new_from_points<'a>(points: impl IntoIterator<Item = &'a Vector3>) -> BoundingBox {
+        let mut points = points.into_iter();
+        let first = points.next().copied().unwrap_or(Vector3::new(0., 0., 0.));
+        points.fold(BoundingBox::new(first, first), |bounding_box, point| {
+            bounding_box.calculate_union(&BoundingBox::new(*point, *point))
+        })
+    }
+
+    /// The box around a flat disk.
+    pub fn new_from_disk(center: Vector3, normal: Vector3, radius: f64) -> BoundingBox {
+        let normal = normal.normalized();
+        // Along each axis, the disk spans its radius times the sine of the
+        // angle between the axis and the normal
+        let extent = |component: f64| radius * (1. - component * component).max(0.).sqrt();
+        let half_size = Vector3::new(extent(normal.x()), extent(normal.y()), extent(normal.z()));
+        BoundingBox::new(center - half_size, center + half_size)
+    }
+
+    pub fn get_min(&self) -> &Vector3 {
+        &self.min
+    }
+
+    pub fn get_max(&self) -> &Vector3 {
+        &self.max
+    }
+
+    pub fn calculate_union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox::new(
+            Vector3::new(
+                self.min.x().min(other.min.x()),
+                self.min.y().min(other.min.y()),
+                self.min.z().min(other.min.z()),
+            ),
+            Vector3::new(
+                self.max.x().max(other.max.x()),
+                self.max.y().max(other.max.y()),
+                self.max.z().max(other.max.z()),
+            ),
+        )
+    }
+
+    /// The overlap of two boxes. It is empty, with `min` greater than `max`
+    /// along some axis, if they do not overlap.
+    pub fn calculate_intersection(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox::new(
+            Vector3::new(
+                self.min.x().max(other.min.x()),
+                self.min.y().max(other.min.y()),
+                self.min.z().max(other.min.z()),
+            ),
+            Vector3::new(
+                self.max.x().min(other.max.x()),
+                self.max.y().min(other.max.y()),
+                self.max.z().min(other.max.z()),
+            ),
+        )
+    }
+
+    /// The box grown by `margin` on every side.
+    pub fn calculate_padded(&self, margin: f64) -> BoundingBox {
+        let margin = Vector3::new(margin, margin, margin);
+        BoundingBox::new(self.min - margin, self.max + margin)
+    }
+
+    /// The box around the transformed corners of this box.
+    pub fn calculate_transformed(&self, transform: &Transform) -> BoundingBox {
+        let corners: Vec<Vector3> = (0..8)
+            .map(|corner| {
+                let pick = |bit: usize, axis: usize| {
+                    if corner & bit == 0 {
+                        self.min[axis]
+                    } else {
+                        self.max[axis]
+                    }
+                };
+                transform.transform_point(&Vector3::new(pick(1, 0), pick(2, 1), pick(4, 2)))
+            })
+            .collect();
+        BoundingBox::new_from_points(&corners)
+    }
+
+    /// Distance along the ray at which it enters the box, or 0 if it starts
+    /// inside, provided it does so before `max_distance`.
+    pub fn calculate_entry_distance(&self, ray: &Ray, max_distance: f64) -> Option<f64> {
+        let origin = ray.get_origin();
+        let direction = ray.get_direction();
+
+        let mut entry = 0.;
+        let mut exit = max_distance;
+        for axis in 0..3 {
+            // Slab test: clip the ray between the two planes of each axis.
+            // Directions parallel to the planes give infinite distances, and
+            // `min` and `max` skip the NaNs of rays lying in a plane.
+            let inverse_direction = 1. / direction[axis];
+            let mut near = (self.min[axis] - origin[axis]) * inverse_direction;
+            let mut far = (self.max[axis] - origin[axis]) * inverse_direction;
+            if inverse_direction < 0. {
+                std::mem::swap(&mut near, &mut far);
+            }
+            entry = near.max(entry);
+            exit = (far * SLAB_EXIT_SCALE).min(exit);
+            if exit < entry {
+                return None;
+            }
+        }
+
+        Some(entry)
+    }
+
     pub fn new_from_mesh(mesh: &Mesh) -> BoundingBox {
         let mut min_x = f64::INFINITY;
         let mut min_y = f64::INFINITY;
@@ -99,10 +221,25 @@ impl BoundingBox {
     pub fn calculate_center(&self) -> Vector3 {
         (self.min + self.max) / 2.
     }
+
+    /// Area of the faces of the box, or 0 for an empty box.
+    pub fn calculate_surface_area(&self) -> f64 {
+        let diagonals = self.calculate_diagonals();
+        if diagonals.x() < 0. || diagonals.y() < 0. || diagonals.z() < 0. {
+            return 0.;
+        }
+        2. * (diagonals.x() * diagonals.y()
+            + diagonals.y() * diagonals.z()
+            + diagonals.z() * diagonals.x())
+    }
 }
 
 impl Intersectable for BoundingBox {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        Some(*self)
+    }
+
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
         let point_a = self.min;
         let point_b = Vector3::new(self.max.x(), self.min.y(), self.min.z());
         let point_c = Vector3::new(self.min.x(), self.min.y(), self.max.z());
@@ -133,7 +270,7 @@ impl Intersectable for BoundingBox {
                     direction_dot_normal_1 < 0.,
                     None,
                     None,
-                    ray.clone(),
+                    *ray,
                 ));
             }
         }
@@ -156,7 +293,7 @@ impl Intersectable for BoundingBox {
                     direction_dot_normal_2 < 0.,
                     None,
                     None,
-                    ray.clone(),
+                    *ray,
                 ));
             }
         }
@@ -179,7 +316,7 @@ impl Intersectable for BoundingBox {
                     direction_dot_normal_3 < 0.,
                     None,
                     None,
-                    ray.clone(),
+                    *ray,
                 ));
             }
         }
@@ -202,7 +339,7 @@ impl Intersectable for BoundingBox {
                     direction_dot_normal_4 < 0.,
                     None,
                     None,
-                    ray.clone(),
+                    *ray,
                 ));
             }
         }
@@ -225,7 +362,7 @@ impl Intersectable for BoundingBox {
                     direction_dot_normal_5 < 0.,
                     None,
                     None,
-                    ray.clone(),
+                    *ray,
                 ));
             }
         }
@@ -248,7 +385,7 @@ impl Intersectable for BoundingBox {
                     direction_dot_normal_6 < 0.,
                     None,
                     None,
-                    ray.clone(),
+                    *ray,
                 ));
             }
         }
@@ -1,139 +1,678 @@
-use crate::{
-    core::{BoundingBox, Intersectable, Intersection, Mesh, Ray},
-    utils::MIN_BVH_NODE_SIZE,
+use {
+    crate::{
+        core::{
+            bvh_cache::{self, BVHCacheKey},
+            BoundingBox, Intersectable, Intersection, Mesh, Ray, RenderSettings, TriangleIndices,
+        },
+        error::{Error, Result},
+        utils::Vector3,
+    },
+    std::{fmt, fs},
 };
 
+/// Number of candidate split planes per axis tried by the SAH builder.
+const SAH_BIN_COUNT: usize = 16;
+
+/// Nodes with fewer triangles build their children on the current thread.
+const PARALLEL_BUILD_MIN_TRIANGLES: usize = 4096;
+
+/// Nodes this deep are not split further, so that traversal can use a
+/// fixed-size stack.
+pub(crate) const MAX_BVH_DEPTH: usize = 64;
+
+/// How the triangles of a node are split between its children.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BVHBuildMethod {
+    /// Split at the center of the node, along its longest axis. Fast to
+    /// build, but produces poor trees on meshes with uneven triangle sizes.
+    Midpoint,
+    /// Binned surface area heuristic: try several split planes along each
+    /// axis, and keep the one minimizing the expected cost of a ray.
+    SurfaceAreaHeuristic,
+}
+
+impl BVHBuildMethod {
+    /// Look up a method by the name used in scene files and on the command
+    /// line, `midpoint` or `sah`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "midpoint" => Some(BVHBuildMethod::Midpoint),
+            "sah" => Some(BVHBuildMethod::SurfaceAreaHeuristic),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            BVHBuildMethod::Midpoint => "midpoint",
+            BVHBuildMethod::SurfaceAreaHeuristic => "sah",
+        }
+    }
+}
+
+/// Bounding volume hierarchy over the triangles of a mesh.
+///
+/// The nodes are stored in a single array, in depth-first order: the left
+/// child of a node directly follows it, and the node stores the index of its
+/// right child. The triangles of the mesh are reordered so that each leaf
+/// covers a contiguous range of them.
 pub struct BVHTree {
-    root: BVHNode,
+    nodes: Vec<BVHNode>,
     mesh: Mesh,
 }
 
-pub struct BVHNode {
-    bounding_box: BoundingBox,
-    left: Option<Box<BVHNode>>,
-    right: Option<Box<BVHNode>>,
-    start_triangle_index: usize,
-    end_triangle_index: usize,
+/// A node of the flattened tree, which fits in a cache line.
+pub(crate) struct BVHNode {
+    pub(crate) bounding_box: BoundingBox,
+    pub(crate) start_triangle_index: u32,
+    pub(crate) triangle_count: u32,
+    /// 0 for leaves, as the root is never a right child
+    pub(crate) right_child_index: u32,
+}
+
+/// Recursive construction of the nodes of a `BVHTree`.
+struct BVHBuilder<'a> {
+    vertices: &'a [Vector3],
+    method: BVHBuildMethod,
+    leaf_size: usize,
+    traversal_cost: f64,
+}
+
+/// Statistics about the shape of a BVH, to compare build methods.
+pub struct BVHQualityReport {
+    sah_cost: f64,
+    depth: usize,
+    node_count: usize,
+    leaf_count: usize,
+    triangle_count: usize,
+    leaf_size_histogram: Vec<usize>,
 }
 
 impl BVHTree {
-    pub fn new_from_mesh(mut mesh: Mesh) -> Self {
-        let end = mesh.get_triangles().len();
-        let root = BVHNode::new_from_mesh(&mut mesh, 0, end);
-        BVHTree { root, mesh }
+    /// Build the BVH with the midpoint method.
+    pub fn new_from_mesh(mesh: Mesh, leaf_size: usize) -> Self {
+        Self::new_from_mesh_with_method(mesh, BVHBuildMethod::Midpoint, leaf_size, 1.)
     }
-}
 
-impl Intersectable for BVHTree {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
-        if self.root.bounding_box.intersect(ray).is_none() {
-            return None;
+    /// Build the BVH with the given method. Leaves hold at most `leaf_size`
+    /// triangles, unless they cannot be split. The traversal cost is the cost
+    /// of visiting a node relative to intersecting a triangle, and is only
+    /// used by the surface area heuristic. Large subtrees are built in
+    /// parallel, and the result does not depend on the number of threads.
+    pub fn new_from_mesh_with_method(
+        mut mesh: Mesh,
+        method: BVHBuildMethod,
+        leaf_size: usize,
+        traversal_cost: f64,
+    ) -> Self {
+        let (vertices, triangles) = mesh.get_vertices_and_triangles_mut();
+        let builder = BVHBuilder {
+            vertices,
+            method,
+            leaf_size,
+            traversal_cost,
+        };
+        let mut nodes = Vec::new();
+        builder.build(triangles, 0, 1, &mut nodes);
+
+        BVHTree { nodes, mesh }
+    }
+
+    /// Build the BVH of a mesh with the normal mapping, subdivision and BVH
+    /// settings of the settings, for instance to share it between several
+    /// objects (see `MeshObjectBuilder::new_instance`).
+    pub fn new_from_mesh_with_settings(mut mesh: Mesh, settings: &RenderSettings) -> Self {
+        mesh.set_normal_mapping(settings.is_normal_mapping_enabled());
+        mesh.subdivide(settings.get_subdivision_levels());
+        Self::new_from_mesh_with_method(
+            mesh,
+            settings.get_bvh_build_method(),
+            settings.get_bvh_leaf_size(),
+            settings.get_bvh_traversal_cost(),
+        )
+    }
+
+    /// Load a mesh from an OBJ, PLY or STL file (see `Mesh::from_file`),
+    /// subdivide it, and build its BVH with the settings.
+    ///
+    /// If the BVH cache is enabled in the settings, the BVH is read from a
+    /// cache file next to the mesh file, `model.obj.bvh` for `model.obj`. The
+    /// cache is only used if it was built from the same file contents with the
+    /// same BVH settings, crease angle and subdivision levels; otherwise, the
+    /// BVH is built and the cache file is rewritten. Failing to write the
+    /// cache is not an error, the BVH is just built again next time.
+    pub fn new_from_file(mesh_file: &str, settings: &RenderSettings) -> Result<Self> {
+        let mut mesh = Mesh::from_file_with_crease_angle(mesh_file, settings.get_crease_angle())?;
+        mesh.set_normal_mapping(settings.is_normal_mapping_enabled());
+        mesh.subdivide(settings.get_subdivision_levels());
+
+        let build = |mesh| {
+            Self::new_from_mesh_with_method(
+                mesh,
+                settings.get_bvh_build_method(),
+                settings.get_bvh_leaf_size(),
+                settings.get_bvh_traversal_cost(),
+            )
+        };
+        if !settings.is_bvh_cache_enabled() {
+            return Ok(build(mesh));
         }
 
-        let mut nodes_to_visit = vec![&self.root];
+        let contents = fs::read(mesh_file).map_err(|err| Error::io(mesh_file, err))?;
+        let key = BVHCacheKey::new(
+            &contents,
+            settings.get_bvh_build_method(),
+            settings.get_bvh_leaf_size(),
+            settings.get_bvh_traversal_cost(),
+            settings.get_crease_angle(),
+            settings.get_subdivision_levels(),
+        );
+        let cache_file = bvh_cache::calculate_cache_path(mesh_file);
 
-        let mut closest_intersection: Option<Intersection> = None;
+        if let Some((triangles, nodes)) = bvh_cache::read_cache(&cache_file, &key, &mesh) {
+            mesh.set_triangles(triangles);
+            return Ok(BVHTree { nodes, mesh });
+        }
+
+        let bvh = build(mesh);
+        let _ = bvh_cache::write_cache(&cache_file, &key, &bvh);
+        Ok(bvh)
+    }
+
+    pub fn get_mesh(&self) -> &Mesh {
+        &self.mesh
+    }
 
-        while let Some(node) = nodes_to_visit.pop() {
-            let bounding_box_intersection = node.bounding_box.intersect(ray);
-            if bounding_box_intersection.is_none() {
+    pub(crate) fn get_nodes(&self) -> &[BVHNode] {
+        &self.nodes
+    }
+
+    /// Measure the tree. The SAH cost is computed with the given traversal
+    /// cost, so that trees built with different methods can be compared on
+    /// equal terms.
+    pub fn calculate_quality_report(&self, traversal_cost: f64) -> BVHQualityReport {
+        let mut report = BVHQualityReport {
+            sah_cost: 0.,
+            depth: 0,
+            node_count: self.nodes.len(),
+            leaf_count: 0,
+            triangle_count: self.mesh.get_triangles().len(),
+            leaf_size_histogram: Vec::new(),
+        };
+
+        let root_area = self.nodes[0].bounding_box.calculate_surface_area();
+        let mut nodes_to_visit = vec![(0, 1)];
+        while let Some((node_index, depth)) = nodes_to_visit.pop() {
+            let node = &self.nodes[node_index];
+            // Probability that a ray hitting the root also hits the node
+            let hit_probability = if root_area > 0. {
+                node.bounding_box.calculate_surface_area() / root_area
+            } else {
+                1.
+            };
+            report.depth = report.depth.max(depth);
+
+            if node.is_leaf() {
+                let size = node.triangle_count as usize;
+                report.sah_cost += hit_probability * size as f64;
+                report.leaf_count += 1;
+                if report.leaf_size_histogram.len() <= size {
+                    report.leaf_size_histogram.resize(size + 1, 0);
+                }
+                report.leaf_size_histogram[size] += 1;
                 continue;
             }
 
+            report.sah_cost += hit_probability * traversal_cost;
+            nodes_to_visit.push((node_index + 1, depth + 1));
+            nodes_to_visit.push((node.right_child_index as usize, depth + 1));
+        }
+
+        report
+    }
+}
+
+impl BVHTree {
+    /// Visit the leaves the ray enters before `max_distance`, nearest child
+    /// first. `visit_leaf` is given the triangle range of each leaf and the
+    /// current maximum distance, and returns the new maximum distance, or
+    /// `None` to stop. Nodes entered beyond the maximum distance are skipped.
+    fn traverse(
+        &self,
+        ray: &Ray,
+        mut max_distance: f64,
+        mut visit_leaf: impl FnMut(usize, usize, f64) -> Option<f64>,
+    ) {
+        if self.nodes[0]
+            .bounding_box
+            .calculate_entry_distance(ray, max_distance)
+            .is_none()
+        {
+            return;
+        }
+
+        // Farther children left for later, with the distance at which the
+        // ray enters them
+        let mut nodes_to_visit = [(0, 0.); MAX_BVH_DEPTH];
+        let mut nodes_to_visit_count = 0;
+        let mut node_index = 0;
+
+        loop {
+            let node = &self.nodes[node_index];
+
             if node.is_leaf() {
-                if let Some(mesh_intersection) = self.mesh.intersect_part(
-                    ray,
-                    node.start_triangle_index,
-                    node.end_triangle_index,
-                ) {
-                    if closest_intersection.as_ref().map_or(true, |closest| {
-                        mesh_intersection.get_distance() < closest.get_distance()
-                    }) {
-                        closest_intersection = Some(mesh_intersection);
+                let start = node.start_triangle_index as usize;
+                let end = start + node.triangle_count as usize;
+                match visit_leaf(start, end, max_distance) {
+                    Some(distance) => max_distance = distance,
+                    None => return,
+                }
+            } else {
+                let left_index = node_index + 1;
+                let right_index = node.right_child_index as usize;
+                let entry_distance = |index: usize| {
+                    self.nodes[index]
+                        .bounding_box
+                        .calculate_entry_distance(ray, max_distance)
+                };
+
+                match (entry_distance(left_index), entry_distance(right_index)) {
+                    (Some(left_distance), Some(right_distance)) => {
+                        let (near_index, far_index, far_distance) =
+                            if left_distance <= right_distance {
+                                (left_index, right_index, right_distance)
+                            } else {
+                                (right_index, left_index, left_distance)
+                            };
+                        nodes_to_visit[nodes_to_visit_count] = (far_index, far_distance);
+                        nodes_to_visit_count += 1;
+                        node_index = near_index;
+                        continue;
                     }
+                    (Some(_), None) => {
+                        node_index = left_index;
+                        continue;
+                    }
+                    (None, Some(_)) => {
+                        node_index = right_index;
+                        continue;
+                    }
+                    (None, None) => {}
                 }
-                continue;
             }
 
-            if let Some(left) = &node.left {
-                nodes_to_visit.push(left);
-            }
-            if let Some(right) = &node.right {
-                nodes_to_visit.push(right);
+            // Resume with the nearest node left, unless a closer triangle has
+            // been found since it was put aside
+            loop {
+                if nodes_to_visit_count == 0 {
+                    return;
+                }
+                nodes_to_visit_count -= 1;
+                let (index, entry_distance) = nodes_to_visit[nodes_to_visit_count];
+                if entry_distance <= max_distance {
+                    node_index = index;
+                    break;
+                }
             }
         }
+    }
+}
+
+impl Intersectable for BVHTree {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let mut closest_intersection: Option<Intersection> = None;
+
+        self.traverse(ray, f64::INFINITY, |start, end, closest_distance| {
+            if let Some(mesh_intersection) = self.mesh.intersect_part(ray, start, end) {
+                if mesh_intersection.get_distance() < closest_distance {
+                    let distance = mesh_intersection.get_distance();
+                    closest_intersection = Some(mesh_intersection);
+                    return Some(distance);
+                }
+            }
+            Some(closest_distance)
+        });
 
         closest_intersection
     }
+
+    fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        let mut occluded = false;
+
+        self.traverse(ray, max_distance, |start, end, max_distance| {
+            occluded = self.mesh.occluded_part(ray, start, end, max_distance);
+            (!occluded).then_some(max_distance)
+        });
+
+        occluded
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        Some(self.nodes[0].bounding_box)
+    }
 }
 
 impl BVHNode {
-    pub fn new_from_mesh(
-        mesh: &mut Mesh,
+    fn new_leaf(
+        bounding_box: BoundingBox,
         start_triangle_index: usize,
         end_triangle_index: usize,
     ) -> Self {
-        let bounding_box = BoundingBox::new_from_mesh_and_triangle_indices(
-            mesh,
+        BVHNode {
+            bounding_box,
+            start_triangle_index: start_triangle_index as u32,
+            triangle_count: (end_triangle_index - start_triangle_index) as u32,
+            right_child_index: 0,
+        }
+    }
+
+    pub(crate) fn is_leaf(&self) -> bool {
+        self.right_child_index == 0
+    }
+}
+
+/// The triangles whose centers fall between two candidate split planes of
+/// the SAH builder, or on one side of a plane.
+#[derive(Clone, Copy)]
+struct SahBin {
+    bounding_box: Option<BoundingBox>,
+    triangle_count: usize,
+}
+
+impl SahBin {
+    const EMPTY: Self = SahBin {
+        bounding_box: None,
+        triangle_count: 0,
+    };
+
+    fn add(&mut self, other: &SahBin) {
+        self.bounding_box = match (self.bounding_box, other.bounding_box) {
+            (Some(a), Some(b)) => Some(a.calculate_union(&b)),
+            (a, b) => a.or(b),
+        };
+        self.triangle_count += other.triangle_count;
+    }
+
+    /// Area of the box times the number of triangles, the part of the SAH
+    /// cost of a child not yet divided by the area of its parent.
+    fn calculate_cost(&self) -> f64 {
+        self.bounding_box
+            .map_or(0., |bounding_box| bounding_box.calculate_surface_area())
+            * self.triangle_count as f64
+    }
+}
+
+impl BVHBuilder<'_> {
+    /// Build the subtree of a range of triangles, starting at
+    /// `start_triangle_index` in the mesh, and append its nodes in
+    /// depth-first order. The triangles are reordered so that each leaf
+    /// covers a contiguous range.
+    fn build(
+        &self,
+        triangles: &mut [TriangleIndices],
+        start_triangle_index: usize,
+        depth: usize,
+        nodes: &mut Vec<BVHNode>,
+    ) {
+        let bounding_box = self.calculate_bounding_box(triangles);
+        let end_triangle_index = start_triangle_index + triangles.len();
+
+        // A leaf, unless it gets a right child below
+        let node_index = nodes.len();
+        nodes.push(BVHNode::new_leaf(
+            bounding_box,
             start_triangle_index,
             end_triangle_index,
+        ));
+
+        let pivot_index = if depth >= MAX_BVH_DEPTH {
+            None
+        } else {
+            match self.method {
+                BVHBuildMethod::Midpoint => self.split_at_midpoint(triangles, &bounding_box),
+                BVHBuildMethod::SurfaceAreaHeuristic => {
+                    self.split_with_sah(triangles, &bounding_box)
+                }
+            }
+        };
+        let Some(pivot_index) = pivot_index else {
+            return;
+        };
+
+        let triangle_count = triangles.len();
+        let (left_triangles, right_triangles) = triangles.split_at_mut(pivot_index);
+        let right_start_triangle_index = start_triangle_index + pivot_index;
+
+        if triangle_count < PARALLEL_BUILD_MIN_TRIANGLES {
+            self.build(left_triangles, start_triangle_index, depth + 1, nodes);
+            nodes[node_index].right_child_index = nodes.len() as u32;
+            self.build(
+                right_triangles,
+                right_start_triangle_index,
+                depth + 1,
+                nodes,
+            );
+            return;
+        }
+
+        // Build both subtrees on their own, then append them with their
+        // child indices shifted
+        let build_subtree = |triangles: &mut [TriangleIndices], start_triangle_index| {
+            let mut subtree = Vec::new();
+            self.build(triangles, start_triangle_index, depth + 1, &mut subtree);
+            subtree
+        };
+        let (left_nodes, right_nodes) = rayon::join(
+            || build_subtree(left_triangles, start_triangle_index),
+            || build_subtree(right_triangles, right_start_triangle_index),
         );
+        Self::append_subtree(nodes, left_nodes);
+        nodes[node_index].right_child_index = nodes.len() as u32;
+        Self::append_subtree(nodes, right_nodes);
+    }
 
-        if end_triangle_index - start_triangle_index <= MIN_BVH_NODE_SIZE {
-            return BVHNode {
-                bounding_box,
-                left: None,
-                right: None,
-                start_triangle_index,
-                end_triangle_index,
-            };
+    fn append_subtree(nodes: &mut Vec<BVHNode>, subtree: Vec<BVHNode>) {
+        let offset = nodes.len() as u32;
+        nodes.extend(subtree.into_iter().map(|mut node| {
+            if !node.is_leaf() {
+                node.right_child_index += offset;
+            }
+            node
+        }));
+    }
+
+    /// Split at the center of the box, along its longest axis. Returns the
+    /// number of triangles of the left child, or `None` to make a leaf.
+    fn split_at_midpoint(
+        &self,
+        triangles: &mut [TriangleIndices],
+        bounding_box: &BoundingBox,
+    ) -> Option<usize> {
+        if triangles.len() <= self.leaf_size {
+            return None;
         }
 
         let diagonals = bounding_box.calculate_diagonals();
         let longest_axis = diagonals.abs().greatest_component();
         let center = bounding_box.calculate_center();
 
-        let mut pivot_index = start_triangle_index;
-        for i in start_triangle_index..end_triangle_index {
-            let triangle = mesh.get_triangle(i);
-            let triangle_center = mesh.calculate_triangle_center(triangle);
+        let mut pivot_index = 0;
+        for i in 0..triangles.len() {
+            let triangle_center = self.calculate_triangle_center(&triangles[i]);
             if triangle_center[longest_axis] < center[longest_axis] {
-                mesh.swap_triangles(i, pivot_index);
+                triangles.swap(i, pivot_index);
                 pivot_index += 1;
             }
         }
 
-        if pivot_index == start_triangle_index || pivot_index == end_triangle_index {
-            return BVHNode {
-                bounding_box,
-                left: None,
-                right: None,
-                start_triangle_index,
-                end_triangle_index,
-            };
+        if pivot_index == 0 || pivot_index == triangles.len() {
+            None
+        } else {
+            Some(pivot_index)
         }
+    }
 
-        let left = BVHNode::new_from_mesh(mesh, start_triangle_index, pivot_index);
-        let right = BVHNode::new_from_mesh(mesh, pivot_index, end_triangle_index);
+    /// Binned SAH split. The triangle centers are sorted into bins along each
+    /// axis, and the node is split at the bin boundary minimizing
+    /// `traversal_cost + (area(left) * count(left) + area(right) *
+    /// count(right)) / area(node)`. Nodes of at most `leaf_size` triangles
+    /// become leaves when no split is cheaper than testing all of them.
+    fn split_with_sah(
+        &self,
+        triangles: &mut [TriangleIndices],
+        bounding_box: &BoundingBox,
+    ) -> Option<usize> {
+        let triangle_count = triangles.len();
+        if triangle_count <= 1 {
+            return None;
+        }
 
-        BVHNode {
-            bounding_box,
-            left: Some(Box::new(left)),
-            right: Some(Box::new(right)),
-            start_triangle_index,
-            end_triangle_index,
+        let triangle_bounds: Vec<(BoundingBox, Vector3)> = triangles
+            .iter()
+            .map(|triangle| {
+                (
+                    self.calculate_bounding_box(std::slice::from_ref(triangle)),
+                    self.calculate_triangle_center(triangle),
+                )
+            })
+            .collect();
+        let centers: Vec<Vector3> = triangle_bounds.iter().map(|(_, center)| *center).collect();
+        let center_bounds = BoundingBox::new_from_points(&centers);
+        let (center_min, center_extent) =
+            (center_bounds.get_min(), center_bounds.calculate_diagonals());
+        let bin_index = |axis: usize, center: &Vector3| {
+            let offset = (center[axis] - center_min[axis]) / center_extent[axis];
+            ((offset * SAH_BIN_COUNT as f64) as usize).min(SAH_BIN_COUNT - 1)
+        };
+
+        // Cheapest split as (cost, axis, first bin of the right child)
+        let node_area = bounding_box.calculate_surface_area();
+        let mut best_split: Option<(f64, usize, usize)> = None;
+        for axis in 0..3 {
+            if center_extent[axis] <= 0. {
+                continue;
+            }
+
+            let mut bins = [SahBin::EMPTY; SAH_BIN_COUNT];
+            for (triangle_box, center) in &triangle_bounds {
+                bins[bin_index(axis, center)].add(&SahBin {
+                    bounding_box: Some(*triangle_box),
+                    triangle_count: 1,
+                });
+            }
+
+            // Sweep from the right to get the cost of each right child, then
+            // from the left to evaluate each split
+            let mut right_costs = [0.; SAH_BIN_COUNT];
+            let mut right = SahBin::EMPTY;
+            for split in (1..SAH_BIN_COUNT).rev() {
+                right.add(&bins[split]);
+                right_costs[split] = right.calculate_cost();
+            }
+
+            let mut left = SahBin::EMPTY;
+            for split in 1..SAH_BIN_COUNT {
+                left.add(&bins[split - 1]);
+                if left.triangle_count == 0 || left.triangle_count == triangle_count {
+                    continue;
+                }
+                let cost =
+                    self.traversal_cost + (left.calculate_cost() + right_costs[split]) / node_area;
+                if best_split.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best_split = Some((cost, axis, split));
+                }
+            }
+        }
+
+        match best_split {
+            Some((cost, _, _))
+                if triangle_count <= self.leaf_size && triangle_count as f64 <= cost =>
+            {
+                None
+            }
+            Some((_, axis, split)) => {
+                let mut pivot_index = 0;
+                for (i, (_, center)) in triangle_bounds.iter().enumerate() {
+                    if bin_index(axis, center) < split {
+                        triangles.swap(i, pivot_index);
+                        pivot_index += 1;
+                    }
+                }
+                Some(pivot_index)
+            }
+            // All the centers are at the same point, so no plane separates
+            // the triangles: only split large nodes, arbitrarily
+            None if triangle_count <= self.leaf_size => None,
+            None => Some(triangle_count / 2),
         }
     }
 
-    pub fn get_start_triangle_index(&self) -> usize {
-        self.start_triangle_index
+    fn calculate_bounding_box(&self, triangles: &[TriangleIndices]) -> BoundingBox {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for triangle in triangles {
+            let (a, b, c) = triangle.get_vertex_indices();
+            for vertex in [&self.vertices[a], &self.vertices[b], &self.vertices[c]] {
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(vertex[axis]);
+                    max[axis] = max[axis].max(vertex[axis]);
+                }
+            }
+        }
+        BoundingBox::new(
+            Vector3::new(min[0], min[1], min[2]),
+            Vector3::new(max[0], max[1], max[2]),
+        )
+    }
+
+    fn calculate_triangle_center(&self, triangle: &TriangleIndices) -> Vector3 {
+        let (a, b, c) = triangle.get_vertex_indices();
+        (self.vertices[a] + self.vertices[b] + self.vertices[c]) / 3.
+    }
+}
+
+impl BVHQualityReport {
+    /// Expected cost of a ray hitting the root box, in triangle
+    /// intersections.
+    pub fn get_sah_cost(&self) -> f64 {
+        self.sah_cost
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
     }
 
-    pub fn get_end_triangle_index(&self) -> usize {
-        self.end_triangle_index
+    pub fn get_node_count(&self) -> usize {
+        self.node_count
     }
 
-    pub fn is_leaf(&self) -> bool {
-        self.left.is_none() && self.right.is_none()
+    pub fn get_leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn get_triangle_count(&self) -> usize {
+        self.triangle_count
+    }
+
+    /// Number of leaves of each size, indexed by their amount of triangles.
+    pub fn get_leaf_size_histogram(&self) -> &[usize] {
+        &self.leaf_size_histogram
+    }
+}
+
+impl fmt::Display for BVHQualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Triangles: {}", self.triangle_count)?;
+        writeln!(f, "Nodes: {}", self.node_count)?;
+        writeln!(f, "Leaves: {}", self.leaf_count)?;
+        writeln!(f, "Depth: {}", self.depth)?;
+        writeln!(f, "SAH cost: {:.2}", self.sah_cost)?;
+        write!(f, "Leaf sizes:")?;
+        for (size, count) in self.leaf_size_histogram.iter().enumerate() {
+            if *count > 0 {
+                write!(f, "\n  {:>4} triangles: {}", size, count)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,264 @@
+//! Cache of mesh BVHs, stored next to the mesh files they are built from.
+//!
+//! A cache file holds the triangles of the mesh in the order the BVH puts
+//! them, with their group and material, and the flattened nodes. Its header
+//! records a hash of the mesh file, the build parameters, the crease angle
+//! of generated normals and the subdivision levels, so a cache is only used
+//! for the exact file and settings it was built with. All numbers are
+//! little-endian.
+
+use {
+    crate::{
+        core::{
+            bvh::{BVHNode, MAX_BVH_DEPTH},
+            BVHBuildMethod, BVHTree, BoundingBox, Mesh, TriangleIndices,
+        },
+        utils::Vector3,
+    },
+    std::{fs, io},
+};
+
+const CACHE_MAGIC: &[u8; 8] = b"RTBVH\0\0\0";
+const CACHE_VERSION: u32 = 5;
+const CACHE_EXTENSION: &str = "bvh";
+/// Material index of triangles without material
+const NO_MATERIAL: u32 = u32::MAX;
+/// Nine vertex, normal and UV indices, a group and a material index
+const TRIANGLE_SIZE: usize = 11 * 4;
+/// Two corners, a start triangle index, a triangle count and a child index
+const NODE_SIZE: usize = 6 * 8 + 3 * 4;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// What a cached BVH depends on.
+#[derive(PartialEq, Eq)]
+pub(crate) struct BVHCacheKey {
+    file_hash: u64,
+    method: u8,
+    leaf_size: u64,
+    traversal_cost_bits: u64,
+    /// Generated normals depend on it, and triangles index them
+    crease_angle_bits: u64,
+    /// The cached triangles are those of the subdivided mesh
+    subdivision_levels: u64,
+}
+
+impl BVHCacheKey {
+    pub(crate) fn new(
+        file_contents: &[u8],
+        method: BVHBuildMethod,
+        leaf_size: usize,
+        traversal_cost: f64,
+        crease_angle: f64,
+        subdivision_levels: usize,
+    ) -> Self {
+        BVHCacheKey {
+            file_hash: calculate_fnv1a_hash(file_contents),
+            method: match method {
+                BVHBuildMethod::Midpoint => 0,
+                BVHBuildMethod::SurfaceAreaHeuristic => 1,
+            },
+            leaf_size: leaf_size as u64,
+            traversal_cost_bits: traversal_cost.to_bits(),
+            crease_angle_bits: crease_angle.to_bits(),
+            subdivision_levels: subdivision_levels as u64,
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash.
+fn calculate_fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The cache file of a mesh file: `model.obj` is cached in `model.obj.bvh`.
+pub(crate) fn calculate_cache_path(mesh_file: &str) -> String {
+    format!("{}.{}", mesh_file, CACHE_EXTENSION)
+}
+
+/// Read the triangles and nodes of a cached BVH of the mesh. Returns `None`
+/// if the file is missing, was built from another file or with other
+/// parameters, or is malformed.
+pub(crate) fn read_cache(
+    cache_file: &str,
+    key: &BVHCacheKey,
+    mesh: &Mesh,
+) -> Option<(Vec<TriangleIndices>, Vec<BVHNode>)> {
+    let bytes = fs::read(cache_file).ok()?;
+    let mut reader = CacheReader { bytes: &bytes };
+
+    if reader.read_bytes(CACHE_MAGIC.len())? != CACHE_MAGIC || reader.read_u32()? != CACHE_VERSION {
+        return None;
+    }
+    let cached_key = BVHCacheKey {
+        file_hash: reader.read_u64()?,
+        method: reader.read_bytes(1)?[0],
+        leaf_size: reader.read_u64()?,
+        traversal_cost_bits: reader.read_u64()?,
+        crease_angle_bits: reader.read_u64()?,
+        subdivision_levels: reader.read_u64()?,
+    };
+    if cached_key != *key {
+        return None;
+    }
+
+    let triangle_count = reader.read_u64()? as usize;
+    let node_count = reader.read_u64()? as usize;
+    if triangle_count != mesh.get_triangles().len() || node_count == 0 {
+        return None;
+    }
+    // Check the counts against the file size before allocating anything
+    let size = triangle_count
+        .checked_mul(TRIANGLE_SIZE)?
+        .checked_add(node_count.checked_mul(NODE_SIZE)?)?;
+    if size != reader.bytes.len() {
+        return None;
+    }
+
+    let mut triangles = Vec::with_capacity(triangle_count);
+    for _ in 0..triangle_count {
+        let mut read_index = |count: usize| {
+            let index = reader.read_u32()? as usize;
+            (index < count).then_some(index)
+        };
+        let mut read_indices =
+            |count: usize| Some((read_index(count)?, read_index(count)?, read_index(count)?));
+        let vertex_indices = read_indices(mesh.get_vertices().len())?;
+        let normal_indices = read_indices(mesh.get_normals().len())?;
+        let uv_indices = read_indices(mesh.get_uvs().len())?;
+        let group_index = read_index(mesh.get_groups().len())?;
+        let material_index = match reader.read_u32()? {
+            NO_MATERIAL => None,
+            index if (index as usize) < mesh.get_materials().len() => Some(index as usize),
+            _ => return None,
+        };
+        triangles.push(TriangleIndices::new(
+            vertex_indices,
+            normal_indices,
+            uv_indices,
+            group_index,
+            material_index,
+        ));
+    }
+
+    let mut nodes = Vec::with_capacity(node_count);
+    let mut depths = vec![1; node_count];
+    for index in 0..node_count {
+        let min = Vector3::new(reader.read_f64()?, reader.read_f64()?, reader.read_f64()?);
+        let max = Vector3::new(reader.read_f64()?, reader.read_f64()?, reader.read_f64()?);
+        let node = BVHNode {
+            bounding_box: BoundingBox::new(min, max),
+            start_triangle_index: reader.read_u32()?,
+            triangle_count: reader.read_u32()?,
+            right_child_index: reader.read_u32()?,
+        };
+
+        // Traversal trusts the tree, so check that it cannot index out of
+        // bounds, nor overflow the traversal stack
+        if depths[index] > MAX_BVH_DEPTH {
+            return None;
+        }
+        if node.is_leaf() {
+            if node.start_triangle_index as usize + node.triangle_count as usize > triangle_count {
+                return None;
+            }
+        } else {
+            let right_child_index = node.right_child_index as usize;
+            if right_child_index <= index + 1 || right_child_index >= node_count {
+                return None;
+            }
+            for child_index in [index + 1, right_child_index] {
+                depths[child_index] = depths[child_index].max(depths[index] + 1);
+            }
+        }
+        nodes.push(node);
+    }
+
+    reader.bytes.is_empty().then_some((triangles, nodes))
+}
+
+/// Write a BVH to a cache file. The file is written under a temporary name,
+/// then renamed, so that concurrent renders never read a partial file.
+pub(crate) fn write_cache(cache_file: &str, key: &BVHCacheKey, bvh: &BVHTree) -> io::Result<()> {
+    let mesh = bvh.get_mesh();
+    let nodes = bvh.get_nodes();
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(CACHE_MAGIC);
+    bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&key.file_hash.to_le_bytes());
+    bytes.push(key.method);
+    bytes.extend_from_slice(&key.leaf_size.to_le_bytes());
+    bytes.extend_from_slice(&key.traversal_cost_bits.to_le_bytes());
+    bytes.extend_from_slice(&key.crease_angle_bits.to_le_bytes());
+    bytes.extend_from_slice(&key.subdivision_levels.to_le_bytes());
+    bytes.extend_from_slice(&(mesh.get_triangles().len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&(nodes.len() as u64).to_le_bytes());
+
+    for triangle in mesh.get_triangles() {
+        let (a, b, c) = triangle.get_vertex_indices();
+        let (d, e, f) = triangle.get_normal_indices();
+        let (g, h, i) = triangle.get_uv_indices();
+        for index in [a, b, c, d, e, f, g, h, i, triangle.get_group_index()] {
+            let index = u32::try_from(index)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "mesh too large"))?;
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+        let material_index = match triangle.get_material_index() {
+            Some(index) => u32::try_from(index)
+                .ok()
+                .filter(|index| *index != NO_MATERIAL)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "mesh too large"))?,
+            None => NO_MATERIAL,
+        };
+        bytes.extend_from_slice(&material_index.to_le_bytes());
+    }
+
+    for node in nodes {
+        for corner in [node.bounding_box.get_min(), node.bounding_box.get_max()] {
+            for axis in 0..3 {
+                bytes.extend_from_slice(&corner[axis].to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&node.start_triangle_index.to_le_bytes());
+        bytes.extend_from_slice(&node.triangle_count.to_le_bytes());
+        bytes.extend_from_slice(&node.right_child_index.to_le_bytes());
+    }
+
+    let temporary_file = format!("{}.{}.tmp", cache_file, std::process::id());
+    fs::write(&temporary_file, &bytes)?;
+    fs::rename(&temporary_file, cache_file).inspect_err(|_| {
+        let _ = fs::remove_file(&temporary_file);
+    })
+}
+
+/// Reads numbers from the front of a byte slice.
+struct CacheReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl CacheReader<'_> {
+    fn read_bytes(&mut self, count: usize) -> Option<&[u8]> {
+        if self.bytes.len() < count {
+            return None;
+        }
+        let (read, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Some(read)
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.read_bytes(4)?.try_into().ok()?))
+    }
+
+    fn read_u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.read_bytes(8)?.try_into().ok()?))
+    }
+
+    fn read_f64(&mut self) -> Option<f64> {
+        Some(f64::from_le_bytes(self.read_bytes(8)?.try_into().ok()?))
+    }
+}
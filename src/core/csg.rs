@@ -0,0 +1,134 @@
+use crate::core::{BoundingBox, Intersectable, Intersection, Ray};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CsgOperation {
+    /// Points inside either object.
+    Union,
+    /// Points inside both objects.
+    Intersection,
+    /// Points inside the first object, but not inside the second one.
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// Constructive solid geometry: a solid combining two closed objects.
+///
+/// The surface of the result is made of parts of the surfaces of the two
+/// objects, and each part is shaded with the material of the object it comes
+/// from. The objects can themselves be `Csg` nodes.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<dyn Intersectable>,
+    right: Box<dyn Intersectable>,
+}
+
+impl Csg {
+    pub fn new(
+        operation: CsgOperation,
+        left: Box<dyn Intersectable>,
+        right: Box<dyn Intersectable>,
+    ) -> Self {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    pub fn union(left: Box<dyn Intersectable>, right: Box<dyn Intersectable>) -> Self {
+        Self::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Box<dyn Intersectable>, right: Box<dyn Intersectable>) -> Self {
+        Self::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Box<dyn Intersectable>, right: Box<dyn Intersectable>) -> Self {
+        Self::new(CsgOperation::Difference, left, right)
+    }
+
+    pub fn get_operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    /// Whether the ray starts inside an object, given its crossings.
+    fn starts_inside(crossings: &[Intersection]) -> bool {
+        crossings
+            .first()
+            .is_some_and(|crossing| !crossing.is_exterior())
+    }
+}
+
+impl Intersectable for Csg {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        self.intersect_all(ray).into_iter().next()
+    }
+
+    fn intersect_all(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let left_crossings = self.left.intersect_all(ray);
+        let right_crossings = self.right.intersect_all(ray);
+
+        let mut inside_left = Self::starts_inside(&left_crossings);
+        let mut inside_right = Self::starts_inside(&right_crossings);
+        let mut inside = self.operation.contains(inside_left, inside_right);
+
+        let mut left_crossings = left_crossings.into_iter().peekable();
+        let mut right_crossings = right_crossings.into_iter().peekable();
+
+        // Walk along the ray through the crossings of both objects, keeping
+        // those where the ray enters or leaves the combined solid
+        let mut crossings = Vec::new();
+        loop {
+            let from_left = match (left_crossings.peek(), right_crossings.peek()) {
+                (Some(left), Some(right)) => left.get_distance() <= right.get_distance(),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+
+            let mut crossing = if from_left {
+                let crossing = left_crossings.next().unwrap();
+                inside_left = crossing.is_exterior();
+                crossing
+            } else {
+                let crossing = right_crossings.next().unwrap();
+                inside_right = crossing.is_exterior();
+                crossing
+            };
+
+            let now_inside = self.operation.contains(inside_left, inside_right);
+            if now_inside != inside {
+                inside = now_inside;
+                // The surface of a subtracted object faces the other way
+                if !from_left && self.operation == CsgOperation::Difference {
+                    crossing.flip();
+                }
+                crossings.push(crossing);
+            }
+        }
+
+        crossings
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        let left = self.left.calculate_bounding_box();
+        let right = self.right.calculate_bounding_box();
+        match self.operation {
+            CsgOperation::Union => Some(left?.calculate_union(&right?)),
+            CsgOperation::Intersection => match (left, right) {
+                (Some(left), Some(right)) => Some(left.calculate_intersection(&right)),
+                (bounding_box, None) | (None, bounding_box) => bounding_box,
+            },
+            CsgOperation::Difference => left,
+        }
+    }
+}
@@ -1,5 +1,62 @@
-use crate::core::{Intersection, Ray};
+use crate::{
+    core::{BoundingBox, Intersection, Mesh, Ray},
+    utils::RAY_OFFSET_EPSILON,
+};
 
 pub trait Intersectable: Sync + Send {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection>;
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>>;
+
+    /// A box containing the whole object, in world space, or `None` for
+    /// unbounded objects such as planes. Scenes use it to skip the objects
+    /// a ray cannot hit; objects without bounds are tested by every ray.
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        None
+    }
+
+    /// The surface of the object as a triangle mesh in world space, to export
+    /// it, or `None` for objects that cannot be exported.
+    fn calculate_mesh(&self) -> Option<Mesh> {
+        None
+    }
+
+    /// Whether the ray hits the object closer than `max_distance`. This is
+    /// what shadow rays need: implementations can stop at the first hit they
+    /// find, and skip computing normals and texture coordinates.
+    ///
+    /// The default implementation calls `intersect`.
+    fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        self.intersect(ray)
+            .is_some_and(|intersection| intersection.get_distance() < max_distance)
+    }
+
+    /// Every point where the ray crosses the surface, sorted by distance.
+    /// Crossings into the object are exterior, crossings out of it are not,
+    /// so that consecutive crossings bound the intervals of the ray inside
+    /// the object. This is used by constructive solid geometry, and only
+    /// makes sense for closed surfaces.
+    ///
+    /// The default implementation repeatedly calls `intersect`, restarting
+    /// just past the previous crossing.
+    fn intersect_all(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut crossings: Vec<Intersection> = Vec::new();
+        let mut travelled_distance = 0.;
+        let mut next_ray = *ray;
+
+        while let Some(mut crossing) = self.intersect(&next_ray) {
+            travelled_distance += crossing.get_distance();
+            crossing.set_distance(travelled_distance);
+
+            next_ray = Ray::new_with_refractive_index(
+                *crossing.get_point(),
+                *ray.get_direction(),
+                ray.get_refractive_index(),
+            )
+            .add_offset();
+            travelled_distance += RAY_OFFSET_EPSILON;
+
+            crossings.push(crossing);
+        }
+
+        crossings
+    }
 }
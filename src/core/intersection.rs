@@ -11,6 +11,8 @@ pub struct Intersection<'a> {
     mapping_point: Option<Vector3>,
     object: Option<&'a dyn Object>,
     source_ray: Ray,
+    /// Material of the mesh triangle hit, if it has one
+    material_index: Option<usize>,
 }
 
 impl<'a> Intersection<'a> {
@@ -22,7 +24,7 @@ impl<'a> Intersection<'a> {
         mapping_point: Option<Vector3>,
         object: Option<&'a dyn Object>,
         source_ray: Ray,
-    ) -> Intersection {
+    ) -> Intersection<'a> {
         Intersection {
             point,
             normal,
@@ -31,6 +33,7 @@ impl<'a> Intersection<'a> {
             distance,
             object,
             source_ray,
+            material_index: None,
         }
     }
 
@@ -70,6 +73,28 @@ impl<'a> Intersection<'a> {
         self.object = Some(object);
     }
 
+    /// Index of the material of the mesh triangle hit, in
+    /// `Mesh::get_materials`. `None` for other objects, and for triangles
+    /// without material.
+    pub fn get_material_index(&self) -> Option<usize> {
+        self.material_index
+    }
+
+    pub(crate) fn set_material_index(&mut self, material_index: Option<usize>) {
+        self.material_index = material_index;
+    }
+
+    pub(crate) fn set_distance(&mut self, distance: f64) {
+        self.distance = distance;
+    }
+
+    /// Turn the intersection around, as if the surface was facing the other
+    /// way.
+    pub(crate) fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.exterior = !self.exterior;
+    }
+
     pub fn calculate_reflected_ray(&self) -> Ray {
         self.source_ray
             .calculate_reflected_ray(&self.point, &self.normal)
@@ -0,0 +1,346 @@
+//! Materials of Wavefront MTL files.
+//!
+//! Every `newmtl` block of a library is read, with its colors (`Kd`, `Ks`,
+//! `Ke`), specular exponent (`Ns`), refractive index (`Ni`), opacity (`d`
+//! or `Tr`), illumination model (`illum`) and texture maps (`map_Kd`,
+//! `map_Bump`, `map_d`). Other directives, and colors given as a spectral
+//! curve (`spectral`) or in CIE XYZ (`xyz`), are ignored with a warning.
+
+use {
+    crate::{
+        core::{IgnoredDirectives, Mesh, Texture},
+        error::{Error, Result},
+        utils::Vector3,
+    },
+    std::{fs, path::Path, result},
+};
+
+const DEFAULT_DIFFUSE_COLOR: Vector3 = Vector3::new(1., 1., 1.);
+const DEFAULT_SPECULAR_COLOR: Vector3 = Vector3::new(0., 0., 0.);
+const DEFAULT_SPECULAR_EXPONENT: f64 = 0.;
+const DEFAULT_REFRACTIVE_INDEX: f64 = 1.;
+const DEFAULT_OPACITY: f64 = 1.;
+const DEFAULT_EMISSION: Vector3 = Vector3::new(0., 0., 0.);
+const DEFAULT_ILLUMINATION_MODEL: u32 = 2;
+/// Illumination models with ray traced reflections, and no refraction
+const MIRROR_ILLUMINATION_MODELS: [u32; 2] = [3, 5];
+/// Illumination models with refraction
+const TRANSPARENT_ILLUMINATION_MODELS: [u32; 4] = [4, 6, 7, 9];
+/// Options of texture maps, with the most values each one takes
+const TEXTURE_OPTIONS: [(&str, usize); 12] = [
+    ("-blendu", 1),
+    ("-blendv", 1),
+    ("-boost", 1),
+    ("-bm", 1),
+    ("-cc", 1),
+    ("-clamp", 1),
+    ("-imfchan", 1),
+    ("-mm", 2),
+    ("-o", 3),
+    ("-s", 3),
+    ("-t", 3),
+    ("-texres", 1),
+];
+
+/// A material of an MTL file.
+///
+/// Meshes shade it as a mirror if its illumination model has reflections
+/// only (3 or 5), as transparent if it is partly dissolved or its model has
+/// refraction (4, 6, 7 or 9), and as a diffuse surface of its diffuse
+/// texture or color otherwise. Emissive materials glow with their emission
+/// color. Specular colors and exponents, bump maps and opacity maps are read
+/// but not rendered.
+#[derive(Clone)]
+pub struct Material {
+    name: String,
+    diffuse_color: Vector3,
+    specular_color: Vector3,
+    specular_exponent: f64,
+    refractive_index: f64,
+    opacity: f64,
+    emission: Vector3,
+    illumination_model: u32,
+    diffuse_texture: Option<Texture>,
+    bump_texture: Option<Texture>,
+    opacity_texture: Option<Texture>,
+}
+
+impl Material {
+    /// A material with the default values of MTL files: white, opaque and
+    /// diffuse.
+    pub fn new(name: &str) -> Self {
+        Material {
+            name: name.to_string(),
+            diffuse_color: DEFAULT_DIFFUSE_COLOR,
+            specular_color: DEFAULT_SPECULAR_COLOR,
+            specular_exponent: DEFAULT_SPECULAR_EXPONENT,
+            refractive_index: DEFAULT_REFRACTIVE_INDEX,
+            opacity: DEFAULT_OPACITY,
+            emission: DEFAULT_EMISSION,
+            illumination_model: DEFAULT_ILLUMINATION_MODEL,
+            diffuse_texture: None,
+            bump_texture: None,
+            opacity_texture: None,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_diffuse_color(&self) -> &Vector3 {
+        &self.diffuse_color
+    }
+
+    pub fn get_specular_color(&self) -> &Vector3 {
+        &self.specular_color
+    }
+
+    pub fn get_specular_exponent(&self) -> f64 {
+        self.specular_exponent
+    }
+
+    pub fn get_refractive_index(&self) -> f64 {
+        self.refractive_index
+    }
+
+    /// From 0 for a fully dissolved material to 1 for an opaque one.
+    pub fn get_opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    pub fn get_emission(&self) -> &Vector3 {
+        &self.emission
+    }
+
+    pub fn get_illumination_model(&self) -> u32 {
+        self.illumination_model
+    }
+
+    pub fn get_diffuse_texture(&self) -> Option<&Texture> {
+        self.diffuse_texture.as_ref()
+    }
+
+    pub fn get_bump_texture(&self) -> Option<&Texture> {
+        self.bump_texture.as_ref()
+    }
+
+    pub fn get_opacity_texture(&self) -> Option<&Texture> {
+        self.opacity_texture.as_ref()
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission.norm2() > 0.
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.opacity < 1. || TRANSPARENT_ILLUMINATION_MODELS.contains(&self.illumination_model)
+    }
+
+    pub fn is_mirror(&self) -> bool {
+        !self.is_transparent() && MIRROR_ILLUMINATION_MODELS.contains(&self.illumination_model)
+    }
+
+    /// The color of the material at texture coordinates, from its diffuse
+    /// texture if it has one.
+    pub fn calculate_color(&self, mapping_point: &Vector3) -> Vector3 {
+        match &self.diffuse_texture {
+            Some(texture) => texture.get_color(mapping_point),
+            None => self.diffuse_color,
+        }
+    }
+}
+
+/// The materials of one or more MTL files.
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+    warnings: Vec<String>,
+}
+
+impl MaterialLibrary {
+    /// Load every material of an MTL file. Texture maps are loaded relative
+    /// to the file; the ones that cannot be loaded are left out, with a
+    /// warning.
+    pub fn from_mtl_file(filename: &str) -> Result<Self> {
+        let contents = fs::read_to_string(filename).map_err(|err| Error::io(filename, err))?;
+
+        let mut materials: Vec<Material> = Vec::new();
+        let mut warnings = Vec::new();
+        let mut ignored_directives = IgnoredDirectives::default();
+
+        for (index, line) in contents.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() || parts[0].starts_with('#') {
+                continue;
+            }
+            let error =
+                |message: String| Error::parse(filename, Some(index + 1), Some(parts[0]), message);
+
+            if parts[0] == "newmtl" {
+                if parts.len() == 1 {
+                    return Err(error("missing material name".to_string()));
+                }
+                materials.push(Material::new(&parts[1..].join(" ")));
+                continue;
+            }
+            let material = materials
+                .last_mut()
+                .ok_or_else(|| error("no material defined yet (`newmtl`)".to_string()))?;
+
+            let parsed = match parts[0] {
+                "Kd" | "Ks" | "Ke" if matches!(parts.get(1), Some(&("spectral" | "xyz"))) => {
+                    ignored_directives.add(&parts[..2].join(" "), index + 1);
+                    Ok(())
+                }
+                "Kd" => parse_color(&parts).map(|color| material.diffuse_color = color),
+                "Ks" => parse_color(&parts).map(|color| material.specular_color = color),
+                "Ke" => parse_color(&parts).map(|color| material.emission = color),
+                "Ns" => parse_number(&parts).map(|value| material.specular_exponent = value),
+                "Ni" => parse_number(&parts).map(|value| material.refractive_index = value),
+                "d" => parse_number(&parts).map(|value| material.opacity = value),
+                "Tr" => parse_number(&parts).map(|value| material.opacity = 1. - value),
+                "illum" => parts
+                    .get(1)
+                    .and_then(|token| token.parse::<u32>().ok())
+                    .map(|model| material.illumination_model = model)
+                    .ok_or_else(|| format!("invalid illumination model line: {:?}", parts)),
+                "map_Kd" | "map_Bump" | "map_bump" | "bump" | "map_d" => {
+                    parse_texture_filename(&parts).map(|texture_filename| {
+                        let texture_file = Path::new(filename)
+                            .with_file_name(texture_filename)
+                            .display()
+                            .to_string();
+                        let texture = match Texture::from_img_file(&texture_file) {
+                            Ok(texture) => Some(texture),
+                            Err(err) => {
+                                warnings.push(format!(
+                                    "{}:{}: texture ignored, {}",
+                                    filename,
+                                    index + 1,
+                                    err
+                                ));
+                                None
+                            }
+                        };
+                        match parts[0] {
+                            "map_Kd" => material.diffuse_texture = texture,
+                            "map_d" => material.opacity_texture = texture,
+                            _ => material.bump_texture = texture,
+                        }
+                    })
+                }
+                directive => {
+                    ignored_directives.add(directive, index + 1);
+                    Ok(())
+                }
+            };
+            parsed.map_err(error)?;
+        }
+
+        warnings.extend(ignored_directives.into_warnings(filename));
+
+        Ok(MaterialLibrary {
+            materials,
+            warnings,
+        })
+    }
+
+    /// Load the libraries referenced by the `mtllib` directives of an OBJ
+    /// mesh. A material defined in several libraries keeps its first
+    /// definition.
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self> {
+        let mut library = MaterialLibrary {
+            materials: Vec::new(),
+            warnings: Vec::new(),
+        };
+        for filename in mesh.get_material_libraries() {
+            let other = Self::from_mtl_file(filename)?;
+            for material in other.materials {
+                if library.get_material(material.get_name()).is_none() {
+                    library.materials.push(material);
+                }
+            }
+            library.warnings.extend(other.warnings);
+        }
+        Ok(library)
+    }
+
+    pub fn get_materials(&self) -> &Vec<Material> {
+        &self.materials
+    }
+
+    pub fn get_material(&self, name: &str) -> Option<&Material> {
+        self.materials
+            .iter()
+            .find(|material| material.get_name() == name)
+    }
+
+    /// The diffuse texture of the first material that has one, to texture a
+    /// whole mesh with.
+    pub fn get_diffuse_texture(&self) -> Option<&Texture> {
+        self.materials
+            .iter()
+            .find_map(|material| material.get_diffuse_texture())
+    }
+
+    /// Problems found while loading the libraries that did not prevent them
+    /// from loading, such as unsupported directives or missing textures.
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+}
+
+fn parse_number(tokens: &[&str]) -> result::Result<f64, String> {
+    // `d -halo 0.5` puts an option before the value
+    let token = match tokens.len() {
+        1 => return Err(format!("missing value after `{}`", tokens[0])),
+        _ => tokens[tokens.len() - 1],
+    };
+    token
+        .parse::<f64>()
+        .map_err(|_| format!("expected a number, found `{}`", token))
+}
+
+/// Parse an RGB color, or a single value for a gray.
+fn parse_color(tokens: &[&str]) -> result::Result<Vector3, String> {
+    let components = tokens[1..]
+        .iter()
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|_| format!("expected an RGB color, found `{}`", tokens[1..].join(" ")))
+        })
+        .collect::<result::Result<Vec<f64>, String>>()?;
+    match components[..] {
+        [gray] => Ok(Vector3::new(gray, gray, gray)),
+        [r, g, b] => Ok(Vector3::new(r, g, b)),
+        _ => Err(format!("invalid color line: {:?}", tokens)),
+    }
+}
+
+/// The filename of a texture map, after its options. Filenames may contain
+/// spaces.
+fn parse_texture_filename(tokens: &[&str]) -> result::Result<String, String> {
+    let mut index = 1;
+    while let Some(token) = tokens.get(index).filter(|token| token.starts_with('-')) {
+        let Some((_, max_values)) = TEXTURE_OPTIONS.iter().find(|(option, _)| option == token)
+        else {
+            return Err(format!("unknown texture option `{}`", token));
+        };
+        index += 1;
+        // Options take up to their number of values, and at least one
+        let mut values = 0;
+        while values < *max_values
+            && index < tokens.len() - 1
+            && (values == 0 || tokens[index].parse::<f64>().is_ok())
+        {
+            index += 1;
+            values += 1;
+        }
+    }
+
+    match tokens.get(index..).filter(|rest| !rest.is_empty()) {
+        Some(rest) => Ok(rest.join(" ")),
+        None => Err(format!("missing texture file after `{}`", tokens[0])),
+    }
+}
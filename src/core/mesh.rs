@@ -1,19 +1,172 @@
 use {
     crate::{
-        core::{Intersectable, Intersection, Ray},
-        utils::{calculate_rotation_matrix, Vector3, ENABLE_NORMAL_MAPPING, MESH_EPSILON},
+        core::{BoundingBox, Intersectable, Intersection, Ray},
+        error::{Error, Result},
+        io::{ply, stl},
+        utils::{calculate_rotation_matrix, Quaternion, Transform, Vector3, MESH_EPSILON},
+    },
+    std::{
+        collections::HashMap,
+        f64::consts::PI,
+        fs,
+        io::{self, Read},
+        path::{Path, PathBuf},
+        result,
     },
-    std::fs,
 };
 
+/// Group of the triangles defined before any `o` or `g` directive.
+pub(crate) const DEFAULT_GROUP_NAME: &str = "default";
+/// Normals between faces further apart than this are not smoothed.
+pub(crate) const DEFAULT_CREASE_ANGLE: f64 = PI / 3.;
+/// Stands for a normal or texture coordinate missing from a file, until
+/// one is generated.
+const MISSING_INDEX: usize = usize::MAX;
+
+/// Mesh data of a file format that indexes the normals, texture coordinates
+/// and colors like the vertices. Attributes missing from the file are
+/// empty.
+#[derive(Default)]
+pub(crate) struct IndexedMeshData {
+    pub(crate) vertices: Vec<Vector3>,
+    pub(crate) normals: Vec<Vector3>,
+    pub(crate) uvs: Vec<Vector3>,
+    pub(crate) colors: Vec<Vector3>,
+    pub(crate) triangles: Vec<(usize, usize, usize)>,
+    /// Index in `groups` of each triangle
+    pub(crate) triangle_groups: Vec<usize>,
+    pub(crate) groups: Vec<String>,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Merges the vertices at the same position into one, which keeps the color
+/// of the first of them.
+#[derive(Default)]
+pub(crate) struct VertexWelder {
+    indices: HashMap<[u64; 3], usize>,
+    pub(crate) vertices: Vec<Vector3>,
+    pub(crate) colors: Vec<Vector3>,
+}
+
+impl VertexWelder {
+    /// The index of the welded vertex at the position of `vertex`.
+    pub(crate) fn weld(&mut self, vertex: Vector3, color: Option<&Vector3>) -> usize {
+        // Adding zero turns -0 into 0, so that both are welded
+        let key = [vertex.x() + 0., vertex.y() + 0., vertex.z() + 0.].map(f64::to_bits);
+        *self.indices.entry(key).or_insert_with(|| {
+            self.vertices.push(vertex);
+            self.colors.extend(color);
+            self.vertices.len() - 1
+        })
+    }
+}
+
+/// The unsupported directives of an OBJ or MTL file, reported once each,
+/// at the first line they appear on.
+#[derive(Default)]
+pub(crate) struct IgnoredDirectives {
+    /// Directive, first line and number of lines of each directive
+    directives: Vec<(String, usize, usize)>,
+}
+
+impl IgnoredDirectives {
+    pub(crate) fn add(&mut self, directive: &str, line: usize) {
+        match self
+            .directives
+            .iter_mut()
+            .find(|(ignored, _, _)| ignored == directive)
+        {
+            Some((_, _, count)) => *count += 1,
+            None => self.directives.push((directive.to_string(), line, 1)),
+        }
+    }
+
+    pub(crate) fn into_warnings(self, filename: &str) -> Vec<String> {
+        self.directives
+            .into_iter()
+            .map(|(directive, line, count)| {
+                format!(
+                    "{}:{}: `{}` is not supported, {} line{} ignored",
+                    filename,
+                    line,
+                    directive,
+                    count,
+                    if count > 1 { "s" } else { "" }
+                )
+            })
+            .collect()
+    }
+}
+
+enum MeshFormat {
+    Obj,
+    Ply,
+    Stl,
+}
+
+impl MeshFormat {
+    /// Guess the format of a file from its extension, or else from its
+    /// first bytes. Text files without a known header are taken as OBJ.
+    fn detect(filename: &str) -> Result<Self> {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("obj") => return Ok(MeshFormat::Obj),
+            Some("ply") => return Ok(MeshFormat::Ply),
+            Some("stl") => return Ok(MeshFormat::Stl),
+            _ => {}
+        }
+
+        let file = fs::File::open(filename).map_err(|err| Error::io(filename, err))?;
+        let file_size = file
+            .metadata()
+            .map_err(|err| Error::io(filename, err))?
+            .len();
+        let mut header = Vec::with_capacity(stl::BINARY_HEADER_SIZE);
+        file.take(stl::BINARY_HEADER_SIZE as u64)
+            .read_to_end(&mut header)
+            .map_err(|err| Error::io(filename, err))?;
+
+        Ok(if ply::is_ply_header(&header) {
+            MeshFormat::Ply
+        } else if stl::is_stl_header(&header, file_size) {
+            MeshFormat::Stl
+        } else {
+            MeshFormat::Obj
+        })
+    }
+}
+
+/// Vertex, texture coordinate and normal indices of a corner of an OBJ face.
+type FaceCorner = (usize, Option<usize>, Option<usize>);
+
 #[derive(Clone)]
 pub struct TriangleIndices {
     vertex_indices: (usize, usize, usize),
     normal_indices: (usize, usize, usize),
     uv_indices: (usize, usize, usize),
+    group_index: usize,
+    material_index: Option<usize>,
 }
 
 impl TriangleIndices {
+    pub(crate) fn new(
+        vertex_indices: (usize, usize, usize),
+        normal_indices: (usize, usize, usize),
+        uv_indices: (usize, usize, usize),
+        group_index: usize,
+        material_index: Option<usize>,
+    ) -> Self {
+        TriangleIndices {
+            vertex_indices,
+            normal_indices,
+            uv_indices,
+            group_index,
+            material_index,
+        }
+    }
+
     pub fn get_vertex_indices(&self) -> (usize, usize, usize) {
         self.vertex_indices
     }
@@ -25,6 +178,33 @@ impl TriangleIndices {
     pub fn get_uv_indices(&self) -> (usize, usize, usize) {
         self.uv_indices
     }
+
+    /// Index of the group of the triangle in `Mesh::get_groups`.
+    pub fn get_group_index(&self) -> usize {
+        self.group_index
+    }
+
+    /// Index of the material of the triangle in `Mesh::get_materials`, or
+    /// `None` for faces before any `usemtl` directive.
+    pub fn get_material_index(&self) -> Option<usize> {
+        self.material_index
+    }
+
+    pub(crate) fn reverse_winding(&mut self) {
+        let swap = |(a, b, c)| (a, c, b);
+        self.vertex_indices = swap(self.vertex_indices);
+        self.normal_indices = swap(self.normal_indices);
+        self.uv_indices = swap(self.uv_indices);
+    }
+}
+
+/// Where a ray crosses a triangle, before building an `Intersection`.
+struct TriangleHit {
+    distance: f64,
+    beta: f64,
+    gamma: f64,
+    normal: Vector3,
+    exterior: bool,
 }
 
 #[derive(Clone)]
@@ -32,7 +212,14 @@ pub struct Mesh {
     vertices: Vec<Vector3>,
     normals: Vec<Vector3>,
     uvs: Vec<Vector3>,
+    /// One per vertex, or none if the file has no vertex colors
+    colors: Vec<Vector3>,
     triangles: Vec<TriangleIndices>,
+    groups: Vec<String>,
+    materials: Vec<String>,
+    material_libraries: Vec<String>,
+    warnings: Vec<String>,
+    normal_mapping: bool,
 }
 
 impl Mesh {
@@ -48,8 +235,227 @@ impl Mesh {
         &self.triangles[index]
     }
 
-    pub fn swap_triangles(&mut self, i: usize, j: usize) {
-        self.triangles.swap(i, j);
+    pub fn get_normals(&self) -> &Vec<Vector3> {
+        &self.normals
+    }
+
+    pub fn get_uvs(&self) -> &Vec<Vector3> {
+        &self.uvs
+    }
+
+    /// Colors of the vertices, for formats that store them (PLY). Empty if
+    /// the file has none. They are kept for export, and not rendered.
+    pub fn get_vertex_colors(&self) -> &Vec<Vector3> {
+        &self.colors
+    }
+
+    /// Names of the `o` and `g` groups of the file, in order of appearance.
+    /// Groups with the same name are merged.
+    pub fn get_groups(&self) -> &Vec<String> {
+        &self.groups
+    }
+
+    /// Names of the materials used by `usemtl` directives, in order of
+    /// appearance.
+    pub fn get_materials(&self) -> &Vec<String> {
+        &self.materials
+    }
+
+    /// Paths of the material libraries referenced by `mtllib` directives,
+    /// relative to the working directory. See `MaterialLibrary::from_mesh`.
+    pub fn get_material_libraries(&self) -> &Vec<String> {
+        &self.material_libraries
+    }
+
+    /// Problems found while loading the mesh that did not prevent it from
+    /// loading, such as unsupported directives.
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    /// Copy the triangles of one group into a new mesh, with only the
+    /// vertices, normals and texture coordinates they use.
+    pub fn extract_group(&self, group_index: usize) -> Mesh {
+        let mut vertex_map = vec![None; self.vertices.len()];
+        let mut normal_map = vec![None; self.normals.len()];
+        let mut uv_map = vec![None; self.uvs.len()];
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+
+        let remap = |(a, b, c): (usize, usize, usize),
+                     map: &mut Vec<Option<usize>>,
+                     source: &Vec<Vector3>,
+                     target: &mut Vec<Vector3>| {
+            let mut remap_index = |index: usize| {
+                *map[index].get_or_insert_with(|| {
+                    target.push(source[index]);
+                    target.len() - 1
+                })
+            };
+            (remap_index(a), remap_index(b), remap_index(c))
+        };
+
+        let triangles = self
+            .triangles
+            .iter()
+            .filter(|triangle| triangle.group_index == group_index)
+            .map(|triangle| TriangleIndices {
+                vertex_indices: remap(
+                    triangle.vertex_indices,
+                    &mut vertex_map,
+                    &self.vertices,
+                    &mut vertices,
+                ),
+                normal_indices: remap(
+                    triangle.normal_indices,
+                    &mut normal_map,
+                    &self.normals,
+                    &mut normals,
+                ),
+                uv_indices: remap(triangle.uv_indices, &mut uv_map, &self.uvs, &mut uvs),
+                group_index: 0,
+                material_index: triangle.material_index,
+            })
+            .collect();
+
+        let mut colors = Vec::new();
+        if !self.colors.is_empty() {
+            colors.resize(vertices.len(), Vector3::new(0., 0., 0.));
+            for (index, new_index) in vertex_map.into_iter().enumerate() {
+                if let Some(new_index) = new_index {
+                    colors[new_index] = self.colors[index];
+                }
+            }
+        }
+
+        Mesh {
+            vertices,
+            normals,
+            uvs,
+            colors,
+            triangles,
+            groups: vec![self.groups[group_index].clone()],
+            materials: self.materials.clone(),
+            material_libraries: self.material_libraries.clone(),
+            warnings: Vec::new(),
+            normal_mapping: self.normal_mapping,
+        }
+    }
+
+    /// Replace the vertices, and their colors if the mesh has any.
+    pub(crate) fn set_vertices(&mut self, vertices: Vec<Vector3>, colors: Vec<Vector3>) {
+        self.vertices = vertices;
+        self.colors = colors;
+    }
+
+    /// Replace the triangles with the same ones in another order.
+    pub(crate) fn set_triangles(&mut self, triangles: Vec<TriangleIndices>) {
+        self.triangles = triangles;
+    }
+
+    /// Replace the normals and texture coordinates, along with triangles
+    /// indexing them.
+    pub(crate) fn set_normals_and_uvs(&mut self, normals: Vec<Vector3>, uvs: Vec<Vector3>) {
+        self.normals = normals;
+        self.uvs = uvs;
+    }
+
+    /// The triangles, to be reordered, along with the vertices they index.
+    pub(crate) fn get_vertices_and_triangles_mut(
+        &mut self,
+    ) -> (&[Vector3], &mut [TriangleIndices]) {
+        (&self.vertices, &mut self.triangles)
+    }
+
+    /// Whether to interpolate the vertex normals over the triangles. When
+    /// disabled, each triangle is shaded with its flat geometric normal.
+    pub fn set_normal_mapping(&mut self, normal_mapping: bool) -> &mut Self {
+        self.normal_mapping = normal_mapping;
+        self
+    }
+
+    /// Combine meshes into one. Groups and materials with the same name are
+    /// merged. If some meshes have vertex colors, the vertices of the others
+    /// are white.
+    pub fn merge(meshes: &[Mesh]) -> Mesh {
+        let mut merged = Mesh {
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            colors: Vec::new(),
+            triangles: Vec::new(),
+            groups: Vec::new(),
+            materials: Vec::new(),
+            material_libraries: Vec::new(),
+            warnings: Vec::new(),
+            normal_mapping: true,
+        };
+        let has_colors = meshes.iter().any(|mesh| !mesh.colors.is_empty());
+
+        for mesh in meshes {
+            let vertex_offset = merged.vertices.len();
+            let normal_offset = merged.normals.len();
+            let uv_offset = merged.uvs.len();
+            let group_indices: Vec<usize> = mesh
+                .groups
+                .iter()
+                .map(|group| Self::find_or_push(&mut merged.groups, group))
+                .collect();
+            let material_indices: Vec<usize> = mesh
+                .materials
+                .iter()
+                .map(|material| Self::find_or_push(&mut merged.materials, material))
+                .collect();
+            for library in &mesh.material_libraries {
+                Self::find_or_push(&mut merged.material_libraries, library);
+            }
+
+            merged.vertices.extend_from_slice(&mesh.vertices);
+            merged.normals.extend_from_slice(&mesh.normals);
+            merged.uvs.extend_from_slice(&mesh.uvs);
+            if has_colors {
+                match mesh.colors.is_empty() {
+                    true => merged
+                        .colors
+                        .resize(merged.vertices.len(), Vector3::new(1., 1., 1.)),
+                    false => merged.colors.extend_from_slice(&mesh.colors),
+                }
+            }
+
+            let offset = |(a, b, c): (usize, usize, usize), offset: usize| {
+                (a + offset, b + offset, c + offset)
+            };
+            merged
+                .triangles
+                .extend(mesh.triangles.iter().map(|triangle| TriangleIndices {
+                    vertex_indices: offset(triangle.vertex_indices, vertex_offset),
+                    normal_indices: offset(triangle.normal_indices, normal_offset),
+                    uv_indices: offset(triangle.uv_indices, uv_offset),
+                    group_index: group_indices[triangle.group_index],
+                    material_index: triangle.material_index.map(|index| material_indices[index]),
+                }));
+        }
+
+        merged
+    }
+
+    /// Rename every group, to keep them apart when merging meshes.
+    pub(crate) fn prefix_groups(&mut self, prefix: &str) {
+        for group in self.groups.iter_mut() {
+            *group = format!("{}{}", prefix, group);
+        }
+    }
+
+    /// Index of a name in a list, added at the end if missing.
+    fn find_or_push(names: &mut Vec<String>, name: &str) -> usize {
+        match names.iter().position(|other| other == name) {
+            Some(index) => index,
+            None => {
+                names.push(name.to_string());
+                names.len() - 1
+            }
+        }
     }
 }
 
@@ -72,6 +478,16 @@ impl Mesh {
         self
     }
 
+    pub fn rotate_quaternion(&mut self, rotation: &Quaternion) -> &mut Self {
+        for vertex in self.vertices.iter_mut() {
+            *vertex = rotation.rotate_vector(vertex);
+        }
+        for normal in self.normals.iter_mut() {
+            *normal = rotation.rotate_vector(normal);
+        }
+        self
+    }
+
     pub fn scale(&mut self, scale: f64) -> &mut Self {
         for vertex in self.vertices.iter_mut() {
             *vertex *= scale;
@@ -79,6 +495,37 @@ impl Mesh {
         self
     }
 
+    /// Replace the normals of the mesh with generated ones, smoothed across
+    /// the whole mesh up to the crease angle, in radians.
+    pub fn smooth_normals(&mut self, crease_angle: f64) -> &mut Self {
+        self.normals.clear();
+        for triangle in &mut self.triangles {
+            triangle.normal_indices = (MISSING_INDEX, MISSING_INDEX, MISSING_INDEX);
+        }
+        let smoothing_groups = vec![Some(0); self.triangles.len()];
+        self.generate_missing_normals(&smoothing_groups, crease_angle);
+        self
+    }
+
+    /// Apply any affine transform, including non-uniform scales and shears.
+    /// Normals are transformed by the inverse transpose, so that they stay
+    /// perpendicular to the surface.
+    pub fn transform(&mut self, transform: &Transform) -> &mut Self {
+        for vertex in self.vertices.iter_mut() {
+            *vertex = transform.transform_point(vertex);
+        }
+        for normal in self.normals.iter_mut() {
+            *normal = transform.transform_normal(normal);
+        }
+        // A mirroring transform turns the triangles inside out
+        if transform.get_matrix().calculate_linear().determinant() < 0. {
+            for triangle in self.triangles.iter_mut() {
+                triangle.reverse_winding();
+            }
+        }
+        self
+    }
+
     pub fn calculate_triangle_center(&self, triangle: &TriangleIndices) -> Vector3 {
         let vertex_indices = triangle.get_vertex_indices();
         let a = self.vertices[vertex_indices.0];
@@ -87,12 +534,76 @@ impl Mesh {
         (a + b + c) / 3.
     }
 
+    /// Distance along the ray to the triangle, with the barycentric
+    /// coordinates of the hit point, if the ray crosses it.
+    fn intersect_triangle(&self, ray: &Ray, triangle: &TriangleIndices) -> Option<TriangleHit> {
+        let u = *ray.get_direction();
+        let o = *ray.get_origin();
+
+        let vertex_indices = triangle.get_vertex_indices();
+
+        let a = self.vertices[vertex_indices.0];
+        let b = self.vertices[vertex_indices.1];
+        let c = self.vertices[vertex_indices.2];
+
+        let e1 = b - a;
+        let e2 = c - a;
+        let n = e1.cross(&e2);
+        let u_dot_n = u.dot(&n);
+        if u_dot_n.abs() < MESH_EPSILON {
+            return None;
+        }
+
+        let a_o = a - o;
+        let a_o_x_u = a_o.cross(&u);
+
+        let beta = e2.dot(&a_o_x_u) / u_dot_n;
+        if !(0. ..=1.).contains(&beta) {
+            return None;
+        }
+
+        let gamma = -e1.dot(&a_o_x_u) / u_dot_n;
+        if gamma < 0. || gamma + beta > 1. {
+            return None;
+        }
+
+        let t = a_o.dot(&n) / u_dot_n;
+        if t <= MESH_EPSILON {
+            return None;
+        }
+
+        Some(TriangleHit {
+            distance: t,
+            beta,
+            gamma,
+            normal: n,
+            exterior: u_dot_n < 0.,
+        })
+    }
+
+    /// Whether the ray hits one of the triangles in the range closer than
+    /// `max_distance`.
+    pub fn occluded_part(
+        &self,
+        ray: &Ray,
+        start_triangle_index: usize,
+        end_triangle_index: usize,
+        max_distance: f64,
+    ) -> bool {
+        self.triangles[start_triangle_index..end_triangle_index]
+            .iter()
+            .any(|triangle| {
+                self.intersect_triangle(ray, triangle)
+                    .is_some_and(|hit| hit.distance < max_distance)
+            })
+    }
+
     pub fn intersect_part(
         &self,
         ray: &Ray,
         start_triangle_index: usize,
         end_triangle_index: usize,
-    ) -> Option<Intersection> {
+    ) -> Option<Intersection<'_>> {
         let mut closest_distance = f64::INFINITY;
         let mut closest_normal = Vector3::new(0., 0., 0.);
         let mut closest_triangle = &self.triangles[0];
@@ -101,46 +612,18 @@ impl Mesh {
         let mut closest_gamma = 0.;
         let mut closest_exterior = true;
         for triangle in &self.triangles[start_triangle_index..end_triangle_index] {
-            let u = *ray.get_direction();
-            let o = *ray.get_origin();
-
-            let vertex_indices = triangle.get_vertex_indices();
-
-            let a = self.vertices[vertex_indices.0];
-            let b = self.vertices[vertex_indices.1];
-            let c = self.vertices[vertex_indices.2];
-
-            let e1 = b - a;
-            let e2 = c - a;
-            let n = e1.cross(&e2);
-            let u_dot_n = u.dot(&n);
-            if u_dot_n.abs() < MESH_EPSILON {
-                continue;
-            }
-
-            let a_o = a - o;
-            let a_o_x_u = a_o.cross(&u);
-
-            let beta = e2.dot(&a_o_x_u) / u_dot_n;
-            if beta < 0. || beta > 1. {
-                continue;
-            }
-
-            let gamma = -e1.dot(&a_o_x_u) / u_dot_n;
-            if gamma < 0. || gamma + beta > 1. {
+            let Some(hit) = self.intersect_triangle(ray, triangle) else {
                 continue;
-            }
-
-            let t = a_o.dot(&n) / u_dot_n;
+            };
 
-            if t > MESH_EPSILON && t < closest_distance {
-                closest_distance = t;
+            if hit.distance < closest_distance {
+                closest_distance = hit.distance;
                 closest_triangle = triangle;
-                closest_alpha = 1. - beta - gamma;
-                closest_beta = beta;
-                closest_gamma = gamma;
-                closest_exterior = u_dot_n < 0.;
-                closest_normal = n;
+                closest_alpha = 1. - hit.beta - hit.gamma;
+                closest_beta = hit.beta;
+                closest_gamma = hit.gamma;
+                closest_exterior = hit.exterior;
+                closest_normal = hit.normal;
             }
         }
 
@@ -149,7 +632,7 @@ impl Mesh {
         } else {
             let distance = closest_distance;
             let point = *ray.get_origin() + *ray.get_direction() * distance;
-            let normal = if ENABLE_NORMAL_MAPPING {
+            let normal = if self.normal_mapping {
                 let normal_indices = closest_triangle.get_normal_indices();
 
                 let normal_a = self.normals[normal_indices.0];
@@ -174,125 +657,610 @@ impl Mesh {
             let map_y = (mapping_point.y() % 1.).abs();
             let map_point = Vector3::new(map_x, map_y, 0.);
 
-            Some(Intersection::new(
+            let mut intersection = Intersection::new(
                 point,
                 normal,
                 distance,
                 closest_exterior,
                 Some(map_point),
                 None,
-                ray.clone(),
-            ))
+                *ray,
+            );
+            intersection.set_material_index(closest_triangle.get_material_index());
+            Some(intersection)
         }
     }
 }
 
 impl Intersectable for Mesh {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
         self.intersect_part(ray, 0, self.triangles.len())
     }
+
+    fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        self.occluded_part(ray, 0, self.triangles.len(), max_distance)
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new_from_mesh(self))
+    }
 }
 
 impl Mesh {
-    pub fn from_obj_file(filename: &str) -> Mesh {
+    /// Load a mesh from an OBJ, PLY or STL file. The format is given by the
+    /// extension of the file, or detected from its first bytes if the
+    /// extension is not known. Files without normals get generated ones, as
+    /// described in `Mesh::from_obj_file_with_crease_angle`.
+    pub fn from_file(filename: &str) -> Result<Mesh> {
+        Self::from_file_with_crease_angle(filename, DEFAULT_CREASE_ANGLE)
+    }
+
+    pub fn from_file_with_crease_angle(filename: &str, crease_angle: f64) -> Result<Mesh> {
+        match MeshFormat::detect(filename)? {
+            MeshFormat::Obj => Self::from_obj_file_with_crease_angle(filename, crease_angle),
+            MeshFormat::Ply => ply::load_ply_file(filename, crease_angle),
+            MeshFormat::Stl => stl::load_stl_file(filename, crease_angle),
+        }
+    }
+
+    /// Write the mesh to an OBJ or PLY file, depending on the extension of
+    /// the file.
+    pub fn write_file(&self, filename: &str) -> Result<()> {
+        let extension = Path::new(filename)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("obj") => self.write_obj_file(filename),
+            Some("ply") => ply::write_ply_file(self, filename),
+            _ => Err(Error::io(
+                filename,
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "unsupported mesh format, expected an .obj or .ply file",
+                ),
+            )),
+        }
+    }
+
+    /// Build a mesh from a format that indexes normals, texture coordinates
+    /// and colors like the vertices, generating the missing normals.
+    pub(crate) fn from_indexed_data(data: IndexedMeshData, crease_angle: f64) -> Mesh {
+        let triangles = data
+            .triangles
+            .iter()
+            .zip(&data.triangle_groups)
+            .map(|(vertex_indices, group_index)| {
+                let optional_indices = |present: bool| match present {
+                    true => *vertex_indices,
+                    false => (MISSING_INDEX, MISSING_INDEX, MISSING_INDEX),
+                };
+                TriangleIndices {
+                    vertex_indices: *vertex_indices,
+                    normal_indices: optional_indices(!data.normals.is_empty()),
+                    uv_indices: optional_indices(!data.uvs.is_empty()),
+                    group_index: *group_index,
+                    material_index: None,
+                }
+            })
+            .collect();
+
+        let mut mesh = Mesh {
+            vertices: data.vertices,
+            normals: data.normals,
+            uvs: data.uvs,
+            colors: data.colors,
+            triangles,
+            groups: data.groups,
+            materials: Vec::new(),
+            material_libraries: Vec::new(),
+            warnings: data.warnings,
+            normal_mapping: true,
+        };
+        let smoothing_groups = vec![Some(0); mesh.triangles.len()];
+        mesh.generate_missing_normals(&smoothing_groups, crease_angle);
+        mesh.fill_missing_uvs();
+        mesh
+    }
+
+    /// Load a Wavefront OBJ file. Polygons are triangulated, and faces may
+    /// omit texture coordinates or normals (`v`, `v/vt`, `v//vn` or
+    /// `v/vt/vn`). Missing normals are generated (see
+    /// `Mesh::from_obj_file_with_crease_angle`), and missing texture
+    /// coordinates default to the corner of the texture. Negative indices
+    /// count back from the last element defined. Triangles keep the `o` or
+    /// `g` group they belong to (see `Mesh::extract_group`), and unsupported
+    /// directives are reported in `Mesh::get_warnings` instead of failing.
+    pub fn from_obj_file(filename: &str) -> Result<Mesh> {
+        Self::from_obj_file_with_crease_angle(filename, DEFAULT_CREASE_ANGLE)
+    }
+
+    /// Load a Wavefront OBJ file, generating the normals it lacks with the
+    /// given crease angle, in radians. Generated normals average the normals
+    /// of the faces around each vertex, weighted by their area, over the
+    /// faces of the same `s` smoothing group whose normal is within the
+    /// crease angle. Faces after `s off` are shaded flat.
+    pub fn from_obj_file_with_crease_angle(filename: &str, crease_angle: f64) -> Result<Mesh> {
         let mut vertices: Vec<Vector3> = Vec::new();
         let mut normals: Vec<Vector3> = Vec::new();
         let mut uvs: Vec<Vector3> = Vec::new();
         let mut triangles: Vec<TriangleIndices> = Vec::new();
+        let mut groups: Vec<String> = Vec::new();
+        let mut group_name = DEFAULT_GROUP_NAME.to_string();
+        let mut group_index = None;
+        // Smoothing group of each triangle, `None` for flat shading. Faces
+        // before any `s` directive are smoothed together.
+        let mut smoothing_groups: Vec<Option<u64>> = Vec::new();
+        let mut smoothing_group = Some(0);
+        let mut materials: Vec<String> = Vec::new();
+        let mut material_index = None;
+        let mut material_libraries: Vec<String> = Vec::new();
+        let mut ignored_directives = IgnoredDirectives::default();
 
-        let contents = fs::read_to_string(filename).expect("Failed to read file");
-        for line in contents.lines() {
+        let contents = fs::read_to_string(filename).map_err(|err| Error::io(filename, err))?;
+        for (index, line) in contents.lines().enumerate() {
             let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() == 0 {
+            if parts.is_empty() || parts[0].starts_with('#') {
                 continue;
             }
-            match parts[0] {
-                "v" => vertices.push(Self::parse_vertex(&parts)),
-                "vn" => normals.push(Self::parse_normal(&parts)),
-                "vt" => uvs.push(Self::parse_uv(&parts)),
-                "vp" => {} // vp is not supported
-                "f" => triangles.push(Self::parse_face(&parts)),
-                "mtllib" => {} // mtllib is managed elsewhere
-                "usemtl" => {} // usemtl is managed elsewhere
-                "s" => {}      // s is managed globally
-                "g" => {}      // g is not supported
-                "#" => {}      // comment
-                _ => {
-                    println!("Unknown line: {}", line);
+            let parsed = match parts[0] {
+                "v" => Self::parse_vertex(&parts).map(|vertex| vertices.push(vertex)),
+                "vn" => Self::parse_normal(&parts).map(|normal| normals.push(normal)),
+                "vt" => Self::parse_uv(&parts).map(|uv| uvs.push(uv)),
+                "f" => {
+                    Self::parse_face(&parts, vertices.len(), uvs.len(), normals.len()).map(|face| {
+                        // Groups are only created once they hold a face
+                        let group_index = *group_index.get_or_insert_with(|| {
+                            match groups.iter().position(|name| *name == group_name) {
+                                Some(group_index) => group_index,
+                                None => {
+                                    groups.push(group_name.clone());
+                                    groups.len() - 1
+                                }
+                            }
+                        });
+                        let positions: Vec<Vector3> =
+                            face.iter().map(|corner| vertices[corner.0]).collect();
+                        for (a, b, c) in Self::triangulate_polygon(&positions) {
+                            let (a, b, c) = (face[a], face[b], face[c]);
+                            triangles.push(TriangleIndices {
+                                vertex_indices: (a.0, b.0, c.0),
+                                normal_indices: (
+                                    a.2.unwrap_or(MISSING_INDEX),
+                                    b.2.unwrap_or(MISSING_INDEX),
+                                    c.2.unwrap_or(MISSING_INDEX),
+                                ),
+                                uv_indices: (
+                                    a.1.unwrap_or(MISSING_INDEX),
+                                    b.1.unwrap_or(MISSING_INDEX),
+                                    c.1.unwrap_or(MISSING_INDEX),
+                                ),
+                                group_index,
+                                material_index,
+                            });
+                            smoothing_groups.push(smoothing_group);
+                        }
+                    })
                 }
-            }
+                "o" | "g" => {
+                    group_name = match parts.len() {
+                        1 => DEFAULT_GROUP_NAME.to_string(),
+                        _ => parts[1..].join(" "),
+                    };
+                    group_index = None;
+                    Ok(())
+                }
+                "s" => Self::parse_smoothing_group(&parts).map(|group| smoothing_group = group),
+                "usemtl" => Self::parse_name(&parts).map(|name| {
+                    material_index = match materials.iter().position(|material| *material == name) {
+                        Some(index) => Some(index),
+                        None => {
+                            materials.push(name);
+                            Some(materials.len() - 1)
+                        }
+                    };
+                }),
+                "mtllib" => {
+                    // Library names are separated by spaces
+                    for library in &parts[1..] {
+                        let library = Path::new(filename)
+                            .with_file_name(library)
+                            .display()
+                            .to_string();
+                        if !material_libraries.contains(&library) {
+                            material_libraries.push(library);
+                        }
+                    }
+                    Ok(())
+                }
+                directive => {
+                    ignored_directives.add(directive, index + 1);
+                    Ok(())
+                }
+            };
+            parsed.map_err(|message| {
+                Error::parse(filename, Some(index + 1), Some(parts[0]), message)
+            })?;
         }
 
-        Mesh {
+        let mut mesh = Mesh {
             vertices,
             normals,
             uvs,
+            colors: Vec::new(),
             triangles,
+            groups,
+            materials,
+            material_libraries,
+            warnings: ignored_directives.into_warnings(filename),
+            normal_mapping: true,
+        };
+        mesh.generate_missing_normals(&smoothing_groups, crease_angle);
+        mesh.fill_missing_uvs();
+        Ok(mesh)
+    }
+
+    /// Write the mesh to a Wavefront OBJ file, with its normals, texture
+    /// coordinates, groups and `usemtl` materials. Material libraries are
+    /// referenced relative to the written file if they are next to it or
+    /// below it, and by their absolute path otherwise.
+    /// Triangles are sorted by material, then by group.
+    pub fn write_obj_file(&self, filename: &str) -> Result<()> {
+        let mut lines = Vec::new();
+
+        let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+        for library in &self.material_libraries {
+            let library = match Path::new(library).strip_prefix(directory) {
+                Ok(relative_library) => relative_library.to_path_buf(),
+                Err(_) => fs::canonicalize(library).unwrap_or_else(|_| PathBuf::from(library)),
+            };
+            lines.push(format!("mtllib {}", library.display()));
+        }
+        for vertex in &self.vertices {
+            lines.push(format!(
+                "v {:?} {:?} {:?}",
+                vertex.x(),
+                vertex.y(),
+                vertex.z()
+            ));
+        }
+        for uv in &self.uvs {
+            lines.push(format!("vt {:?} {:?}", uv.x(), uv.y()));
+        }
+        for normal in &self.normals {
+            lines.push(format!(
+                "vn {:?} {:?} {:?}",
+                normal.x(),
+                normal.y(),
+                normal.z()
+            ));
         }
+
+        // Faces before any `usemtl` have no material, so they come first
+        let mut triangles: Vec<&TriangleIndices> = self.triangles.iter().collect();
+        triangles.sort_by_key(|triangle| (triangle.material_index, triangle.group_index));
+        let mut group_index = None;
+        let mut material_index = None;
+        for triangle in triangles {
+            if group_index != Some(triangle.group_index) {
+                group_index = Some(triangle.group_index);
+                lines.push(format!("g {}", self.groups[triangle.group_index]));
+            }
+            if material_index != triangle.material_index {
+                material_index = triangle.material_index;
+                if let Some(index) = material_index {
+                    lines.push(format!("usemtl {}", self.materials[index]));
+                }
+            }
+            let (v, uv, n) = (
+                triangle.vertex_indices,
+                triangle.uv_indices,
+                triangle.normal_indices,
+            );
+            lines.push(format!(
+                "f {}/{}/{} {}/{}/{} {}/{}/{}",
+                v.0 + 1,
+                uv.0 + 1,
+                n.0 + 1,
+                v.1 + 1,
+                uv.1 + 1,
+                n.1 + 1,
+                v.2 + 1,
+                uv.2 + 1,
+                n.2 + 1
+            ));
+        }
+
+        lines.push(String::new());
+        fs::write(filename, lines.join("\n")).map_err(|err| Error::io(filename, err))
+    }
+
+    fn parse_f64(token: &str) -> result::Result<f64, String> {
+        token
+            .parse::<f64>()
+            .map_err(|_| format!("expected a number, found `{}`", token))
     }
 
-    fn parse_vertex(tokens: &Vec<&str>) -> Vector3 {
+    /// Parse an index referring to one of the `count` elements already
+    /// defined: 1-based if positive, relative to the last element if
+    /// negative.
+    fn parse_index(token: &str, count: usize) -> result::Result<usize, String> {
+        let index = match token.parse::<i64>() {
+            Ok(index) if index > 0 => index - 1,
+            Ok(index) if index < 0 => count as i64 + index,
+            _ => return Err(format!("expected a non-zero index, found `{}`", token)),
+        };
+        if index < 0 || index >= count as i64 {
+            return Err(format!(
+                "index {} is out of range, only {} elements are defined",
+                token, count
+            ));
+        }
+        Ok(index as usize)
+    }
+
+    fn parse_vertex(tokens: &[&str]) -> result::Result<Vector3, String> {
         if tokens.len() < 4 {
-            panic!("Invalid vertex line: {:?}", tokens);
+            return Err(format!("invalid vertex line: {:?}", tokens));
         }
-        let x = tokens[1].parse::<f64>().unwrap();
-        let y = tokens[2].parse::<f64>().unwrap();
-        let z = tokens[3].parse::<f64>().unwrap();
+        let x = Self::parse_f64(tokens[1])?;
+        let y = Self::parse_f64(tokens[2])?;
+        let z = Self::parse_f64(tokens[3])?;
 
         // the w component is optional, and not used in this context
-        let _w = if tokens.len() > 4 {
-            tokens[4].parse::<f64>().unwrap()
-        } else {
-            1.
-        };
-        Vector3::new(x, y, z)
+        if tokens.len() > 4 {
+            Self::parse_f64(tokens[4])?;
+        }
+        Ok(Vector3::new(x, y, z))
     }
 
-    fn parse_normal(tokens: &Vec<&str>) -> Vector3 {
+    fn parse_normal(tokens: &[&str]) -> result::Result<Vector3, String> {
         if tokens.len() < 4 {
-            panic!("Invalid normal line: {:?}", tokens);
+            return Err(format!("invalid normal line: {:?}", tokens));
         }
-        let x = tokens[1].parse::<f64>().unwrap();
-        let y = tokens[2].parse::<f64>().unwrap();
-        let z = tokens[3].parse::<f64>().unwrap();
-        Vector3::new(x, y, z).normalized()
+        let x = Self::parse_f64(tokens[1])?;
+        let y = Self::parse_f64(tokens[2])?;
+        let z = Self::parse_f64(tokens[3])?;
+        Ok(Vector3::new(x, y, z).normalized())
     }
 
-    fn parse_uv(tokens: &Vec<&str>) -> Vector3 {
+    fn parse_uv(tokens: &[&str]) -> result::Result<Vector3, String> {
         if tokens.len() < 2 {
-            panic!("Invalid uv line: {:?}", tokens);
+            return Err(format!("invalid uv line: {:?}", tokens));
         }
-        let u = tokens[1].parse::<f64>().unwrap();
+        let u = Self::parse_f64(tokens[1])?;
         let v = if tokens.len() > 2 {
-            tokens[2].parse::<f64>().unwrap()
-        } else {
-            0.
-        };
-        let _w = if tokens.len() > 3 {
-            tokens[3].parse::<f64>().unwrap()
+            Self::parse_f64(tokens[2])?
         } else {
             0.
         };
-        Vector3::new(u, v, 0.)
+        // the w component is optional, and not used in this context
+        if tokens.len() > 3 {
+            Self::parse_f64(tokens[3])?;
+        }
+        Ok(Vector3::new(u, v, 0.))
+    }
+
+    /// Parse the name following a directive, which may contain spaces.
+    fn parse_name(tokens: &[&str]) -> result::Result<String, String> {
+        match tokens.len() {
+            1 => Err(format!("missing name after `{}`", tokens[0])),
+            _ => Ok(tokens[1..].join(" ")),
+        }
+    }
+
+    /// Parse the smoothing group of an `s` directive, `None` for `off` or 0.
+    fn parse_smoothing_group(tokens: &[&str]) -> result::Result<Option<u64>, String> {
+        match tokens.get(1) {
+            Some(&"off") => Ok(None),
+            Some(token) => token
+                .parse::<u64>()
+                .map(|group| (group > 0).then_some(group))
+                .map_err(|_| format!("expected a smoothing group or `off`, found `{}`", token)),
+            None => Err(format!("invalid smoothing group line: {:?}", tokens)),
+        }
     }
 
-    fn parse_face(tokens: &Vec<&str>) -> TriangleIndices {
+    /// Parse the vertex, texture coordinate and normal indices of each
+    /// corner of a face.
+    fn parse_face(
+        tokens: &[&str],
+        vertex_count: usize,
+        uv_count: usize,
+        normal_count: usize,
+    ) -> result::Result<Vec<FaceCorner>, String> {
         if tokens.len() < 4 {
-            panic!("Invalid face line: {:?}", tokens);
+            return Err(format!("invalid face line: {:?}", tokens));
+        }
+        tokens[1..]
+            .iter()
+            .map(|token| {
+                let face_parts: Vec<&str> = token.split('/').collect();
+                let optional_index = |part: Option<&&str>, count| match part {
+                    None | Some(&"") => Ok(None),
+                    Some(part) => Self::parse_index(part, count).map(Some),
+                };
+                match face_parts.len() {
+                    1..=3 => Ok((
+                        Self::parse_index(face_parts[0], vertex_count)?,
+                        optional_index(face_parts.get(1), uv_count)?,
+                        optional_index(face_parts.get(2), normal_count)?,
+                    )),
+                    _ => Err(format!(
+                        "expected a `v`, `v/vt`, `v//vn` or `v/vt/vn` face vertex, found `{}`",
+                        token
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    /// Split a polygon into triangles by ear clipping, in the plane that it
+    /// covers the most. Returns the corners of each triangle, in the winding
+    /// order of the polygon. Falls back to a fan for degenerate polygons.
+    pub(crate) fn triangulate_polygon(positions: &[Vector3]) -> Vec<(usize, usize, usize)> {
+        let count = positions.len();
+        let mut triangles = Vec::with_capacity(count - 2);
+        if count == 3 {
+            triangles.push((0, 1, 2));
+            return triangles;
         }
-        let mut vertex_indices: [usize; 3] = [0, 0, 0];
-        let mut normal_indices: [usize; 3] = [0, 0, 0];
-        let mut uv_indices: [usize; 3] = [0, 0, 0];
-        for i in 0..3 {
-            let face_parts: Vec<&str> = tokens[i + 1].split('/').collect();
-            vertex_indices[i] = face_parts[0].parse::<usize>().unwrap() - 1;
-            uv_indices[i] = face_parts[1].parse::<usize>().unwrap() - 1;
-            normal_indices[i] = face_parts[2].parse::<usize>().unwrap() - 1;
+
+        // Newell's method, which is robust to concave polygons
+        let mut normal = Vector3::new(0., 0., 0.);
+        for (index, current) in positions.iter().enumerate() {
+            let next = positions[(index + 1) % count];
+            normal += current.cross(&next);
         }
-        TriangleIndices {
-            vertex_indices: (vertex_indices[0], vertex_indices[1], vertex_indices[2]),
-            normal_indices: (normal_indices[0], normal_indices[1], normal_indices[2]),
-            uv_indices: (uv_indices[0], uv_indices[1], uv_indices[2]),
+        let (u_axis, v_axis) = match (normal.x().abs(), normal.y().abs(), normal.z().abs()) {
+            (x, y, z) if x >= y && x >= z => (1, 2),
+            (_, y, z) if y >= z => (2, 0),
+            _ => (0, 1),
+        };
+        let orientation = normal[3 - u_axis - v_axis].signum();
+        let points: Vec<(f64, f64)> = positions
+            .iter()
+            .map(|position| (position[u_axis], position[v_axis] * orientation))
+            .collect();
+        let cross = |a: usize, b: usize, c: usize| {
+            let (ax, ay) = points[a];
+            let (bx, by) = points[b];
+            let (cx, cy) = points[c];
+            (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+        };
+
+        let mut remaining: Vec<usize> = (0..count).collect();
+        while remaining.len() > 3 {
+            let remaining_count = remaining.len();
+            let ear = (0..remaining_count).find(|index| {
+                let a = remaining[(index + remaining_count - 1) % remaining_count];
+                let b = remaining[*index];
+                let c = remaining[(index + 1) % remaining_count];
+                cross(a, b, c) > 0.
+                    && remaining.iter().all(|&other| {
+                        other == a
+                            || other == b
+                            || other == c
+                            || cross(a, b, other) < 0.
+                            || cross(b, c, other) < 0.
+                            || cross(c, a, other) < 0.
+                    })
+            });
+            let Some(index) = ear else {
+                break;
+            };
+            triangles.push((
+                remaining[(index + remaining_count - 1) % remaining_count],
+                remaining[index],
+                remaining[(index + 1) % remaining_count],
+            ));
+            remaining.remove(index);
+        }
+
+        for index in 1..remaining.len() - 1 {
+            triangles.push((remaining[0], remaining[index], remaining[index + 1]));
+        }
+        triangles
+    }
+
+    /// Generate the normals of the triangle corners that have none, as
+    /// described in `Mesh::from_obj_file_with_crease_angle`.
+    fn generate_missing_normals(&mut self, smoothing_groups: &[Option<u64>], crease_angle: f64) {
+        let is_missing = |triangle: &TriangleIndices| {
+            let (a, b, c) = triangle.normal_indices;
+            a == MISSING_INDEX || b == MISSING_INDEX || c == MISSING_INDEX
+        };
+        if !self.triangles.iter().any(is_missing) {
+            return;
+        }
+
+        // Not normalized, so that summing them weights them by their area
+        let face_normals: Vec<Vector3> = self
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let (a, b, c) = triangle.vertex_indices;
+                (self.vertices[b] - self.vertices[a]).cross(&(self.vertices[c] - self.vertices[a]))
+            })
+            .collect();
+        let mut vertex_triangles: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for (triangle_index, triangle) in self.triangles.iter().enumerate() {
+            let (a, b, c) = triangle.vertex_indices;
+            for vertex_index in [a, b, c] {
+                vertex_triangles[vertex_index].push(triangle_index);
+            }
+        }
+
+        let min_cos = crease_angle.cos();
+        // Corners with the same vertex and normal share it
+        let mut generated_normals: HashMap<(usize, [u64; 3]), usize> = HashMap::new();
+        for triangle_index in 0..self.triangles.len() {
+            if !is_missing(&self.triangles[triangle_index]) {
+                continue;
+            }
+            let face_normal = face_normals[triangle_index];
+            let unit_face_normal = Self::normalize_or_zero(face_normal);
+            let smoothing_group = smoothing_groups[triangle_index];
+
+            let (a, b, c) = self.triangles[triangle_index].vertex_indices;
+            let mut normal_indices = [0; 3];
+            for (corner, vertex_index) in [a, b, c].into_iter().enumerate() {
+                let normal = match smoothing_group {
+                    Some(_) => vertex_triangles[vertex_index]
+                        .iter()
+                        .filter(|other_index| {
+                            let other_normal = face_normals[**other_index];
+                            smoothing_groups[**other_index] == smoothing_group
+                                && unit_face_normal.dot(&Self::normalize_or_zero(other_normal))
+                                    >= min_cos
+                        })
+                        .fold(Vector3::new(0., 0., 0.), |sum, other_index| {
+                            sum + face_normals[*other_index]
+                        }),
+                    None => face_normal,
+                };
+                let normal = Self::normalize_or_zero(normal);
+                let key = (
+                    vertex_index,
+                    [normal.x(), normal.y(), normal.z()].map(f64::to_bits),
+                );
+                normal_indices[corner] = *generated_normals.entry(key).or_insert_with(|| {
+                    self.normals.push(normal);
+                    self.normals.len() - 1
+                });
+            }
+
+            let triangle = &mut self.triangles[triangle_index];
+            let (a, b, c) = &mut triangle.normal_indices;
+            for (index, generated_index) in [a, b, c].into_iter().zip(normal_indices) {
+                if *index == MISSING_INDEX {
+                    *index = generated_index;
+                }
+            }
+        }
+    }
+
+    /// Point the triangle corners without texture coordinates to a shared
+    /// default one, so that they are shaded with the corner of the texture.
+    fn fill_missing_uvs(&mut self) {
+        let mut default_uv_index = None;
+        for triangle in &mut self.triangles {
+            let (a, b, c) = &mut triangle.uv_indices;
+            for index in [a, b, c] {
+                if *index == MISSING_INDEX {
+                    *index = *default_uv_index.get_or_insert_with(|| {
+                        self.uvs.push(Vector3::new(0., 0., 0.));
+                        self.uvs.len() - 1
+                    });
+                }
+            }
+        }
+    }
+
+    fn normalize_or_zero(vector: Vector3) -> Vector3 {
+        if vector.norm2() > 0. {
+            vector.normalized()
+        } else {
+            vector
         }
     }
 }
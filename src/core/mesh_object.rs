@@ -1,6 +1,13 @@
-use crate::{
-    core::{BVHTree, Intersectable, Intersection, Mesh, Object, Ray, Texture},
-    utils::Vector3,
+use {
+    crate::{
+        core::{
+            BVHBuildMethod, BVHTree, BoundingBox, Intersectable, Intersection, Material,
+            MaterialLibrary, Mesh, Object, Ray, RenderSettings, Texture, DEFAULT_BVH_BUILD_METHOD,
+            DEFAULT_BVH_LEAF_SIZE, DEFAULT_BVH_TRAVERSAL_COST, DEFAULT_SUBDIVISION_LEVELS,
+        },
+        utils::{Quaternion, Transform, Vector3, GAMMA_CORRECTION},
+    },
+    std::sync::Arc,
 };
 
 const DEFAULT_OPAQUE: bool = false;
@@ -10,6 +17,14 @@ const DEFAULT_TRANSPARENT: bool = false;
 const DEFAULT_REFRACTIVE_INDEX: f64 = 1.;
 const DEFAULT_TEXTURE: Option<Texture> = None;
 
+/// A mesh placed in the scene, with its own material.
+///
+/// The mesh and its BVH are shared between the instances of a same mesh, and
+/// each instance has its own transform: rays are brought to the space of the
+/// mesh to be intersected.
+///
+/// Triangles whose `usemtl` material is in the object's material libraries
+/// are shaded with that material instead.
 pub struct MeshObject {
     opaque: bool,
     color: Vector3,
@@ -17,47 +32,131 @@ pub struct MeshObject {
     transparent: bool,
     refractive_index: f64,
     texture: Option<Texture>,
-    bvh: BVHTree,
+    /// For each material of the mesh, the part shaded with it, if defined
+    material_parts: Vec<Option<MaterialPart>>,
+    bvh: Arc<BVHTree>,
+    /// From the space of the mesh to the scene
+    transform: Transform,
+    inverse_transform: Transform,
+}
+
+enum MeshSource {
+    Mesh(Box<Mesh>),
+    Instance(Arc<BVHTree>),
 }
 
 pub struct MeshObjectBuilder {
-    mesh: Mesh,
+    source: MeshSource,
+    transform: Transform,
     opaque: bool,
     color: Vector3,
     mirror: bool,
     transparent: bool,
     refractive_index: f64,
     texture: Option<Texture>,
+    materials: Vec<Material>,
+    bvh_leaf_size: usize,
+    bvh_build_method: BVHBuildMethod,
+    bvh_traversal_cost: f64,
+    subdivision_levels: usize,
 }
 
 impl MeshObjectBuilder {
+    /// Build an object with its own copy of the mesh, and its own BVH.
     pub fn new(mesh: &Mesh) -> Self {
+        Self::new_from_source(MeshSource::Mesh(Box::new(mesh.clone())))
+    }
+
+    /// Build an instance of a mesh whose BVH has already been built, sharing
+    /// it with the other instances. The BVH of a `MeshObject` can be shared
+    /// with `MeshObject::get_bvh`.
+    pub fn new_instance(bvh: &Arc<BVHTree>) -> Self {
+        Self::new_from_source(MeshSource::Instance(Arc::clone(bvh)))
+    }
+
+    fn new_from_source(source: MeshSource) -> Self {
         MeshObjectBuilder {
-            mesh: mesh.clone(),
+            source,
+            transform: Transform::IDENTITY,
             opaque: DEFAULT_OPAQUE,
             color: DEFAULT_COLOR,
             mirror: DEFAULT_MIRROR,
             transparent: DEFAULT_TRANSPARENT,
             refractive_index: DEFAULT_REFRACTIVE_INDEX,
             texture: DEFAULT_TEXTURE,
+            materials: Vec::new(),
+            bvh_leaf_size: DEFAULT_BVH_LEAF_SIZE,
+            bvh_build_method: DEFAULT_BVH_BUILD_METHOD,
+            bvh_traversal_cost: DEFAULT_BVH_TRAVERSAL_COST,
+            subdivision_levels: DEFAULT_SUBDIVISION_LEVELS,
         }
     }
 
-    pub fn with_scale(&mut self, scale: f64) -> &mut Self {
-        self.mesh.scale(scale);
+    /// Apply the settings that affect how meshes are built: normal mapping,
+    /// subdivision and the BVH settings. Like `with_normal_mapping`,
+    /// `with_subdivision` and the `with_bvh_*` methods, this has no effect on
+    /// instances of an already built BVH.
+    pub fn with_settings(&mut self, settings: &RenderSettings) -> &mut Self {
+        self.with_normal_mapping(settings.is_normal_mapping_enabled())
+            .with_subdivision(settings.get_subdivision_levels())
+            .with_bvh_leaf_size(settings.get_bvh_leaf_size())
+            .with_bvh_build_method(settings.get_bvh_build_method())
+            .with_bvh_traversal_cost(settings.get_bvh_traversal_cost())
+    }
+
+    pub fn with_normal_mapping(&mut self, normal_mapping: bool) -> &mut Self {
+        if let MeshSource::Mesh(mesh) = &mut self.source {
+            mesh.set_normal_mapping(normal_mapping);
+        }
         self
     }
 
-    pub fn with_translation(&mut self, translation: Vector3) -> &mut Self {
-        self.mesh.translate(translation);
+    /// Smooth the mesh with `Mesh::subdivide` before its BVH is built. Like
+    /// `with_normal_mapping`, this has no effect on instances: subdivide
+    /// their mesh before building the shared BVH, for instance with
+    /// `RenderSettings::with_subdivision_levels`.
+    pub fn with_subdivision(&mut self, levels: usize) -> &mut Self {
+        self.subdivision_levels = levels;
         self
     }
 
-    pub fn with_rotation(&mut self, rotation: Vector3) -> &mut Self {
-        self.mesh.rotate(rotation);
+    pub fn with_bvh_leaf_size(&mut self, bvh_leaf_size: usize) -> &mut Self {
+        self.bvh_leaf_size = bvh_leaf_size;
+        self
+    }
+
+    pub fn with_bvh_build_method(&mut self, bvh_build_method: BVHBuildMethod) -> &mut Self {
+        self.bvh_build_method = bvh_build_method;
+        self
+    }
+
+    pub fn with_bvh_traversal_cost(&mut self, bvh_traversal_cost: f64) -> &mut Self {
+        self.bvh_traversal_cost = bvh_traversal_cost;
+        self
+    }
+
+    /// Transformations are applied in the order of the calls.
+    pub fn with_transform(&mut self, transform: &Transform) -> &mut Self {
+        self.transform = self.transform.then(transform);
         self
     }
 
+    pub fn with_scale(&mut self, scale: f64) -> &mut Self {
+        self.with_transform(&Transform::new_uniform_scale(scale))
+    }
+
+    pub fn with_translation(&mut self, translation: Vector3) -> &mut Self {
+        self.with_transform(&Transform::new_translation(translation))
+    }
+
+    pub fn with_rotation(&mut self, rotation: Vector3) -> &mut Self {
+        self.with_transform(&Transform::new_rotation(rotation))
+    }
+
+    pub fn with_quaternion_rotation(&mut self, rotation: &Quaternion) -> &mut Self {
+        self.with_transform(&Transform::new_quaternion_rotation(rotation))
+    }
+
     pub fn with_opaque(&mut self, opaque: bool) -> &mut Self {
         self.opaque = opaque;
         self
@@ -91,8 +190,48 @@ impl MeshObjectBuilder {
         self
     }
 
+    /// Shade the triangles of the materials defined in the library with
+    /// them. When several libraries define a material, the first one wins.
+    pub fn with_material_library(&mut self, library: &MaterialLibrary) -> &mut Self {
+        for material in library.get_materials() {
+            if !self
+                .materials
+                .iter()
+                .any(|other| other.get_name() == material.get_name())
+            {
+                self.materials.push(material.clone());
+            }
+        }
+        self
+    }
+
     pub fn build(self) -> MeshObject {
-        let bvh = BVHTree::new_from_mesh(self.mesh);
+        let bvh = match self.source {
+            MeshSource::Mesh(mut mesh) => {
+                mesh.subdivide(self.subdivision_levels);
+                Arc::new(BVHTree::new_from_mesh_with_method(
+                    *mesh,
+                    self.bvh_build_method,
+                    self.bvh_leaf_size,
+                    self.bvh_traversal_cost,
+                ))
+            }
+            MeshSource::Instance(bvh) => bvh,
+        };
+        let material_parts = bvh
+            .get_mesh()
+            .get_materials()
+            .iter()
+            .map(|name| {
+                self.materials
+                    .iter()
+                    .find(|material| material.get_name() == name)
+                    .map(|material| MaterialPart {
+                        material: material.clone(),
+                    })
+            })
+            .collect();
+
         MeshObject {
             opaque: self.opaque,
             color: self.color,
@@ -100,17 +239,72 @@ impl MeshObjectBuilder {
             transparent: self.transparent,
             refractive_index: self.refractive_index,
             texture: self.texture,
+            material_parts,
             bvh,
+            transform: self.transform,
+            inverse_transform: self.transform.inverse(),
         }
     }
 }
 
+impl MeshObject {
+    /// The BVH of the mesh, to build other instances of it with
+    /// `MeshObjectBuilder::new_instance`.
+    pub fn get_bvh(&self) -> &Arc<BVHTree> {
+        &self.bvh
+    }
+
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+}
+
 impl Intersectable for MeshObject {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
-        self.bvh.intersect(ray).and_then(|mut i| {
-            i.set_object(self);
-            Some(i)
-        })
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let mesh_ray = ray.calculate_transformed_ray(&self.inverse_transform);
+        let mesh_intersection = self.bvh.intersect(&mesh_ray)?;
+
+        let distance = mesh_intersection.get_distance();
+        let point = *ray.get_origin() + *ray.get_direction() * distance;
+        let normal = self
+            .transform
+            .transform_normal(mesh_intersection.get_normal());
+
+        let material_index = mesh_intersection.get_material_index();
+        let object: &dyn Object =
+            match material_index.and_then(|index| self.material_parts.get(index)) {
+                Some(Some(part)) => part,
+                _ => self,
+            };
+
+        let mut intersection = Intersection::new(
+            point,
+            normal,
+            distance,
+            mesh_intersection.is_exterior(),
+            Some(*mesh_intersection.get_mapping_point()),
+            Some(object),
+            *ray,
+        );
+        intersection.set_material_index(material_index);
+        Some(intersection)
+    }
+
+    fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        // Distances are preserved, as the direction is not normalized
+        let mesh_ray = ray.calculate_transformed_ray(&self.inverse_transform);
+        self.bvh.occluded(&mesh_ray, max_distance)
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        let mesh_bounding_box = self.bvh.calculate_bounding_box()?;
+        Some(mesh_bounding_box.calculate_transformed(&self.transform))
+    }
+
+    fn calculate_mesh(&self) -> Option<Mesh> {
+        let mut mesh = self.bvh.get_mesh().clone();
+        mesh.transform(&self.transform);
+        Some(mesh)
     }
 }
 
@@ -136,11 +330,8 @@ impl Object for MeshObject {
     }
 
     fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
-        if self.texture.is_some() {
-            self.texture
-                .as_ref()
-                .unwrap()
-                .get_color(intersection.get_mapping_point())
+        if let Some(texture) = &self.texture {
+            texture.get_color(intersection.get_mapping_point())
         } else {
             self.color
         }
@@ -154,3 +345,59 @@ impl Object for MeshObject {
         0.
     }
 }
+
+/// The triangles of a mesh object that use one material. Intersections of
+/// the mesh object point to it, like the children of a `Csg`.
+struct MaterialPart {
+    material: Material,
+}
+
+impl Intersectable for MaterialPart {
+    /// Only reached through the intersections of its mesh object.
+    fn intersect(&self, _ray: &Ray) -> Option<Intersection<'_>> {
+        None
+    }
+}
+
+impl Object for MaterialPart {
+    fn is_opaque(&self) -> bool {
+        !self.is_mirror() && !self.is_transparent()
+    }
+
+    fn is_mirror(&self) -> bool {
+        self.material.is_mirror()
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.material.is_transparent()
+    }
+
+    fn is_light_source(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn get_color(&self) -> &Vector3 {
+        self.material.get_diffuse_color()
+    }
+
+    fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
+        self.material
+            .calculate_color(intersection.get_mapping_point())
+    }
+
+    fn get_refractive_index(&self) -> f64 {
+        self.material.get_refractive_index()
+    }
+
+    fn get_light_intensity(&self) -> f64 {
+        match self.is_light_source() {
+            true => 255_f64.powf(1. / GAMMA_CORRECTION),
+            false => 0.,
+        }
+    }
+
+    /// An emission color of 1 renders as white.
+    fn calculate_emission(&self, _intersection: &Intersection) -> Vector3 {
+        *self.material.get_emission() * self.get_light_intensity()
+    }
+}
@@ -0,0 +1,419 @@
+//! Checks and repairs of the triangles of meshes.
+//!
+//! Loaders reject indices out of range, but files may still hold NaN
+//! vertices, degenerate or duplicate triangles that slow BVHs down, or
+//! triangles wound inconsistently, which turns parts of the surface inside
+//! out. Edges are found through vertex indices, so a mesh whose vertices are
+//! duplicated along seams has boundary edges there until it is repaired.
+
+use {
+    crate::{
+        core::{Mesh, TriangleIndices, VertexWelder},
+        utils::Vector3,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+    },
+};
+
+/// Triangles whose area is below this fraction of the square of their
+/// longest edge are degenerate.
+const DEGENERATE_AREA_RATIO: f64 = 1E-12;
+
+/// Problems found in a mesh by `Mesh::validate`. Vertices and triangles are
+/// given by their index in `Mesh::get_vertices` and `Mesh::get_triangles`,
+/// and edges by the indices of their vertices, in increasing order.
+pub struct MeshValidationReport {
+    triangle_count: usize,
+    invalid_vertices: Vec<usize>,
+    invalid_index_triangles: Vec<usize>,
+    degenerate_triangles: Vec<usize>,
+    duplicate_triangles: Vec<usize>,
+    non_manifold_edges: Vec<(usize, usize)>,
+    inconsistent_winding_edges: Vec<(usize, usize)>,
+    boundary_edge_count: usize,
+}
+
+/// Triangles along each edge, with whether they go along it from its lower
+/// vertex index to its higher one.
+type EdgeMap = HashMap<(usize, usize), Vec<(usize, bool)>>;
+
+impl Mesh {
+    /// Look for the problems a mesh can have. Triangles with invalid indices
+    /// are left out of the other checks, and triangles with NaN vertices out
+    /// of the degenerate check.
+    pub fn validate(&self) -> MeshValidationReport {
+        let vertices = self.get_vertices();
+        let invalid_vertices: Vec<usize> = vertices
+            .iter()
+            .enumerate()
+            .filter(|(_, vertex)| !is_finite(vertex))
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut invalid_index_triangles = Vec::new();
+        let mut degenerate_triangles = Vec::new();
+        let mut duplicate_triangles = Vec::new();
+        let mut vertex_sets = HashSet::new();
+        let mut valid_triangles = Vec::new();
+        for (index, triangle) in self.get_triangles().iter().enumerate() {
+            if !self.has_valid_indices(triangle) {
+                invalid_index_triangles.push(index);
+                continue;
+            }
+            let vertex_indices = triangle.get_vertex_indices();
+            if is_degenerate(vertices, vertex_indices) {
+                degenerate_triangles.push(index);
+            }
+            if !vertex_sets.insert(calculate_vertex_set(vertex_indices)) {
+                duplicate_triangles.push(index);
+            }
+            valid_triangles.push((index, vertex_indices));
+        }
+
+        let mut non_manifold_edges = Vec::new();
+        let mut inconsistent_winding_edges = Vec::new();
+        let mut boundary_edge_count = 0;
+        for (edge, triangles) in calculate_edges(&valid_triangles) {
+            match triangles[..] {
+                [_] => boundary_edge_count += 1,
+                [(_, forward), (_, other_forward)] => {
+                    if forward == other_forward {
+                        inconsistent_winding_edges.push(edge);
+                    }
+                }
+                _ => non_manifold_edges.push(edge),
+            }
+        }
+        non_manifold_edges.sort_unstable();
+        inconsistent_winding_edges.sort_unstable();
+
+        MeshValidationReport {
+            triangle_count: self.get_triangles().len(),
+            invalid_vertices,
+            invalid_index_triangles,
+            degenerate_triangles,
+            duplicate_triangles,
+            non_manifold_edges,
+            inconsistent_winding_edges,
+            boundary_edge_count,
+        }
+    }
+
+    /// Fix what can be fixed of the problems reported by `Mesh::validate`:
+    /// triangles with invalid indices or NaN vertices are dropped, vertices
+    /// at the same position are welded, degenerate and duplicate triangles
+    /// are dropped, and the triangles of each connected part are wound
+    /// consistently, facing outwards if the part is closed, with the normals
+    /// of the flipped triangles reversed. Vertices no triangle uses are
+    /// removed. Other vertex normals are kept: regenerate them with
+    /// `Mesh::smooth_normals` if they were wrong too.
+    pub fn repair(&mut self) -> &mut Self {
+        let vertices = self.get_vertices();
+        let colors = self.get_vertex_colors();
+
+        let mut welder = VertexWelder::default();
+        let mut vertex_sets = HashSet::new();
+        let mut triangles = Vec::new();
+        for triangle in self.get_triangles() {
+            let (a, b, c) = triangle.get_vertex_indices();
+            if !self.has_valid_indices(triangle)
+                || [a, b, c].iter().any(|index| !is_finite(&vertices[*index]))
+            {
+                continue;
+            }
+            let mut weld = |index: usize| welder.weld(vertices[index], colors.get(index));
+            let vertex_indices = (weld(a), weld(b), weld(c));
+
+            if is_degenerate(&welder.vertices, vertex_indices)
+                || !vertex_sets.insert(calculate_vertex_set(vertex_indices))
+            {
+                continue;
+            }
+            triangles.push(TriangleIndices::new(
+                vertex_indices,
+                triangle.get_normal_indices(),
+                triangle.get_uv_indices(),
+                triangle.get_group_index(),
+                triangle.get_material_index(),
+            ));
+        }
+
+        // Flipped triangles face the other way, and so must their normals,
+        // copied if triangles that are not flipped share them
+        let mut normals = self.get_normals().clone();
+        let mut reversed_normals: HashMap<usize, usize> = HashMap::new();
+        let flips = calculate_orientation(&welder.vertices, &triangles);
+        for (triangle, flip) in triangles.iter_mut().zip(flips) {
+            if !flip {
+                continue;
+            }
+            triangle.reverse_winding();
+            let mut reverse = |index: usize| {
+                *reversed_normals.entry(index).or_insert_with(|| {
+                    let normal = -normals[index];
+                    normals.push(normal);
+                    normals.len() - 1
+                })
+            };
+            let (a, b, c) = triangle.get_normal_indices();
+            *triangle = TriangleIndices::new(
+                triangle.get_vertex_indices(),
+                (reverse(a), reverse(b), reverse(c)),
+                triangle.get_uv_indices(),
+                triangle.get_group_index(),
+                triangle.get_material_index(),
+            );
+        }
+
+        // Vertices only used by dropped triangles may have been welded
+        let mut used = vec![false; welder.vertices.len()];
+        for triangle in &triangles {
+            let (a, b, c) = triangle.get_vertex_indices();
+            for index in [a, b, c] {
+                used[index] = true;
+            }
+        }
+        let mut new_indices = vec![0; welder.vertices.len()];
+        let mut new_vertices = Vec::new();
+        let mut new_colors = Vec::new();
+        for (index, vertex) in welder.vertices.iter().enumerate() {
+            if used[index] {
+                new_indices[index] = new_vertices.len();
+                new_vertices.push(*vertex);
+                if let Some(color) = welder.colors.get(index) {
+                    new_colors.push(*color);
+                }
+            }
+        }
+        let triangles = triangles
+            .into_iter()
+            .map(|triangle| {
+                let (a, b, c) = triangle.get_vertex_indices();
+                TriangleIndices::new(
+                    (new_indices[a], new_indices[b], new_indices[c]),
+                    triangle.get_normal_indices(),
+                    triangle.get_uv_indices(),
+                    triangle.get_group_index(),
+                    triangle.get_material_index(),
+                )
+            })
+            .collect();
+
+        let uvs = self.get_uvs().clone();
+        self.set_vertices(new_vertices, new_colors);
+        self.set_normals_and_uvs(normals, uvs);
+        self.set_triangles(triangles);
+        self
+    }
+
+    fn has_valid_indices(&self, triangle: &TriangleIndices) -> bool {
+        let in_range =
+            |(a, b, c): (usize, usize, usize), count: usize| a < count && b < count && c < count;
+        in_range(triangle.get_vertex_indices(), self.get_vertices().len())
+            && in_range(triangle.get_normal_indices(), self.get_normals().len())
+            && in_range(triangle.get_uv_indices(), self.get_uvs().len())
+            && triangle.get_group_index() < self.get_groups().len()
+            && triangle
+                .get_material_index()
+                .is_none_or(|index| index < self.get_materials().len())
+    }
+}
+
+impl MeshValidationReport {
+    /// Whether the mesh has none of the problems looked for. Boundary edges
+    /// are not a problem: open meshes are valid.
+    pub fn is_valid(&self) -> bool {
+        self.invalid_vertices.is_empty()
+            && self.invalid_index_triangles.is_empty()
+            && self.degenerate_triangles.is_empty()
+            && self.duplicate_triangles.is_empty()
+            && self.non_manifold_edges.is_empty()
+            && self.inconsistent_winding_edges.is_empty()
+    }
+
+    pub fn get_triangle_count(&self) -> usize {
+        self.triangle_count
+    }
+
+    /// Vertices with a NaN or infinite coordinate.
+    pub fn get_invalid_vertices(&self) -> &[usize] {
+        &self.invalid_vertices
+    }
+
+    /// Triangles with a vertex, normal, texture coordinate, group or
+    /// material index out of range.
+    pub fn get_invalid_index_triangles(&self) -> &[usize] {
+        &self.invalid_index_triangles
+    }
+
+    /// Triangles with a zero area, including those using a vertex twice.
+    pub fn get_degenerate_triangles(&self) -> &[usize] {
+        &self.degenerate_triangles
+    }
+
+    /// Triangles with the same vertices as an earlier one, in any order.
+    pub fn get_duplicate_triangles(&self) -> &[usize] {
+        &self.duplicate_triangles
+    }
+
+    /// Edges shared by more than two triangles.
+    pub fn get_non_manifold_edges(&self) -> &[(usize, usize)] {
+        &self.non_manifold_edges
+    }
+
+    /// Edges shared by two triangles that go along them in the same
+    /// direction, so that one of them is wound the wrong way.
+    pub fn get_inconsistent_winding_edges(&self) -> &[(usize, usize)] {
+        &self.inconsistent_winding_edges
+    }
+
+    /// Number of edges with a single triangle, zero for closed meshes.
+    pub fn get_boundary_edge_count(&self) -> usize {
+        self.boundary_edge_count
+    }
+}
+
+impl fmt::Display for MeshValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Triangles: {}", self.triangle_count)?;
+        writeln!(f, "Invalid vertices: {}", self.invalid_vertices.len())?;
+        writeln!(
+            f,
+            "Triangles with invalid indices: {}",
+            self.invalid_index_triangles.len()
+        )?;
+        writeln!(
+            f,
+            "Degenerate triangles: {}",
+            self.degenerate_triangles.len()
+        )?;
+        writeln!(f, "Duplicate triangles: {}", self.duplicate_triangles.len())?;
+        writeln!(f, "Non-manifold edges: {}", self.non_manifold_edges.len())?;
+        writeln!(
+            f,
+            "Inconsistently wound edges: {}",
+            self.inconsistent_winding_edges.len()
+        )?;
+        write!(f, "Boundary edges: {}", self.boundary_edge_count)
+    }
+}
+
+fn is_finite(vertex: &Vector3) -> bool {
+    vertex.x().is_finite() && vertex.y().is_finite() && vertex.z().is_finite()
+}
+
+fn is_degenerate(vertices: &[Vector3], (a, b, c): (usize, usize, usize)) -> bool {
+    let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+    let longest_edge = (b - a).norm2().max((c - b).norm2()).max((a - c).norm2());
+    (b - a).cross(&(c - a)).norm() <= DEGENERATE_AREA_RATIO * longest_edge
+}
+
+/// The vertices of a triangle, whatever their order.
+fn calculate_vertex_set((a, b, c): (usize, usize, usize)) -> [usize; 3] {
+    let mut vertex_set = [a, b, c];
+    vertex_set.sort_unstable();
+    vertex_set
+}
+
+/// The edges of triangles, given with their index. Triangles using a vertex
+/// twice have no edges.
+fn calculate_edges(triangles: &[(usize, (usize, usize, usize))]) -> EdgeMap {
+    let mut edges: EdgeMap = HashMap::new();
+    for (index, (a, b, c)) in triangles {
+        if a == b || b == c || c == a {
+            continue;
+        }
+        for (start, end) in [(*a, *b), (*b, *c), (*c, *a)] {
+            edges
+                .entry((start.min(end), start.max(end)))
+                .or_default()
+                .push((*index, start < end));
+        }
+    }
+    edges
+}
+
+/// Which triangles to flip so that the triangles of each part connected by
+/// manifold edges are wound consistently, with a positive volume if the part
+/// is closed, and in the way most of its triangles already are otherwise.
+fn calculate_orientation(vertices: &[Vector3], triangles: &[TriangleIndices]) -> Vec<bool> {
+    let indexed_triangles: Vec<(usize, (usize, usize, usize))> = triangles
+        .iter()
+        .map(|triangle| triangle.get_vertex_indices())
+        .enumerate()
+        .collect();
+    let edges = calculate_edges(&indexed_triangles);
+    // Triangles across the manifold edges of each triangle, with whether
+    // each of the two goes forward along the edge
+    let mut neighbours: Vec<Vec<(usize, bool, bool)>> = vec![Vec::new(); triangles.len()];
+    for edge_triangles in edges.values() {
+        if let [(first, first_forward), (second, second_forward)] = edge_triangles[..] {
+            neighbours[first].push((second, first_forward, second_forward));
+            neighbours[second].push((first, second_forward, first_forward));
+        }
+    }
+
+    let mut flips = vec![false; triangles.len()];
+    let mut visited = vec![false; triangles.len()];
+    for start in 0..triangles.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut part = vec![start];
+        let mut triangles_to_visit = vec![start];
+        while let Some(index) = triangles_to_visit.pop() {
+            for &(neighbour, forward, neighbour_forward) in &neighbours[index] {
+                if visited[neighbour] {
+                    continue;
+                }
+                visited[neighbour] = true;
+                // Consistent neighbours go along the edge in opposite directions
+                flips[neighbour] = (forward != flips[index]) == neighbour_forward;
+                part.push(neighbour);
+                triangles_to_visit.push(neighbour);
+            }
+        }
+
+        // Open parts, with boundary or non-manifold edges, enclose no volume
+        if part.iter().any(|index| neighbours[*index].len() < 3) {
+            if 2 * part.iter().filter(|index| flips[**index]).count() > part.len() {
+                for index in part {
+                    flips[index] = !flips[index];
+                }
+            }
+            continue;
+        }
+
+        // The signed volume enclosed by the part, relative to its center
+        let corners = |index: usize| {
+            let (a, b, c) = triangles[index].get_vertex_indices();
+            [vertices[a], vertices[b], vertices[c]]
+        };
+        let center = part
+            .iter()
+            .flat_map(|index| corners(*index))
+            .fold(Vector3::new(0., 0., 0.), |sum, vertex| sum + vertex)
+            / (3 * part.len()) as f64;
+        let volume: f64 = part
+            .iter()
+            .map(|index| {
+                let [a, b, c] = corners(*index).map(|vertex| vertex - center);
+                let volume = a.dot(&b.cross(&c));
+                if flips[*index] {
+                    -volume
+                } else {
+                    volume
+                }
+            })
+            .sum();
+        if volume < 0. {
+            for index in part {
+                flips[index] = !flips[index];
+            }
+        }
+    }
+    flips
+}
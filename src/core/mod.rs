@@ -1,20 +1,45 @@
 mod bounding_box;
 mod bvh;
+mod bvh_cache;
+mod csg;
 mod intersectable;
 mod intersection;
 mod light_source;
+mod material;
 mod mesh;
 mod mesh_object;
+mod mesh_validation;
 mod object;
+mod object_bvh;
+mod plane;
 mod point_light_source;
+mod quadric;
 mod ray;
+mod render_settings;
 mod scene;
 mod sphere;
+mod subdivision;
+mod surface;
 mod texture;
+mod torus;
+
+pub(crate) use {
+    mesh::{
+        IgnoredDirectives, IndexedMeshData, VertexWelder, DEFAULT_CREASE_ANGLE, DEFAULT_GROUP_NAME,
+    },
+    render_settings::{
+        DEFAULT_BVH_BUILD_METHOD, DEFAULT_BVH_LEAF_SIZE, DEFAULT_BVH_TRAVERSAL_COST,
+        DEFAULT_SUBDIVISION_LEVELS,
+    },
+};
 
 pub use {
-    bounding_box::BoundingBox, bvh::BVHNode, bvh::BVHTree, intersectable::Intersectable,
-    intersection::Intersection, light_source::LightSource, mesh::Mesh, mesh::TriangleIndices,
-    mesh_object::MeshObjectBuilder, object::Object, point_light_source::PointLightSource, ray::Ray,
-    scene::Scene, sphere::SphereBuilder, texture::Texture,
+    bounding_box::BoundingBox, bvh::BVHBuildMethod, bvh::BVHQualityReport, bvh::BVHTree, csg::Csg,
+    csg::CsgOperation, intersectable::Intersectable, intersection::Intersection,
+    light_source::LightSource, material::Material, material::MaterialLibrary, mesh::Mesh,
+    mesh::TriangleIndices, mesh_object::MeshObject, mesh_object::MeshObjectBuilder,
+    mesh_validation::MeshValidationReport, object::Object, plane::Plane, plane::PlaneBuilder,
+    point_light_source::PointLightSource, quadric::Quadric, quadric::QuadricBuilder, ray::Ray,
+    render_settings::RenderSettings, scene::Scene, sphere::Sphere, sphere::SphereBuilder,
+    surface::Surface, surface::SurfaceBuilder, texture::Texture, torus::Torus, torus::TorusBuilder,
 };
@@ -18,5 +18,14 @@ pub trait Object: Sync + Send + Intersectable {
 
     fn get_refractive_index(&self) -> f64;
 
+    /// The radiance of the object, seen by the rays hitting it. Only the
+    /// objects that are also a `LightSource` are sampled by shadow rays: other
+    /// glowing objects, like planes, quadrics and tori, only light what
+    /// reflects or refracts rays towards them.
     fn get_light_intensity(&self) -> f64;
+
+    /// The radiance of a light source at an intersection, white by default.
+    fn calculate_emission(&self, _intersection: &Intersection) -> Vector3 {
+        Vector3::new(1., 1., 1.) * self.get_light_intensity()
+    }
 }
@@ -0,0 +1,228 @@
+use crate::{
+    core::{BoundingBox, Intersectable, Intersection, Ray},
+    utils::Vector3,
+};
+
+const OBJECT_BVH_LEAF_SIZE: usize = 2;
+
+/// Top-level BVH over the objects of a scene, storing their indices.
+///
+/// Objects without bounds, such as planes, are kept aside and tested by every
+/// ray.
+pub(crate) struct ObjectBVH {
+    root: Option<ObjectBVHNode>,
+    object_indices: Vec<usize>,
+    unbounded_object_indices: Vec<usize>,
+}
+
+struct ObjectBVHNode {
+    bounding_box: BoundingBox,
+    left: Option<Box<ObjectBVHNode>>,
+    right: Option<Box<ObjectBVHNode>>,
+    start_index: usize,
+    end_index: usize,
+}
+
+/// An object being sorted into the tree.
+struct BoundedObject {
+    index: usize,
+    bounding_box: BoundingBox,
+    center: Vector3,
+}
+
+impl ObjectBVH {
+    pub(crate) fn new(objects: &[Box<dyn Intersectable>]) -> Self {
+        let mut bounded_objects = Vec::new();
+        let mut unbounded_object_indices = Vec::new();
+        for (index, object) in objects.iter().enumerate() {
+            match object.calculate_bounding_box() {
+                Some(bounding_box) => bounded_objects.push(BoundedObject {
+                    index,
+                    bounding_box,
+                    center: bounding_box.calculate_center(),
+                }),
+                None => unbounded_object_indices.push(index),
+            }
+        }
+
+        let root = if bounded_objects.is_empty() {
+            None
+        } else {
+            Some(ObjectBVHNode::new(&mut bounded_objects, 0))
+        };
+
+        ObjectBVH {
+            root,
+            object_indices: bounded_objects.iter().map(|object| object.index).collect(),
+            unbounded_object_indices,
+        }
+    }
+
+    /// The box around all the objects, or `None` if some are unbounded.
+    pub(crate) fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        if !self.unbounded_object_indices.is_empty() {
+            return None;
+        }
+        self.root.as_ref().map(|root| root.bounding_box)
+    }
+
+    /// Closest intersection of the ray with the objects the tree was built
+    /// from.
+    pub(crate) fn intersect<'a>(
+        &self,
+        objects: &'a [Box<dyn Intersectable>],
+        ray: &Ray,
+    ) -> Option<Intersection<'a>> {
+        // Along with the index of the object, so that the first added object
+        // wins ties, as when testing the objects in order
+        let mut closest_intersection: Option<(Intersection, usize)> = None;
+
+        for &index in &self.unbounded_object_indices {
+            Self::keep_closest(&mut closest_intersection, objects, index, ray);
+        }
+
+        let mut nodes_to_visit: Vec<&ObjectBVHNode> = self.root.iter().collect();
+        while let Some(node) = nodes_to_visit.pop() {
+            let closest_distance = closest_intersection
+                .as_ref()
+                .map_or(f64::INFINITY, |(closest, _)| closest.get_distance());
+            if node
+                .bounding_box
+                .calculate_entry_distance(ray, closest_distance)
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.is_leaf() {
+                for &index in &self.object_indices[node.start_index..node.end_index] {
+                    Self::keep_closest(&mut closest_intersection, objects, index, ray);
+                }
+                continue;
+            }
+
+            if let Some(left) = &node.left {
+                nodes_to_visit.push(left);
+            }
+            if let Some(right) = &node.right {
+                nodes_to_visit.push(right);
+            }
+        }
+
+        closest_intersection.map(|(intersection, _)| intersection)
+    }
+
+    /// Whether the ray hits one of the objects closer than `max_distance`.
+    pub(crate) fn occluded(
+        &self,
+        objects: &[Box<dyn Intersectable>],
+        ray: &Ray,
+        max_distance: f64,
+    ) -> bool {
+        let occluded_by = |index: &usize| objects[*index].occluded(ray, max_distance);
+
+        if self.unbounded_object_indices.iter().any(occluded_by) {
+            return true;
+        }
+
+        let mut nodes_to_visit: Vec<&ObjectBVHNode> = self.root.iter().collect();
+        while let Some(node) = nodes_to_visit.pop() {
+            if node
+                .bounding_box
+                .calculate_entry_distance(ray, max_distance)
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.is_leaf() {
+                if self.object_indices[node.start_index..node.end_index]
+                    .iter()
+                    .any(occluded_by)
+                {
+                    return true;
+                }
+                continue;
+            }
+
+            if let Some(left) = &node.left {
+                nodes_to_visit.push(left);
+            }
+            if let Some(right) = &node.right {
+                nodes_to_visit.push(right);
+            }
+        }
+
+        false
+    }
+
+    fn keep_closest<'a>(
+        closest_intersection: &mut Option<(Intersection<'a>, usize)>,
+        objects: &'a [Box<dyn Intersectable>],
+        index: usize,
+        ray: &Ray,
+    ) {
+        if let Some(intersection) = objects[index].intersect(ray) {
+            let distance = intersection.get_distance();
+            if closest_intersection
+                .as_ref()
+                .is_none_or(|(closest, closest_index)| {
+                    distance < closest.get_distance()
+                        || (distance == closest.get_distance() && index < *closest_index)
+                })
+            {
+                *closest_intersection = Some((intersection, index));
+            }
+        }
+    }
+}
+
+impl ObjectBVHNode {
+    /// Build the node of a slice of the objects, which starts at `start_index`
+    /// in the whole list. The slice is reordered so that each node covers a
+    /// contiguous range.
+    fn new(objects: &mut [BoundedObject], start_index: usize) -> Self {
+        let bounding_box = objects
+            .iter()
+            .skip(1)
+            .fold(objects[0].bounding_box, |bounding_box, object| {
+                bounding_box.calculate_union(&object.bounding_box)
+            });
+        let end_index = start_index + objects.len();
+
+        if objects.len() <= OBJECT_BVH_LEAF_SIZE {
+            return ObjectBVHNode {
+                bounding_box,
+                left: None,
+                right: None,
+                start_index,
+                end_index,
+            };
+        }
+
+        // Split at the median of the centers, along the axis they spread the
+        // most
+        let centers: Vec<Vector3> = objects.iter().map(|object| object.center).collect();
+        let axis = BoundingBox::new_from_points(&centers)
+            .calculate_diagonals()
+            .greatest_component();
+        let middle = objects.len() / 2;
+        objects.select_nth_unstable_by(middle, |a, b| a.center[axis].total_cmp(&b.center[axis]));
+
+        let (left_objects, right_objects) = objects.split_at_mut(middle);
+        let left = ObjectBVHNode::new(left_objects, start_index);
+        let right = ObjectBVHNode::new(right_objects, start_index + middle);
+
+        ObjectBVHNode {
+            bounding_box,
+            left: Some(Box::new(left)),
+            right: Some(Box::new(right)),
+            start_index,
+            end_index,
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    core::{Intersectable, Intersection, Object, Ray, Surface, SurfaceBuilder},
+    utils::{Vector3, MESH_EPSILON},
+};
+
+const DEFAULT_TILE_SIZE: f64 = 1.;
+
+/// An infinite plane, going through `point` and facing `normal`.
+///
+/// Rays hitting the plane from the side its normal points to are exterior.
+pub struct Plane {
+    point: Vector3,
+    normal: Vector3,
+    u_axis: Vector3,
+    v_axis: Vector3,
+    surface: Surface,
+    tile_size: f64,
+}
+
+pub struct PlaneBuilder {
+    point: Vector3,
+    normal: Vector3,
+    surface: Surface,
+    tile_size: f64,
+}
+
+impl PlaneBuilder {
+    /// A plane going through `point` and facing `normal`, which does not
+    /// need to be normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the normal is zero.
+    pub fn new(point: Vector3, normal: Vector3) -> Self {
+        assert!(normal.norm2() > 0., "the normal must not be zero");
+        PlaneBuilder {
+            point,
+            normal: normal.normalized(),
+            surface: Surface::new(),
+            tile_size: DEFAULT_TILE_SIZE,
+        }
+    }
+
+    /// Size, in scene units, of one repetition of the texture on the plane.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is not positive.
+    pub fn with_tile_size(&mut self, tile_size: f64) -> &mut Self {
+        assert!(tile_size > 0., "the tile size must be positive");
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn build(&self) -> Plane {
+        let (u_axis, v_axis) = self.normal.calculate_orthonormal_basis();
+
+        Plane {
+            point: self.point,
+            normal: self.normal,
+            u_axis,
+            v_axis,
+            surface: self.surface.clone(),
+            tile_size: self.tile_size,
+        }
+    }
+}
+
+impl SurfaceBuilder for PlaneBuilder {
+    fn get_surface_mut(&mut self) -> &mut Surface {
+        &mut self.surface
+    }
+}
+
+impl Plane {
+    pub fn get_point(&self) -> &Vector3 {
+        &self.point
+    }
+
+    pub fn get_normal(&self) -> &Vector3 {
+        &self.normal
+    }
+
+    /// Texture coordinates of a point of the plane, wrapped to `[0, 1)`.
+    fn calculate_mapping_point(&self, point: &Vector3) -> Vector3 {
+        let offset = *point - self.point;
+        let u = (offset.dot(&self.u_axis) / self.tile_size).rem_euclid(1.);
+        let v = (offset.dot(&self.v_axis) / self.tile_size).rem_euclid(1.);
+        Vector3::new(u, v, 0.)
+    }
+}
+
+impl Intersectable for Plane {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let direction_dot_normal = ray.get_direction().dot(&self.normal);
+        if direction_dot_normal.abs() < MESH_EPSILON {
+            return None;
+        }
+
+        let distance = (self.point - *ray.get_origin()).dot(&self.normal) / direction_dot_normal;
+        if distance <= 0. {
+            return None;
+        }
+
+        let intersection_point = *ray.get_origin() + *ray.get_direction() * distance;
+
+        Some(Intersection::new(
+            intersection_point,
+            self.normal,
+            distance,
+            direction_dot_normal < 0.,
+            Some(self.calculate_mapping_point(&intersection_point)),
+            Some(self),
+            *ray,
+        ))
+    }
+}
+
+impl Object for Plane {
+    fn is_opaque(&self) -> bool {
+        self.surface.is_opaque()
+    }
+
+    fn is_mirror(&self) -> bool {
+        self.surface.is_mirror()
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.surface.is_transparent()
+    }
+
+    fn is_light_source(&self) -> bool {
+        self.surface.is_light_source()
+    }
+
+    fn get_color(&self) -> &Vector3 {
+        self.surface.get_color()
+    }
+
+    fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
+        self.surface.calculate_color(intersection)
+    }
+
+    fn get_refractive_index(&self) -> f64 {
+        self.surface.get_refractive_index()
+    }
+
+    fn get_light_intensity(&self) -> f64 {
+        self.surface.get_light_intensity()
+    }
+}
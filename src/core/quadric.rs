@@ -0,0 +1,516 @@
+use {
+    crate::{
+        core::{BoundingBox, Intersectable, Intersection, Object, Ray, Surface, SurfaceBuilder},
+        utils::{solve_quadratic, Vector3, MESH_EPSILON},
+    },
+    std::f64::consts::PI,
+};
+
+const DEFAULT_CAPPED: bool = true;
+
+/// Geometry of a quadric. Cylinders, cones and disks are described in a local
+/// frame whose `w` axis is their axis of revolution.
+#[derive(Clone, Copy)]
+enum Shape {
+    Box {
+        min: Vector3,
+        max: Vector3,
+    },
+    Cylinder {
+        frame: Frame,
+        height: f64,
+        radius: f64,
+        capped: bool,
+    },
+    Cone {
+        frame: Frame,
+        height: f64,
+        radius: f64,
+        capped: bool,
+    },
+    Disk {
+        frame: Frame,
+        radius: f64,
+    },
+}
+
+/// Orthonormal frame, used to express points and directions relatively to the
+/// base of a shape.
+#[derive(Clone, Copy)]
+struct Frame {
+    origin: Vector3,
+    u: Vector3,
+    v: Vector3,
+    w: Vector3,
+}
+
+impl Frame {
+    /// A zero axis is replaced by the Z axis.
+    fn new(origin: Vector3, axis: Vector3) -> Self {
+        let w = if axis.norm2() > 0. {
+            axis.normalized()
+        } else {
+            Vector3::new(0., 0., 1.)
+        };
+        let (u, v) = w.calculate_orthonormal_basis();
+        Frame { origin, u, v, w }
+    }
+
+    fn calculate_local_point(&self, point: &Vector3) -> Vector3 {
+        self.calculate_local_direction(&(*point - self.origin))
+    }
+
+    fn calculate_local_direction(&self, direction: &Vector3) -> Vector3 {
+        Vector3::new(
+            direction.dot(&self.u),
+            direction.dot(&self.v),
+            direction.dot(&self.w),
+        )
+    }
+
+    fn calculate_world_direction(&self, direction: &Vector3) -> Vector3 {
+        direction.x() * self.u + direction.y() * self.v + direction.z() * self.w
+    }
+}
+
+/// Closest hit found on one of the surfaces of a shape.
+struct SurfaceHit {
+    distance: f64,
+    normal: Vector3,
+    mapping_point: Vector3,
+}
+
+impl SurfaceHit {
+    fn keep_closest(closest: Option<SurfaceHit>, hit: SurfaceHit) -> Option<SurfaceHit> {
+        match closest {
+            Some(closest) if closest.distance <= hit.distance => Some(closest),
+            _ => Some(hit),
+        }
+    }
+}
+
+/// An analytic primitive other than a sphere: an axis-aligned box, a capped
+/// cylinder, a cone or a disk.
+pub struct Quadric {
+    shape: Shape,
+    surface: Surface,
+}
+
+pub struct QuadricBuilder {
+    shape: Shape,
+    surface: Surface,
+}
+
+impl QuadricBuilder {
+    fn new(shape: Shape) -> Self {
+        QuadricBuilder {
+            shape,
+            surface: Surface::new(),
+        }
+    }
+
+    /// An axis-aligned box, given two opposite corners.
+    pub fn new_box(corner: Vector3, opposite_corner: Vector3) -> Self {
+        let min = Vector3::new(
+            corner.x().min(opposite_corner.x()),
+            corner.y().min(opposite_corner.y()),
+            corner.z().min(opposite_corner.z()),
+        );
+        let max = Vector3::new(
+            corner.x().max(opposite_corner.x()),
+            corner.y().max(opposite_corner.y()),
+            corner.z().max(opposite_corner.z()),
+        );
+        Self::new(Shape::Box { min, max })
+    }
+
+    /// A cylinder going from the center of its `base` to the center of its
+    /// `top`. Cylinders are capped unless `with_caps(false)` is used. A
+    /// cylinder whose top is its base is a flat disk, facing the Z axis.
+    pub fn new_cylinder(base: Vector3, top: Vector3, radius: f64) -> Self {
+        Self::new(Shape::Cylinder {
+            frame: Frame::new(base, top - base),
+            height: (top - base).norm(),
+            radius: radius.abs(),
+            capped: DEFAULT_CAPPED,
+        })
+    }
+
+    /// A cone going from the center of its `base`, of the given radius, to
+    /// its `apex`. Cones are capped unless `with_caps(false)` is used. A cone
+    /// whose apex is its base is a flat disk, facing the Z axis.
+    pub fn new_cone(base: Vector3, apex: Vector3, radius: f64) -> Self {
+        Self::new(Shape::Cone {
+            frame: Frame::new(base, apex - base),
+            height: (apex - base).norm(),
+            radius: radius.abs(),
+            capped: DEFAULT_CAPPED,
+        })
+    }
+
+    /// A flat disk facing `normal`, or the Z axis if it is zero. Rays hitting
+    /// it from the side its normal points to are exterior.
+    pub fn new_disk(center: Vector3, normal: Vector3, radius: f64) -> Self {
+        Self::new(Shape::Disk {
+            frame: Frame::new(center, normal),
+            radius: radius.abs(),
+        })
+    }
+
+    /// Whether to close the ends of cylinders and the base of cones. Has no
+    /// effect on boxes and disks.
+    pub fn with_caps(&mut self, caps: bool) -> &mut Self {
+        match &mut self.shape {
+            Shape::Cylinder { capped, .. } | Shape::Cone { capped, .. } => *capped = caps,
+            Shape::Box { .. } | Shape::Disk { .. } => {}
+        }
+        self
+    }
+
+    pub fn build(&self) -> Quadric {
+        Quadric {
+            shape: self.shape,
+            surface: self.surface.clone(),
+        }
+    }
+}
+
+impl SurfaceBuilder for QuadricBuilder {
+    fn get_surface_mut(&mut self) -> &mut Surface {
+        &mut self.surface
+    }
+}
+
+impl Quadric {
+    fn intersect_shape(&self, ray: &Ray) -> Option<SurfaceHit> {
+        match self.shape {
+            Shape::Box { min, max } => Self::intersect_box(ray, &min, &max),
+            Shape::Cylinder {
+                frame,
+                height,
+                radius,
+                capped,
+            } => Self::intersect_cylinder(ray, &frame, height, radius, capped),
+            Shape::Cone {
+                frame,
+                height,
+                radius,
+                capped,
+            } => Self::intersect_cone(ray, &frame, height, radius, capped),
+            Shape::Disk { frame, radius } => {
+                let origin = frame.calculate_local_point(ray.get_origin());
+                let direction = frame.calculate_local_direction(ray.get_direction());
+                Self::intersect_cap(&origin, &direction, 0., radius, 1.)
+                    .map(|hit| Self::to_world_hit(&frame, hit))
+            }
+        }
+    }
+
+    /// Slab test, keeping track of the face through which the ray enters and
+    /// leaves the box. Each face is mapped to the whole texture.
+    fn intersect_box(ray: &Ray, min: &Vector3, max: &Vector3) -> Option<SurfaceHit> {
+        let origin = ray.get_origin();
+        let direction = ray.get_direction();
+
+        let mut t_near = f64::NEG_INFINITY;
+        let mut t_far = f64::INFINITY;
+        let mut near_axis = 0;
+        let mut far_axis = 0;
+
+        for axis in 0..3 {
+            if direction[axis].abs() < MESH_EPSILON {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (min[axis] - origin[axis]) / direction[axis];
+            let t2 = (max[axis] - origin[axis]) / direction[axis];
+            let (t_min, t_max) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+            if t_min > t_near {
+                t_near = t_min;
+                near_axis = axis;
+            }
+            if t_max < t_far {
+                t_far = t_max;
+                far_axis = axis;
+            }
+        }
+
+        if t_near > t_far || t_far <= 0. {
+            return None;
+        }
+
+        let (distance, axis) = if t_near > 0. {
+            (t_near, near_axis)
+        } else {
+            (t_far, far_axis)
+        };
+
+        let point = *origin + *direction * distance;
+        let center = (*min + *max) / 2.;
+
+        let mut normal = [0.; 3];
+        normal[axis] = if point[axis] > center[axis] { 1. } else { -1. };
+
+        let size = *max - *min;
+        let u_axis = (axis + 1) % 3;
+        let v_axis = (axis + 2) % 3;
+        let mapping_point = Vector3::new(
+            Self::calculate_ratio(point[u_axis] - min[u_axis], size[u_axis]),
+            Self::calculate_ratio(point[v_axis] - min[v_axis], size[v_axis]),
+            0.,
+        );
+
+        Some(SurfaceHit {
+            distance,
+            normal: Vector3::new(normal[0], normal[1], normal[2]),
+            mapping_point,
+        })
+    }
+
+    fn intersect_cylinder(
+        ray: &Ray,
+        frame: &Frame,
+        height: f64,
+        radius: f64,
+        capped: bool,
+    ) -> Option<SurfaceHit> {
+        let origin = frame.calculate_local_point(ray.get_origin());
+        let direction = frame.calculate_local_direction(ray.get_direction());
+
+        let a = direction.x() * direction.x() + direction.y() * direction.y();
+        let b = 2. * (origin.x() * direction.x() + origin.y() * direction.y());
+        let c = origin.x() * origin.x() + origin.y() * origin.y() - radius * radius;
+
+        // Flat cylinders only have their caps
+        let mut closest = None;
+        for distance in solve_quadratic(a, b, c) {
+            let point = origin + direction * distance;
+            if distance > 0. && height > 0. && radius > 0. && (0. ..=height).contains(&point.z()) {
+                let hit = SurfaceHit {
+                    distance,
+                    normal: Vector3::new(point.x(), point.y(), 0.) / radius,
+                    mapping_point: Vector3::new(
+                        Self::calculate_azimuth(&point),
+                        Self::calculate_ratio(point.z(), height),
+                        0.,
+                    ),
+                };
+                closest = SurfaceHit::keep_closest(closest, hit);
+            }
+        }
+
+        if capped {
+            for (z, side) in [(0., -1.), (height, 1.)] {
+                if let Some(hit) = Self::intersect_cap(&origin, &direction, z, radius, side) {
+                    closest = SurfaceHit::keep_closest(closest, hit);
+                }
+            }
+        }
+
+        closest.map(|hit| Self::to_world_hit(frame, hit))
+    }
+
+    fn intersect_cone(
+        ray: &Ray,
+        frame: &Frame,
+        height: f64,
+        radius: f64,
+        capped: bool,
+    ) -> Option<SurfaceHit> {
+        let origin = frame.calculate_local_point(ray.get_origin());
+        let direction = frame.calculate_local_direction(ray.get_direction());
+        if height == 0. {
+            // Flat cones only have their base
+            return match capped {
+                true => Self::intersect_cap(&origin, &direction, 0., radius, -1.)
+                    .map(|hit| Self::to_world_hit(frame, hit)),
+                false => None,
+            };
+        }
+
+        // The radius of the cone decreases linearly, from `radius` at the base
+        // to 0 at the apex: x² + y² = k² (height - z)²
+        let k2 = (radius / height) * (radius / height);
+        let apex_z = height - origin.z();
+
+        let a = direction.x() * direction.x() + direction.y() * direction.y()
+            - k2 * direction.z() * direction.z();
+        let b = 2.
+            * (origin.x() * direction.x()
+                + origin.y() * direction.y()
+                + k2 * apex_z * direction.z());
+        let c = origin.x() * origin.x() + origin.y() * origin.y() - k2 * apex_z * apex_z;
+
+        let mut closest = None;
+        for distance in solve_quadratic(a, b, c) {
+            let point = origin + direction * distance;
+            if distance > 0. && (0. ..=height).contains(&point.z()) {
+                // The normal is undefined at the apex, where the axis is used
+                let normal = Vector3::new(point.x(), point.y(), k2 * (height - point.z()));
+                let hit = SurfaceHit {
+                    distance,
+                    normal: if normal.norm2() > 0. {
+                        normal.normalized()
+                    } else {
+                        Vector3::new(0., 0., 1.)
+                    },
+                    mapping_point: Vector3::new(
+                        Self::calculate_azimuth(&point),
+                        point.z() / height,
+                        0.,
+                    ),
+                };
+                closest = SurfaceHit::keep_closest(closest, hit);
+            }
+        }
+
+        if capped {
+            if let Some(hit) = Self::intersect_cap(&origin, &direction, 0., radius, -1.) {
+                closest = SurfaceHit::keep_closest(closest, hit);
+            }
+        }
+
+        closest.map(|hit| Self::to_world_hit(frame, hit))
+    }
+
+    /// Intersect a disk of the local frame, at height `z` and facing the `w`
+    /// axis (`side = 1`) or the opposite direction (`side = -1`).
+    fn intersect_cap(
+        origin: &Vector3,
+        direction: &Vector3,
+        z: f64,
+        radius: f64,
+        side: f64,
+    ) -> Option<SurfaceHit> {
+        if direction.z().abs() < MESH_EPSILON {
+            return None;
+        }
+
+        let distance = (z - origin.z()) / direction.z();
+        if distance <= 0. {
+            return None;
+        }
+
+        let point = *origin + *direction * distance;
+        if point.x() * point.x() + point.y() * point.y() > radius * radius {
+            return None;
+        }
+
+        Some(SurfaceHit {
+            distance,
+            normal: Vector3::new(0., 0., side),
+            mapping_point: Vector3::new(
+                Self::calculate_ratio(point.x() + radius, 2. * radius),
+                Self::calculate_ratio(point.y() + radius, 2. * radius),
+                0.,
+            ),
+        })
+    }
+
+    fn to_world_hit(frame: &Frame, hit: SurfaceHit) -> SurfaceHit {
+        SurfaceHit {
+            normal: frame.calculate_world_direction(&hit.normal),
+            ..hit
+        }
+    }
+
+    /// Position along a side of the given size, mapped to `[0, 1)`, or 0 if
+    /// the side is flat.
+    fn calculate_ratio(offset: f64, size: f64) -> f64 {
+        if size > 0. {
+            (offset / size).clamp(0., 1. - f64::EPSILON)
+        } else {
+            0.
+        }
+    }
+
+    /// Angle of a point around the `w` axis, mapped to `[0, 1)`.
+    fn calculate_azimuth(point: &Vector3) -> f64 {
+        ((point.y().atan2(point.x()) + PI) / (2. * PI)).clamp(0., 1. - f64::EPSILON)
+    }
+}
+
+impl Intersectable for Quadric {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let hit = self.intersect_shape(ray)?;
+        let point = *ray.get_origin() + *ray.get_direction() * hit.distance;
+
+        Some(Intersection::new(
+            point,
+            hit.normal,
+            hit.distance,
+            ray.get_direction().dot(&hit.normal) < 0.,
+            Some(hit.mapping_point),
+            Some(self),
+            *ray,
+        ))
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        let bounding_box = match self.shape {
+            Shape::Box { min, max } => BoundingBox::new(min, max),
+            Shape::Cylinder {
+                frame,
+                height,
+                radius,
+                ..
+            } => {
+                let top = frame.origin + frame.w * height;
+                BoundingBox::new_from_disk(frame.origin, frame.w, radius)
+                    .calculate_union(&BoundingBox::new_from_disk(top, frame.w, radius))
+            }
+            Shape::Cone {
+                frame,
+                height,
+                radius,
+                ..
+            } => {
+                let apex = frame.origin + frame.w * height;
+                BoundingBox::new_from_disk(frame.origin, frame.w, radius)
+                    .calculate_union(&BoundingBox::new(apex, apex))
+            }
+            Shape::Disk { frame, radius } => {
+                BoundingBox::new_from_disk(frame.origin, frame.w, radius)
+            }
+        };
+        Some(bounding_box)
+    }
+}
+
+impl Object for Quadric {
+    fn is_opaque(&self) -> bool {
+        self.surface.is_opaque()
+    }
+
+    fn is_mirror(&self) -> bool {
+        self.surface.is_mirror()
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.surface.is_transparent()
+    }
+
+    fn is_light_source(&self) -> bool {
+        self.surface.is_light_source()
+    }
+
+    fn get_color(&self) -> &Vector3 {
+        self.surface.get_color()
+    }
+
+    fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
+        self.surface.calculate_color(intersection)
+    }
+
+    fn get_refractive_index(&self) -> f64 {
+        self.surface.get_refractive_index()
+    }
+
+    fn get_light_intensity(&self) -> f64 {
+        self.surface.get_light_intensity()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::utils::{Vector3, RAY_OFFSET_EPSILON};
+use crate::utils::{Transform, Vector3, RAY_OFFSET_EPSILON};
 
 const DEFAULT_REFRACTIVE_INDEX: f64 = 1.;
 
@@ -47,6 +47,16 @@ impl Ray {
         *self
     }
 
+    /// The same ray in another space. The direction is not normalized, so
+    /// that distances along both rays are the same.
+    pub fn calculate_transformed_ray(&self, transform: &Transform) -> Self {
+        Ray::new_with_refractive_index(
+            transform.transform_point(&self.origin),
+            transform.transform_direction(&self.direction),
+            self.refractive_index,
+        )
+    }
+
     pub fn calculate_reflected_ray(&self, intersection_point: &Vector3, normal: &Vector3) -> Self {
         let reflected_direction = self.direction - 2. * *normal * self.direction.dot(normal);
         Ray::new_with_refractive_index(
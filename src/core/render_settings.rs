@@ -0,0 +1,225 @@
+use crate::core::{BVHBuildMethod, DEFAULT_CREASE_ANGLE};
+
+const DEFAULT_SAMPLES_PER_PIXEL: u32 = 1024;
+const DEFAULT_MAX_RECURSION_DEPTH: i32 = 5;
+const DEFAULT_FRESNEL: bool = true;
+const DEFAULT_INDIRECT_LIGHTING: bool = true;
+const DEFAULT_ANTIALIASING: bool = true;
+const DEFAULT_NORMAL_MAPPING: bool = true;
+pub(crate) const DEFAULT_SUBDIVISION_LEVELS: usize = 0;
+pub(crate) const DEFAULT_BVH_LEAF_SIZE: usize = 5;
+pub(crate) const DEFAULT_BVH_BUILD_METHOD: BVHBuildMethod = BVHBuildMethod::SurfaceAreaHeuristic;
+pub(crate) const DEFAULT_BVH_TRAVERSAL_COST: f64 = 1.;
+const DEFAULT_BVH_CACHE: bool = false;
+
+const PREVIEW_SAMPLES_PER_PIXEL: u32 = 16;
+const PREVIEW_MAX_RECURSION_DEPTH: i32 = 3;
+
+/// Parameters of a render that can change between runs without rebuilding
+/// the ray tracer.
+///
+/// Most settings are read while rendering (`Image` and `Scene`). The normal
+/// mapping, crease angle, subdivision and BVH settings apply to meshes, and
+/// are read when the mesh objects are built (see
+/// `MeshObjectBuilder::with_settings`).
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    samples_per_pixel: u32,
+    max_recursion_depth: i32,
+    fresnel: bool,
+    indirect_lighting: bool,
+    antialiasing: bool,
+    normal_mapping: bool,
+    crease_angle: f64,
+    subdivision_levels: usize,
+    bvh_leaf_size: usize,
+    bvh_build_method: BVHBuildMethod,
+    bvh_traversal_cost: f64,
+    bvh_cache: bool,
+    seed: Option<u64>,
+}
+
+impl RenderSettings {
+    /// Settings for a fast, noisy render, to check the composition of a scene.
+    pub fn preview() -> Self {
+        RenderSettings {
+            samples_per_pixel: PREVIEW_SAMPLES_PER_PIXEL,
+            max_recursion_depth: PREVIEW_MAX_RECURSION_DEPTH,
+            ..Self::final_quality()
+        }
+    }
+
+    /// Settings for a high quality render, with every effect enabled.
+    pub fn final_quality() -> Self {
+        RenderSettings {
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            max_recursion_depth: DEFAULT_MAX_RECURSION_DEPTH,
+            fresnel: DEFAULT_FRESNEL,
+            indirect_lighting: DEFAULT_INDIRECT_LIGHTING,
+            antialiasing: DEFAULT_ANTIALIASING,
+            normal_mapping: DEFAULT_NORMAL_MAPPING,
+            crease_angle: DEFAULT_CREASE_ANGLE,
+            subdivision_levels: DEFAULT_SUBDIVISION_LEVELS,
+            bvh_leaf_size: DEFAULT_BVH_LEAF_SIZE,
+            bvh_build_method: DEFAULT_BVH_BUILD_METHOD,
+            bvh_traversal_cost: DEFAULT_BVH_TRAVERSAL_COST,
+            bvh_cache: DEFAULT_BVH_CACHE,
+            seed: None,
+        }
+    }
+
+    /// Look up a preset by name, as used in scene files and on the command
+    /// line.
+    pub fn from_preset_name(name: &str) -> Option<Self> {
+        match name {
+            "preview" => Some(Self::preview()),
+            "final" => Some(Self::final_quality()),
+            _ => None,
+        }
+    }
+
+    /// Rays traced through each pixel, whose colors are averaged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the amount of samples is zero.
+    pub fn with_samples_per_pixel(&mut self, samples_per_pixel: u32) -> &mut Self {
+        assert!(
+            samples_per_pixel > 0,
+            "the samples per pixel must be positive"
+        );
+        self.samples_per_pixel = samples_per_pixel;
+        self
+    }
+
+    pub fn with_max_recursion_depth(&mut self, max_recursion_depth: i32) -> &mut Self {
+        self.max_recursion_depth = max_recursion_depth;
+        self
+    }
+
+    pub fn with_fresnel(&mut self, fresnel: bool) -> &mut Self {
+        self.fresnel = fresnel;
+        self
+    }
+
+    pub fn with_indirect_lighting(&mut self, indirect_lighting: bool) -> &mut Self {
+        self.indirect_lighting = indirect_lighting;
+        self
+    }
+
+    pub fn with_antialiasing(&mut self, antialiasing: bool) -> &mut Self {
+        self.antialiasing = antialiasing;
+        self
+    }
+
+    pub fn with_normal_mapping(&mut self, normal_mapping: bool) -> &mut Self {
+        self.normal_mapping = normal_mapping;
+        self
+    }
+
+    /// Largest angle, in radians, between faces whose normals are smoothed
+    /// together when generating the normals that a mesh file lacks.
+    pub fn with_crease_angle(&mut self, crease_angle: f64) -> &mut Self {
+        self.crease_angle = crease_angle;
+        self
+    }
+
+    /// Levels of Loop subdivision smoothing meshes before their BVH is built
+    /// (see `Mesh::subdivide`).
+    pub fn with_subdivision_levels(&mut self, subdivision_levels: usize) -> &mut Self {
+        self.subdivision_levels = subdivision_levels;
+        self
+    }
+
+    pub fn with_bvh_leaf_size(&mut self, bvh_leaf_size: usize) -> &mut Self {
+        self.bvh_leaf_size = bvh_leaf_size;
+        self
+    }
+
+    pub fn with_bvh_build_method(&mut self, bvh_build_method: BVHBuildMethod) -> &mut Self {
+        self.bvh_build_method = bvh_build_method;
+        self
+    }
+
+    /// Cost of visiting a BVH node relative to intersecting a triangle, used
+    /// by the surface area heuristic. Higher values give shallower trees with
+    /// larger leaves.
+    pub fn with_bvh_traversal_cost(&mut self, bvh_traversal_cost: f64) -> &mut Self {
+        self.bvh_traversal_cost = bvh_traversal_cost;
+        self
+    }
+
+    /// Cache the BVHs of meshes loaded from files next to them, so that
+    /// later renders skip building them (see `BVHTree::new_from_file`).
+    pub fn with_bvh_cache(&mut self, bvh_cache: bool) -> &mut Self {
+        self.bvh_cache = bvh_cache;
+        self
+    }
+
+    /// Make renders reproducible. Each image row reseeds the random number
+    /// generator from this seed, so the result does not depend on the number
+    /// of threads. Without a seed, rows are reseeded from the operating
+    /// system.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn get_samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    pub fn get_max_recursion_depth(&self) -> i32 {
+        self.max_recursion_depth
+    }
+
+    pub fn is_fresnel_enabled(&self) -> bool {
+        self.fresnel
+    }
+
+    pub fn is_indirect_lighting_enabled(&self) -> bool {
+        self.indirect_lighting
+    }
+
+    pub fn is_antialiasing_enabled(&self) -> bool {
+        self.antialiasing
+    }
+
+    pub fn is_normal_mapping_enabled(&self) -> bool {
+        self.normal_mapping
+    }
+
+    pub fn get_crease_angle(&self) -> f64 {
+        self.crease_angle
+    }
+
+    pub fn get_subdivision_levels(&self) -> usize {
+        self.subdivision_levels
+    }
+
+    pub fn get_bvh_leaf_size(&self) -> usize {
+        self.bvh_leaf_size
+    }
+
+    pub fn get_bvh_build_method(&self) -> BVHBuildMethod {
+        self.bvh_build_method
+    }
+
+    pub fn get_bvh_traversal_cost(&self) -> f64 {
+        self.bvh_traversal_cost
+    }
+
+    pub fn is_bvh_cache_enabled(&self) -> bool {
+        self.bvh_cache
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self::final_quality()
+    }
+}
@@ -1,17 +1,20 @@
 use {
     crate::{
-        core::{Intersectable, Intersection, LightSource, Ray},
-        utils::{
-            random_cos, random_f64, Vector3, ENABLE_FRESNEL, ENABLE_INDIRECT_LIGHTING,
-            MAX_RECURSION_DEPTH, RAY_OFFSET_EPSILON,
+        core::{
+            object_bvh::ObjectBVH, BoundingBox, Intersectable, Intersection, LightSource, Mesh,
+            Ray, RenderSettings,
         },
+        error::Result,
+        utils::{random_cos, random_f64, random_index, Vector3, RAY_OFFSET_EPSILON},
     },
-    rand::Rng,
+    std::sync::OnceLock,
 };
 
 pub struct Scene {
     objects: Vec<Box<dyn Intersectable>>,
     light_sources: Vec<Box<dyn LightSource>>,
+    /// Built on the first query after objects are added
+    object_bvh: OnceLock<ObjectBVH>,
 }
 
 impl Scene {
@@ -19,11 +22,15 @@ impl Scene {
         Scene {
             objects: Vec::new(),
             light_sources: Vec::new(),
+            object_bvh: OnceLock::new(),
         }
     }
 
+    /// Add an object to the scene. The BVH over the objects of the scene is
+    /// rebuilt before the next query.
     pub fn add_object(&mut self, object: Box<dyn Intersectable>) -> &mut Self {
         self.objects.push(object);
+        self.object_bvh = OnceLock::new();
         self
     }
 
@@ -31,48 +38,90 @@ impl Scene {
         self.light_sources.push(light_source);
         self
     }
+
+    /// Combine the objects that can be turned into meshes, such as mesh
+    /// objects and spheres, into one mesh in world space. The groups of each
+    /// object are prefixed with `object_<index>_`, its index in the scene.
+    pub fn calculate_mesh(&self) -> Mesh {
+        let meshes: Vec<Mesh> = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, object)| {
+                let mut mesh = object.calculate_mesh()?;
+                mesh.prefix_groups(&format!("object_{}_", index));
+                Some(mesh)
+            })
+            .collect();
+        Mesh::merge(&meshes)
+    }
+
+    /// Write the mesh of the scene (see `Scene::calculate_mesh`) to an OBJ or
+    /// PLY file, to inspect it in other tools.
+    pub fn export(&self, filename: &str) -> Result<()> {
+        self.calculate_mesh().write_file(filename)
+    }
+
+    fn get_object_bvh(&self) -> &ObjectBVH {
+        self.object_bvh
+            .get_or_init(|| ObjectBVH::new(&self.objects))
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Scene {
-    pub fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
+    pub fn calculate_color(
+        &self,
+        intersection: &Intersection,
+        settings: &RenderSettings,
+    ) -> Vector3 {
         if self.light_sources.is_empty() {
             Vector3::new(0., 0., 0.)
         } else {
-            self.calculate_color_recursive(intersection, 1, false)
+            self.calculate_color_recursive(intersection, settings, 1, false)
         }
     }
 
     fn calculate_color_recursive(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
         indirect_light: bool,
     ) -> Vector3 {
-        if depth > MAX_RECURSION_DEPTH {
+        if depth > settings.get_max_recursion_depth() {
             return Vector3::new(0., 0., 0.);
         }
 
         if intersection.get_object().is_light_source() && !indirect_light {
             self.calculate_light_color(intersection)
         } else if intersection.get_object().is_opaque() {
-            self.calculate_opaque_color(intersection, depth)
+            self.calculate_opaque_color(intersection, settings, depth)
         } else if intersection.get_object().is_mirror() {
-            self.calculate_mirror_color(intersection, depth, indirect_light)
+            self.calculate_mirror_color(intersection, settings, depth, indirect_light)
         } else if intersection.get_object().is_transparent() {
-            self.calculate_transparent_color(intersection, depth, indirect_light)
+            self.calculate_transparent_color(intersection, settings, depth, indirect_light)
         } else {
             Vector3::new(0., 0., 0.)
         }
     }
 
     fn calculate_light_color(&self, intersection: &Intersection) -> Vector3 {
-        Vector3::new(1., 1., 1.) * intersection.get_object().get_light_intensity()
+        intersection.get_object().calculate_emission(intersection)
     }
 
-    fn calculate_opaque_color(&self, intersection: &Intersection, depth: i32) -> Vector3 {
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..self.light_sources.len());
-        let light_source = &self.light_sources[index];
+    fn calculate_opaque_color(
+        &self,
+        intersection: &Intersection,
+        settings: &RenderSettings,
+        depth: i32,
+    ) -> Vector3 {
+        let light_source = &self.light_sources[random_index(self.light_sources.len())];
 
         let light_ray = light_source.calculate_ray_from_light_source(intersection.get_point());
         let direct_lighting = if self.light_ray_reaches_point(&light_ray, intersection.get_point())
@@ -87,8 +136,8 @@ impl Scene {
             Vector3::new(0., 0., 0.)
         };
 
-        if ENABLE_INDIRECT_LIGHTING {
-            direct_lighting + self.calculate_indirect_lighting_color(intersection, depth)
+        if settings.is_indirect_lighting_enabled() {
+            direct_lighting + self.calculate_indirect_lighting_color(intersection, settings, depth)
         } else {
             direct_lighting
         }
@@ -97,20 +146,27 @@ impl Scene {
     fn calculate_mirror_color(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
         indirect_light: bool,
     ) -> Vector3 {
-        self.calculate_reflected_color(intersection, depth, indirect_light)
+        self.calculate_reflected_color(intersection, settings, depth, indirect_light)
     }
 
     fn calculate_reflected_color(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
         indirect_light: bool,
     ) -> Vector3 {
         if let Some(reflected_intersection) = self.calculate_reflected_intersection(intersection) {
-            self.calculate_color_recursive(&reflected_intersection, depth + 1, indirect_light)
+            self.calculate_color_recursive(
+                &reflected_intersection,
+                settings,
+                depth + 1,
+                indirect_light,
+            )
         } else {
             Vector3::new(0., 0., 0.)
         }
@@ -119,54 +175,60 @@ impl Scene {
     fn calculate_transparent_color(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
         indirect_light: bool,
     ) -> Vector3 {
-        if ENABLE_FRESNEL {
-            self.calculate_fresnel_color(intersection, depth, indirect_light)
+        if settings.is_fresnel_enabled() {
+            self.calculate_fresnel_color(intersection, settings, depth, indirect_light)
         } else {
-            self.calculate_refracted_color(intersection, depth, indirect_light)
+            self.calculate_refracted_color(intersection, settings, depth, indirect_light)
         }
     }
 
     fn calculate_refracted_color(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
         indirect_light: bool,
     ) -> Vector3 {
         if let Some(refracted_intersection) = self.calculate_refracted_intersection(intersection) {
-            self.calculate_color_recursive(&refracted_intersection, depth + 1, indirect_light)
+            self.calculate_color_recursive(
+                &refracted_intersection,
+                settings,
+                depth + 1,
+                indirect_light,
+            )
         } else {
             Vector3::new(0., 0., 0.)
         }
     }
 
     fn light_ray_reaches_point(&self, light_ray: &Ray, point: &Vector3) -> bool {
-        let intersection = self.intersect(&light_ray);
+        let distance_to_point = (*point - *light_ray.get_origin()).norm();
 
-        !intersection.as_ref().is_some_and(|i| {
-            RAY_OFFSET_EPSILON + i.get_distance() < (*point - *light_ray.get_origin()).norm()
-        })
+        !self.occluded(light_ray, distance_to_point - RAY_OFFSET_EPSILON)
     }
 
     fn calculate_reflected_intersection(
         &self,
         intersection: &Intersection,
-    ) -> Option<Intersection> {
+    ) -> Option<Intersection<'_>> {
         self.intersect(&intersection.calculate_reflected_ray())
     }
 
     fn calculate_refracted_intersection(
         &self,
         intersection: &Intersection,
-    ) -> Option<Intersection> {
+    ) -> Option<Intersection<'_>> {
         self.intersect(&intersection.calculate_refracted_ray())
     }
 
     fn calculate_fresnel_color(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
         indirect_light: bool,
     ) -> Vector3 {
@@ -177,6 +239,7 @@ impl Scene {
             {
                 return self.calculate_color_recursive(
                     &reflected_intersection,
+                    settings,
                     depth + 1,
                     indirect_light,
                 );
@@ -187,6 +250,7 @@ impl Scene {
             {
                 return self.calculate_color_recursive(
                     &refracted_intersection,
+                    settings,
                     depth + 1,
                     indirect_light,
                 );
@@ -199,11 +263,13 @@ impl Scene {
     fn calculate_indirect_lighting_color(
         &self,
         intersection: &Intersection,
+        settings: &RenderSettings,
         depth: i32,
     ) -> Vector3 {
         let random_ray = self.calculate_random_normal_hemisphere_ray(intersection);
         if let Some(indirect_intersection) = self.intersect(&random_ray) {
-            let color = self.calculate_color_recursive(&indirect_intersection, depth + 1, true);
+            let color =
+                self.calculate_color_recursive(&indirect_intersection, settings, depth + 1, true);
             intersection
                 .get_object()
                 .calculate_color(intersection)
@@ -221,19 +287,16 @@ impl Scene {
 }
 
 impl Intersectable for Scene {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
-        let mut intersection: Option<Intersection> = None;
-
-        for object in self.objects.iter() {
-            if let Some(object_intersection) = object.intersect(ray) {
-                if intersection.as_ref().map_or(true, |i| {
-                    object_intersection.get_distance() < i.get_distance()
-                }) {
-                    intersection = Some(object_intersection);
-                }
-            }
-        }
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        self.get_object_bvh().intersect(&self.objects, ray)
+    }
+
+    fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        self.get_object_bvh()
+            .occluded(&self.objects, ray, max_distance)
+    }
 
-        intersection
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        self.get_object_bvh().calculate_bounding_box()
     }
 }
@@ -1,7 +1,10 @@
 use {
     crate::{
-        core::{Intersectable, Intersection, LightSource, Object, Ray},
-        utils::{random_cos, Vector3},
+        core::{
+            mesh::DEFAULT_CREASE_ANGLE, BoundingBox, IndexedMeshData, Intersectable, Intersection,
+            LightSource, Mesh, Object, Ray,
+        },
+        utils::{random_cos, solve_quadratic, Transform, Vector3},
     },
     std::f64::consts::PI,
 };
@@ -13,11 +16,19 @@ const DEFAULT_TRANSPARENT: bool = false;
 const DEFAULT_REFRACTIVE_INDEX: f64 = 1.;
 const DEFAULT_LIGHT: bool = false;
 const DEFAULT_LIGHT_INTENSITY: f64 = 0.;
+const DEFAULT_TRANSFORM: Option<Transform> = None;
+/// Tessellation of spheres exported as meshes
+const DEFAULT_MESH_RINGS: usize = 32;
+const DEFAULT_MESH_SEGMENTS: usize = 64;
+const MESH_GROUP_NAME: &str = "sphere";
 
 #[derive(Clone)]
 pub struct Sphere {
     center: Vector3,
     radius: f64,
+    /// From the space of the sphere to the scene, if it is not a plain sphere
+    transform: Option<Transform>,
+    position: Vector3,
     opaque: bool,
     color: Vector3,
     mirror: bool,
@@ -30,6 +41,7 @@ pub struct Sphere {
 pub struct SphereBuilder {
     center: Vector3,
     radius: f64,
+    transform: Option<Transform>,
     opaque: bool,
     color: Vector3,
     mirror: bool,
@@ -44,6 +56,7 @@ impl SphereBuilder {
         SphereBuilder {
             center,
             radius,
+            transform: DEFAULT_TRANSFORM,
             opaque: DEFAULT_OPAQUE,
             color: DEFAULT_COLOR,
             mirror: DEFAULT_MIRROR,
@@ -54,6 +67,16 @@ impl SphereBuilder {
         }
     }
 
+    /// Transform the sphere, for instance with a non-uniform scale to make an
+    /// ellipsoid. Transforms are applied in the order of the calls. Light
+    /// sampling assumes that the sphere keeps its radius, so the lighting of
+    /// scaled light spheres is only approximate.
+    pub fn with_transform(&mut self, transform: &Transform) -> &mut Self {
+        let current_transform = self.transform.unwrap_or_default();
+        self.transform = Some(current_transform.then(transform));
+        self
+    }
+
     pub fn with_opaque(&mut self, opaque: bool) -> &mut Self {
         self.opaque = opaque;
         self
@@ -93,9 +116,16 @@ impl SphereBuilder {
     }
 
     pub fn build(&self) -> Sphere {
+        let position = match &self.transform {
+            Some(transform) => transform.transform_point(&self.center),
+            None => self.center,
+        };
+
         Sphere {
             center: self.center,
             radius: self.radius,
+            transform: self.transform,
+            position,
             opaque: self.opaque,
             color: self.color,
             mirror: self.mirror,
@@ -108,14 +138,81 @@ impl SphereBuilder {
 }
 
 impl Sphere {
+    pub fn get_transform(&self) -> Option<&Transform> {
+        self.transform.as_ref()
+    }
+
+    /// Tessellate the sphere into a mesh in world space, along `rings`
+    /// parallels from pole to pole around the Z axis, and `segments`
+    /// meridians. Texture coordinates go around the sphere along u, and from
+    /// the bottom pole to the top one along v.
+    pub fn calculate_tessellation(&self, rings: usize, segments: usize) -> Mesh {
+        let rings = rings.max(2);
+        let segments = segments.max(3);
+        let mut data = IndexedMeshData::default();
+
+        // The seam and the poles have a vertex per segment, for their uvs
+        for ring in 0..=rings {
+            let theta = PI * ring as f64 / rings as f64;
+            for segment in 0..=segments {
+                let phi = 2. * PI * segment as f64 / segments as f64;
+                let normal = Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                data.vertices.push(self.center + normal * self.radius);
+                data.normals.push(normal);
+                data.uvs.push(Vector3::new(
+                    segment as f64 / segments as f64,
+                    1. - ring as f64 / rings as f64,
+                    0.,
+                ));
+            }
+        }
+
+        let index = |ring: usize, segment: usize| ring * (segments + 1) + segment;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let (a, b, c, d) = (
+                    index(ring, segment),
+                    index(ring + 1, segment),
+                    index(ring + 1, segment + 1),
+                    index(ring, segment + 1),
+                );
+                // Skip the triangles that collapse at the poles
+                if ring + 1 < rings {
+                    data.triangles.push((a, b, c));
+                }
+                if ring > 0 {
+                    data.triangles.push((a, c, d));
+                }
+            }
+        }
+        data.triangle_groups = vec![0; data.triangles.len()];
+        data.groups = vec![MESH_GROUP_NAME.to_string()];
+
+        let mut mesh = Mesh::from_indexed_data(data, DEFAULT_CREASE_ANGLE);
+        if let Some(transform) = &self.transform {
+            mesh.transform(transform);
+        }
+        mesh
+    }
+
     pub fn normal(&self, point: &Vector3) -> Vector3 {
-        (*point - self.center).normalize()
+        match &self.transform {
+            Some(transform) => {
+                let local_point = transform.inverse().transform_point(point);
+                transform.transform_normal(&(local_point - self.center))
+            }
+            None => (*point - self.center).normalize(),
+        }
     }
 }
 
 impl LightSource for Sphere {
     fn get_position(&self) -> &Vector3 {
-        &self.center
+        &self.position
     }
 
     fn get_intensity(&self) -> f64 {
@@ -123,8 +220,14 @@ impl LightSource for Sphere {
     }
 
     fn calculate_ray_from_light_source(&self, point: &Vector3) -> Ray {
-        let random_direction = random_cos(&self.normal(point));
-        let random_surface_point = random_direction * self.radius + self.center;
+        let random_surface_point = match &self.transform {
+            Some(transform) => {
+                let local_point = transform.inverse().transform_point(point);
+                let random_direction = random_cos(&(local_point - self.center).normalized());
+                transform.transform_point(&(random_direction * self.radius + self.center))
+            }
+            None => random_cos(&self.normal(point)) * self.radius + self.center,
+        };
 
         let light_direction = (*point - random_surface_point).normalize();
 
@@ -153,8 +256,22 @@ impl LightSource for Sphere {
     }
 }
 
-impl Intersectable for Sphere {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+impl Sphere {
+    /// Distances along the ray to the two points where its line crosses the
+    /// sphere, in increasing order.
+    fn calculate_distances(&self, ray: &Ray) -> Option<(f64, f64)> {
+        if let Some(transform) = &self.transform {
+            let local_ray = ray.calculate_transformed_ray(&transform.inverse());
+            let center_to_origin = *local_ray.get_origin() - self.center;
+            let direction = local_ray.get_direction();
+            let distances = solve_quadratic(
+                direction.norm2(),
+                2. * direction.dot(&center_to_origin),
+                center_to_origin.norm2() - self.radius * self.radius,
+            );
+            return Some((*distances.first()?, *distances.last()?));
+        }
+
         let center_to_origin = *ray.get_origin() - self.center;
         let distance_dot = ray.get_direction().dot(&center_to_origin);
         let determinant =
@@ -167,25 +284,80 @@ impl Intersectable for Sphere {
         let t1 = -distance_dot - determinant.sqrt();
         let t2 = -distance_dot + determinant.sqrt();
 
-        if t2 < 0. {
-            return None;
-        }
-
-        let distance = if t1 > 0. { t1 } else { t2 };
+        Some((t1, t2))
+    }
 
+    fn build_intersection(&self, ray: &Ray, distance: f64) -> Intersection<'_> {
         let intersection_point = *ray.get_origin() + *ray.get_direction() * distance;
 
         let normal = self.normal(&intersection_point);
 
-        Some(Intersection::new(
+        Intersection::new(
             intersection_point,
             normal,
             distance,
             ray.get_direction().dot(&normal) < 0.,
             None,
             Some(self),
-            ray.clone(),
-        ))
+            *ray,
+        )
+    }
+}
+
+impl Intersectable for Sphere {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let (t1, t2) = self.calculate_distances(ray)?;
+
+        if t2 < 0. {
+            return None;
+        }
+
+        let distance = if t1 > 0. { t1 } else { t2 };
+
+        Some(self.build_intersection(ray, distance))
+    }
+
+    fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        let Some((t1, t2)) = self.calculate_distances(ray) else {
+            return false;
+        };
+
+        let distance = if t1 > 0. { t1 } else { t2 };
+
+        t2 >= 0. && distance < max_distance
+    }
+
+    fn intersect_all(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match self.calculate_distances(ray) {
+            Some((t1, t2)) => [t1, t2]
+                .into_iter()
+                .filter(|distance| *distance > 0.)
+                .map(|distance| self.build_intersection(ray, distance))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        let Some(transform) = &self.transform else {
+            let half_size = Vector3::new(self.radius, self.radius, self.radius);
+            return Some(BoundingBox::new(
+                self.center - half_size,
+                self.center + half_size,
+            ));
+        };
+
+        // The transformed sphere is an ellipsoid, which spans along each axis
+        // the radius times the norm of the matching row of the linear part
+        let linear = transform.get_matrix().calculate_linear();
+        let half_size =
+            Vector3::new(linear.a().norm(), linear.b().norm(), linear.c().norm()) * self.radius;
+        let center = transform.transform_point(&self.center);
+        Some(BoundingBox::new(center - half_size, center + half_size))
+    }
+
+    fn calculate_mesh(&self) -> Option<Mesh> {
+        Some(self.calculate_tessellation(DEFAULT_MESH_RINGS, DEFAULT_MESH_SEGMENTS))
     }
 }
 
@@ -0,0 +1,355 @@
+//! Loop subdivision of triangle meshes.
+//!
+//! Each level splits every triangle into four and moves the vertices towards
+//! a smooth limit surface. Edges where the mesh is open, edges shared by more
+//! than two triangles, and edges whose two triangles have different normals
+//! there are creases: they are smoothed as curves of their own, and vertices
+//! where more than two creases meet, or a single one ends, stay in place.
+
+use {
+    crate::{
+        core::{Mesh, TriangleIndices, VertexWelder},
+        utils::Vector3,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        f64::consts::PI,
+    },
+};
+
+/// Two vertices joined by triangles, with the lower index first.
+type EdgeKey = (usize, usize);
+
+struct Edge {
+    key: EdgeKey,
+    /// The triangles along the edge, with the vertex of each opposite to it
+    triangles: Vec<(usize, usize)>,
+}
+
+/// The state of the mesh between two levels of subdivision. Normal indices
+/// of the triangles are left as they were until the last level.
+struct SubdivisionLevel {
+    vertices: Vec<Vector3>,
+    colors: Vec<Vector3>,
+    uvs: Vec<Vector3>,
+    triangles: Vec<TriangleIndices>,
+    creases: HashSet<EdgeKey>,
+}
+
+impl Mesh {
+    /// Smooth the mesh with `levels` levels of Loop subdivision, each
+    /// splitting every triangle into four. Vertices at the same position are
+    /// welded first, like `Mesh::repair` does, and triangles using a vertex
+    /// twice are dropped. Texture coordinates and vertex colors are
+    /// interpolated linearly, and the normals are regenerated, smooth except
+    /// across creases. Groups and materials are kept.
+    pub fn subdivide(&mut self, levels: usize) -> &mut Self {
+        if levels == 0 {
+            return self;
+        }
+
+        let mut level = self.calculate_welded_level();
+        for _ in 0..levels {
+            level = level.subdivide();
+        }
+        let (normals, normal_indices) = level.calculate_normals();
+        let triangles = level
+            .triangles
+            .iter()
+            .zip(normal_indices)
+            .map(|(triangle, normal_indices)| {
+                TriangleIndices::new(
+                    triangle.get_vertex_indices(),
+                    normal_indices,
+                    triangle.get_uv_indices(),
+                    triangle.get_group_index(),
+                    triangle.get_material_index(),
+                )
+            })
+            .collect();
+
+        self.set_vertices(level.vertices, level.colors);
+        self.set_normals_and_uvs(normals, level.uvs);
+        self.set_triangles(triangles);
+        self
+    }
+
+    /// The mesh with its vertices welded, and the creases found from its
+    /// normals.
+    fn calculate_welded_level(&self) -> SubdivisionLevel {
+        let vertices = self.get_vertices();
+        let colors = self.get_vertex_colors();
+
+        let mut welder = VertexWelder::default();
+        let mut triangles = Vec::new();
+        for triangle in self.get_triangles() {
+            let (a, b, c) = triangle.get_vertex_indices();
+            let mut weld = |index: usize| welder.weld(vertices[index], colors.get(index));
+            let (a, b, c) = (weld(a), weld(b), weld(c));
+            if a == b || b == c || c == a {
+                continue;
+            }
+            triangles.push(TriangleIndices::new(
+                (a, b, c),
+                triangle.get_normal_indices(),
+                triangle.get_uv_indices(),
+                triangle.get_group_index(),
+                triangle.get_material_index(),
+            ));
+        }
+
+        let normals = self.get_normals();
+        let normal_at = |triangle: usize, vertex: usize| {
+            let triangle = &triangles[triangle];
+            let corner = find_corner(triangle, vertex);
+            let (a, b, c) = triangle.get_normal_indices();
+            normals[[a, b, c][corner]]
+        };
+        let creases = calculate_edges(&triangles)
+            .iter()
+            .filter(|edge| match edge.triangles[..] {
+                [(first, _), (second, _)] => {
+                    let (start, end) = edge.key;
+                    [start, end].into_iter().any(|vertex| {
+                        (normal_at(first, vertex) - normal_at(second, vertex)).norm2() > 0.
+                    })
+                }
+                _ => true,
+            })
+            .map(|edge| edge.key)
+            .collect();
+
+        SubdivisionLevel {
+            vertices: welder.vertices,
+            colors: welder.colors,
+            uvs: self.get_uvs().clone(),
+            triangles,
+            creases,
+        }
+    }
+}
+
+impl SubdivisionLevel {
+    fn subdivide(&self) -> SubdivisionLevel {
+        let edges = calculate_edges(&self.triangles);
+        let edge_indices: HashMap<EdgeKey, usize> = edges
+            .iter()
+            .enumerate()
+            .map(|(index, edge)| (edge.key, index))
+            .collect();
+
+        let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        let mut crease_neighbours: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for edge in &edges {
+            let (start, end) = edge.key;
+            neighbours[start].push(end);
+            neighbours[end].push(start);
+            if self.creases.contains(&edge.key) {
+                crease_neighbours[start].push(end);
+                crease_neighbours[end].push(start);
+            }
+        }
+
+        let sum = |indices: &[usize]| {
+            indices.iter().fold(Vector3::new(0., 0., 0.), |sum, index| {
+                sum + self.vertices[*index]
+            })
+        };
+        let mut vertices: Vec<Vector3> = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(index, vertex)| match crease_neighbours[index][..] {
+                [] if !neighbours[index].is_empty() => {
+                    let valence = neighbours[index].len();
+                    let weight = calculate_loop_weight(valence);
+                    *vertex * (1. - valence as f64 * weight) + sum(&neighbours[index]) * weight
+                }
+                [_, _] => *vertex * 0.75 + sum(&crease_neighbours[index]) * 0.125,
+                _ => *vertex,
+            })
+            .collect();
+        let mut colors = self.colors.clone();
+        let mut creases = HashSet::new();
+        for edge in &edges {
+            let (start, end) = edge.key;
+            let midpoint = (self.vertices[start] + self.vertices[end]) * 0.5;
+            let vertex = match edge.triangles[..] {
+                [(_, first), (_, second)] if !self.creases.contains(&edge.key) => {
+                    midpoint * 0.75 + (self.vertices[first] + self.vertices[second]) * 0.125
+                }
+                _ => midpoint,
+            };
+            let index = vertices.len();
+            vertices.push(vertex);
+            if !colors.is_empty() {
+                colors.push((self.colors[start] + self.colors[end]) * 0.5);
+            }
+            if self.creases.contains(&edge.key) {
+                creases.insert(calculate_edge_key(start, index));
+                creases.insert(calculate_edge_key(index, end));
+            }
+        }
+
+        // Texture coordinates are split separately, as seams give the two
+        // triangles of an edge different ones
+        let mut uvs = self.uvs.clone();
+        let mut uv_midpoints: HashMap<EdgeKey, usize> = HashMap::new();
+        let mut triangles = Vec::with_capacity(4 * self.triangles.len());
+        for triangle in &self.triangles {
+            let (a, b, c) = triangle.get_vertex_indices();
+            let (ua, ub, uc) = triangle.get_uv_indices();
+            let edge_vertex = |start: usize, end: usize| {
+                self.vertices.len() + edge_indices[&calculate_edge_key(start, end)]
+            };
+            let mut uv_midpoint = |start: usize, end: usize| {
+                *uv_midpoints
+                    .entry(calculate_edge_key(start, end))
+                    .or_insert_with(|| {
+                        uvs.push((uvs[start] + uvs[end]) * 0.5);
+                        uvs.len() - 1
+                    })
+            };
+            let (ab, bc, ca) = (edge_vertex(a, b), edge_vertex(b, c), edge_vertex(c, a));
+            let (uab, ubc, uca) = (
+                uv_midpoint(ua, ub),
+                uv_midpoint(ub, uc),
+                uv_midpoint(uc, ua),
+            );
+
+            for (vertex_indices, uv_indices) in [
+                ((a, ab, ca), (ua, uab, uca)),
+                ((ab, b, bc), (uab, ub, ubc)),
+                ((ca, bc, c), (uca, ubc, uc)),
+                ((ab, bc, ca), (uab, ubc, uca)),
+            ] {
+                triangles.push(TriangleIndices::new(
+                    vertex_indices,
+                    triangle.get_normal_indices(),
+                    uv_indices,
+                    triangle.get_group_index(),
+                    triangle.get_material_index(),
+                ));
+            }
+        }
+
+        SubdivisionLevel {
+            vertices,
+            colors,
+            uvs,
+            triangles,
+            creases,
+        }
+    }
+
+    /// Normals weighted by the area of the triangles, shared by the corners
+    /// of a vertex that are not separated by creases. Returns the normals,
+    /// and the normal indices of each triangle.
+    fn calculate_normals(&self) -> (Vec<Vector3>, Vec<(usize, usize, usize)>) {
+        // Corners are numbered three per triangle, and joined across the
+        // smooth edges
+        let mut parents: Vec<usize> = (0..3 * self.triangles.len()).collect();
+        for edge in calculate_edges(&self.triangles) {
+            if let [(first, _), (second, _)] = edge.triangles[..] {
+                if self.creases.contains(&edge.key) {
+                    continue;
+                }
+                let (start, end) = edge.key;
+                for vertex in [start, end] {
+                    let first_root = find_root(
+                        &mut parents,
+                        3 * first + find_corner(&self.triangles[first], vertex),
+                    );
+                    let second_root = find_root(
+                        &mut parents,
+                        3 * second + find_corner(&self.triangles[second], vertex),
+                    );
+                    parents[second_root] = first_root;
+                }
+            }
+        }
+
+        let mut sums = vec![Vector3::new(0., 0., 0.); parents.len()];
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            let (a, b, c) = triangle.get_vertex_indices();
+            // Not normalized, so that summing them weights them by their area
+            let face_normal =
+                (self.vertices[b] - self.vertices[a]).cross(&(self.vertices[c] - self.vertices[a]));
+            for corner in 0..3 {
+                let root = find_root(&mut parents, 3 * index + corner);
+                sums[root] += face_normal;
+            }
+        }
+
+        let mut normals = Vec::new();
+        let mut root_normals: HashMap<usize, usize> = HashMap::new();
+        let mut normal_index = |corner: usize| {
+            let root = find_root(&mut parents, corner);
+            *root_normals.entry(root).or_insert_with(|| {
+                let sum = sums[root];
+                let norm = sum.norm();
+                normals.push(if norm > 0. { sum / norm } else { sum });
+                normals.len() - 1
+            })
+        };
+        let normal_indices = (0..self.triangles.len())
+            .map(|index| {
+                (
+                    normal_index(3 * index),
+                    normal_index(3 * index + 1),
+                    normal_index(3 * index + 2),
+                )
+            })
+            .collect();
+        (normals, normal_indices)
+    }
+}
+
+fn calculate_edge_key(start: usize, end: usize) -> EdgeKey {
+    (start.min(end), start.max(end))
+}
+
+/// The edges of the triangles, in the order they are first found.
+fn calculate_edges(triangles: &[TriangleIndices]) -> Vec<Edge> {
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut edge_indices: HashMap<EdgeKey, usize> = HashMap::new();
+    for (index, triangle) in triangles.iter().enumerate() {
+        let (a, b, c) = triangle.get_vertex_indices();
+        for (start, end, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+            let key = calculate_edge_key(start, end);
+            let edge_index = *edge_indices.entry(key).or_insert_with(|| {
+                edges.push(Edge {
+                    key,
+                    triangles: Vec::new(),
+                });
+                edges.len() - 1
+            });
+            edges[edge_index].triangles.push((index, opposite));
+        }
+    }
+    edges
+}
+
+/// The position of a vertex among the corners of a triangle using it.
+fn find_corner(triangle: &TriangleIndices, vertex: usize) -> usize {
+    let (a, b, c) = triangle.get_vertex_indices();
+    [a, b, c]
+        .iter()
+        .position(|corner| *corner == vertex)
+        .expect("the triangle should use the vertex")
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+/// The weight of each neighbour of a smooth vertex, as given by Loop.
+fn calculate_loop_weight(valence: usize) -> f64 {
+    let valence = valence as f64;
+    let cosine = 0.375 + 0.25 * (2. * PI / valence).cos();
+    (0.625 - cosine * cosine) / valence
+}
@@ -0,0 +1,135 @@
+use {
+    crate::{
+        core::{Intersection, Texture},
+        utils::Vector3,
+    },
+    std::sync::Arc,
+};
+
+const DEFAULT_OPAQUE: bool = false;
+const DEFAULT_COLOR: Vector3 = Vector3::new(1., 1., 1.);
+const DEFAULT_MIRROR: bool = false;
+const DEFAULT_TRANSPARENT: bool = false;
+const DEFAULT_REFRACTIVE_INDEX: f64 = 1.;
+const DEFAULT_LIGHT: bool = false;
+const DEFAULT_LIGHT_INTENSITY: f64 = 0.;
+const DEFAULT_TEXTURE: Option<Arc<Texture>> = None;
+
+/// The material of planes, quadrics and tori, set through their
+/// `SurfaceBuilder`. Objects built from the same builder share its texture.
+#[derive(Clone)]
+pub struct Surface {
+    opaque: bool,
+    color: Vector3,
+    mirror: bool,
+    transparent: bool,
+    refractive_index: f64,
+    light: bool,
+    light_intensity: f64,
+    texture: Option<Arc<Texture>>,
+}
+
+impl Surface {
+    pub(crate) fn new() -> Self {
+        Surface {
+            opaque: DEFAULT_OPAQUE,
+            color: DEFAULT_COLOR,
+            mirror: DEFAULT_MIRROR,
+            transparent: DEFAULT_TRANSPARENT,
+            refractive_index: DEFAULT_REFRACTIVE_INDEX,
+            light: DEFAULT_LIGHT,
+            light_intensity: DEFAULT_LIGHT_INTENSITY,
+            texture: DEFAULT_TEXTURE,
+        }
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.opaque
+    }
+
+    pub fn is_mirror(&self) -> bool {
+        self.mirror
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.transparent
+    }
+
+    pub fn is_light_source(&self) -> bool {
+        self.light
+    }
+
+    pub fn get_color(&self) -> &Vector3 {
+        &self.color
+    }
+
+    pub fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
+        if let Some(texture) = &self.texture {
+            texture.get_color(intersection.get_mapping_point())
+        } else {
+            self.color
+        }
+    }
+
+    pub fn get_refractive_index(&self) -> f64 {
+        self.refractive_index
+    }
+
+    pub fn get_light_intensity(&self) -> f64 {
+        self.light_intensity
+    }
+}
+
+/// The material options of the builders of planes, quadrics and tori.
+pub trait SurfaceBuilder {
+    fn get_surface_mut(&mut self) -> &mut Surface;
+
+    fn with_opaque(&mut self, opaque: bool) -> &mut Self {
+        self.get_surface_mut().opaque = opaque;
+        self
+    }
+
+    fn with_color(&mut self, color: Vector3) -> &mut Self {
+        let surface = self.get_surface_mut();
+        surface.opaque = true;
+        surface.color = color;
+        self
+    }
+
+    fn with_mirror(&mut self, mirror: bool) -> &mut Self {
+        self.get_surface_mut().mirror = mirror;
+        self
+    }
+
+    fn with_transparent(&mut self, transparent: bool) -> &mut Self {
+        self.get_surface_mut().transparent = transparent;
+        self
+    }
+
+    fn with_refractive_index(&mut self, refractive_index: f64) -> &mut Self {
+        let surface = self.get_surface_mut();
+        surface.transparent = true;
+        surface.refractive_index = refractive_index;
+        self
+    }
+
+    fn with_light(&mut self, light: bool) -> &mut Self {
+        self.get_surface_mut().light = light;
+        self
+    }
+
+    /// Make the object glow, see `Object::get_light_intensity`.
+    fn with_light_intensity(&mut self, light_intensity: f64) -> &mut Self {
+        let surface = self.get_surface_mut();
+        surface.light = true;
+        surface.light_intensity = light_intensity;
+        self
+    }
+
+    fn with_texture(&mut self, texture: Texture) -> &mut Self {
+        let surface = self.get_surface_mut();
+        surface.opaque = true;
+        surface.texture = Some(Arc::new(texture));
+        self
+    }
+}
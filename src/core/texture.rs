@@ -1,8 +1,10 @@
-use {
-    crate::utils::{Vector3, GAMMA_CORRECTION},
-    std::fs,
+use crate::{
+    core::{MaterialLibrary, Mesh},
+    error::{Error, Result},
+    utils::{Vector3, GAMMA_CORRECTION},
 };
 
+#[derive(Clone)]
 pub struct Texture {
     data: Vec<Vector3>,
     width: usize,
@@ -10,52 +12,60 @@ pub struct Texture {
 }
 
 impl Texture {
-    pub fn from_obj_file(filename: &str) -> Self {
-        let contents = fs::read_to_string(filename).expect("Failed to read file");
-        for line in contents.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() == 0 {
-                continue;
-            }
-            match parts[0] {
-                "mtllib" => {
-                    let mtl_relative_filename = parts[1];
-                    let mtl_filename = filename
-                        .replace(filename.split('/').last().unwrap(), mtl_relative_filename);
-                    return Texture::from_mtl_file(&mtl_filename);
-                }
-                _ => {}
-            }
+    /// The diffuse texture of the material libraries of an OBJ file, see
+    /// `MaterialLibrary::get_diffuse_texture`.
+    pub fn from_obj_file(filename: &str) -> Result<Self> {
+        let mesh = Mesh::from_obj_file(filename)?;
+        if mesh.get_material_libraries().is_empty() {
+            return Err(Error::missing_material(
+                filename,
+                "no material library (`mtllib`) referenced".to_string(),
+            ));
         }
+        Self::from_material_library(&MaterialLibrary::from_mesh(&mesh)?, filename)
+    }
 
-        panic!("Could not find texture reference in obj file")
+    /// The diffuse texture of an MTL file, see
+    /// `MaterialLibrary::get_diffuse_texture`.
+    pub fn from_mtl_file(filename: &str) -> Result<Self> {
+        Self::from_material_library(&MaterialLibrary::from_mtl_file(filename)?, filename)
     }
 
-    pub fn from_mtl_file(filename: &str) -> Self {
-        let contents = fs::read_to_string(filename).expect("Failed to read file");
-        for line in contents.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() == 0 {
-                continue;
+    /// The diffuse texture of a library loaded from `filename`, which
+    /// identifies it in errors.
+    pub(crate) fn from_material_library(library: &MaterialLibrary, filename: &str) -> Result<Self> {
+        library.get_diffuse_texture().cloned().ok_or_else(|| {
+            let mut message = "no diffuse texture (`map_Kd`) loaded".to_string();
+            // Such as the texture files that could not be loaded
+            if !library.get_warnings().is_empty() {
+                message = format!("{}: {}", message, library.get_warnings().join("; "));
             }
-            match parts[0] {
-                "map_Kd" => {
-                    let texture_relative_filename = parts[1];
-                    let texture_filename = filename.replace(
-                        filename.split('/').last().unwrap(),
-                        texture_relative_filename,
-                    );
-                    return Texture::from_img_file(&texture_filename);
-                }
-                _ => {}
-            }
-        }
+            Error::missing_material(filename, message)
+        })
+    }
+
+    pub fn from_img_file(filename: &str) -> Result<Self> {
+        let texture = image::open(filename).map_err(|err| match err {
+            image::ImageError::IoError(err) => Error::io(filename, err),
+            err => Error::ImageDecode {
+                path: filename.to_string(),
+                source: err,
+            },
+        })?;
+        Ok(Self::from_image(&texture))
+    }
 
-        panic!("Could not find texture reference in mtl file")
+    /// Decode an image embedded in another file, such as a glTF file. `name`
+    /// identifies the image in errors.
+    pub(crate) fn from_memory(bytes: &[u8], name: &str) -> Result<Self> {
+        let texture = image::load_from_memory(bytes).map_err(|err| Error::ImageDecode {
+            path: name.to_string(),
+            source: err,
+        })?;
+        Ok(Self::from_image(&texture))
     }
 
-    pub fn from_img_file(filename: &str) -> Self {
-        let texture = image::open(filename).expect("Failed to open texture");
+    fn from_image(texture: &image::DynamicImage) -> Self {
         let width = texture.width() as usize;
         let height = texture.height() as usize;
         let data = texture
@@ -78,16 +88,15 @@ impl Texture {
         }
     }
 
+    /// The color at texture coordinates `(point.x(), point.y())`, with the
+    /// origin at the bottom left corner. Coordinates outside of `[0, 1]` wrap
+    /// around, so that the texture repeats.
     pub fn get_color(&self, point: &Vector3) -> Vector3 {
-        let x = (point.x() * self.width as f64) as usize;
-        let y = self.height - 1 - (point.y() * self.height as f64) as usize;
-        let index = y * self.width + x;
-        if index > self.data.len() {
-            println!("Index out of bounds: {}", index);
-            println!("x: {}, y: {}", point.x(), point.y());
-            println!("x: {}, y: {}", x, y);
-            return Vector3::new(0., 0., 0.);
-        }
-        self.data[index]
+        let wrap = |coordinate: f64, size: usize| {
+            ((coordinate.rem_euclid(1.) * size as f64) as usize).min(size - 1)
+        };
+        let x = wrap(point.x(), self.width);
+        let y = self.height - 1 - wrap(point.y(), self.height);
+        self.data[y * self.width + x]
     }
 }
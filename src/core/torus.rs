@@ -0,0 +1,225 @@
+use {
+    crate::{
+        core::{BoundingBox, Intersectable, Intersection, Object, Ray, Surface, SurfaceBuilder},
+        utils::{solve_quadratic, solve_quartic, Vector3},
+    },
+    std::f64::consts::PI,
+};
+
+/// A ring, made of the points at `minor_radius` from a circle of radius
+/// `major_radius` around `axis`.
+pub struct Torus {
+    center: Vector3,
+    u_axis: Vector3,
+    v_axis: Vector3,
+    axis: Vector3,
+    major_radius: f64,
+    minor_radius: f64,
+    surface: Surface,
+}
+
+pub struct TorusBuilder {
+    center: Vector3,
+    axis: Vector3,
+    major_radius: f64,
+    minor_radius: f64,
+    surface: Surface,
+}
+
+impl TorusBuilder {
+    /// A torus around `center`, whose ring lies in the plane normal to
+    /// `axis`. The axis does not need to be normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the axis is zero or a radius is not positive.
+    pub fn new(center: Vector3, axis: Vector3, major_radius: f64, minor_radius: f64) -> Self {
+        assert!(axis.norm2() > 0., "the axis must not be zero");
+        assert!(
+            major_radius > 0. && minor_radius > 0.,
+            "the radii must be positive"
+        );
+        TorusBuilder {
+            center,
+            axis: axis.normalized(),
+            major_radius,
+            minor_radius,
+            surface: Surface::new(),
+        }
+    }
+
+    pub fn build(&self) -> Torus {
+        let (u_axis, v_axis) = self.axis.calculate_orthonormal_basis();
+
+        Torus {
+            center: self.center,
+            u_axis,
+            v_axis,
+            axis: self.axis,
+            major_radius: self.major_radius,
+            minor_radius: self.minor_radius,
+            surface: self.surface.clone(),
+        }
+    }
+}
+
+impl SurfaceBuilder for TorusBuilder {
+    fn get_surface_mut(&mut self) -> &mut Surface {
+        &mut self.surface
+    }
+}
+
+impl Torus {
+    pub fn get_center(&self) -> &Vector3 {
+        &self.center
+    }
+
+    pub fn get_axis(&self) -> &Vector3 {
+        &self.axis
+    }
+
+    pub fn get_major_radius(&self) -> f64 {
+        self.major_radius
+    }
+
+    pub fn get_minor_radius(&self) -> f64 {
+        self.minor_radius
+    }
+
+    fn calculate_local_direction(&self, direction: &Vector3) -> Vector3 {
+        Vector3::new(
+            direction.dot(&self.u_axis),
+            direction.dot(&self.v_axis),
+            direction.dot(&self.axis),
+        )
+    }
+
+    /// Outward normal at a point of the torus, in the local frame: the
+    /// direction from the closest point of the central circle.
+    fn calculate_local_normal(&self, point: &Vector3) -> Vector3 {
+        let radial = Vector3::new(point.x(), point.y(), 0.);
+        let radial_norm = radial.norm();
+        if radial_norm == 0. {
+            return Vector3::new(0., 0., point.z().signum());
+        }
+        (*point - radial * (self.major_radius / radial_norm)).normalized()
+    }
+
+    /// Texture coordinates: the angle around the axis, and the angle around
+    /// the tube, both mapped to `[0, 1)`.
+    fn calculate_mapping_point(&self, point: &Vector3) -> Vector3 {
+        let radial_distance = (point.x() * point.x() + point.y() * point.y()).sqrt();
+        let u = (point.y().atan2(point.x()) + PI) / (2. * PI);
+        let v = (point.z().atan2(radial_distance - self.major_radius) + PI) / (2. * PI);
+        Vector3::new(
+            u.clamp(0., 1. - f64::EPSILON),
+            v.clamp(0., 1. - f64::EPSILON),
+            0.,
+        )
+    }
+
+    /// Smallest positive distance along the ray to the torus, if any.
+    fn calculate_distance(&self, ray: &Ray) -> Option<f64> {
+        let direction = self.calculate_local_direction(ray.get_direction());
+        let origin = self.calculate_local_direction(&(*ray.get_origin() - self.center));
+
+        // Start from the bounding sphere of the torus: this rejects most rays
+        // early, and keeps the coefficients of the quartic well scaled for
+        // rays coming from far away.
+        let bounding_radius = self.major_radius + self.minor_radius;
+        let a = direction.norm2();
+        let sphere_distances = solve_quadratic(
+            a,
+            2. * origin.dot(&direction),
+            origin.norm2() - bounding_radius * bounding_radius,
+        );
+        let (&enter, &exit) = (sphere_distances.first()?, sphere_distances.last()?);
+        if exit <= 0. {
+            return None;
+        }
+        let start = enter.max(0.);
+        let origin = origin + direction * start;
+
+        // (|p|² + R² - r²)² = 4 R² (x² + y²), with p = origin + t direction
+        let major_radius2 = self.major_radius * self.major_radius;
+        let b = 2. * origin.dot(&direction);
+        let g = origin.norm2() + major_radius2 - self.minor_radius * self.minor_radius;
+        let planar_direction2 = direction.x() * direction.x() + direction.y() * direction.y();
+        let planar_dot = origin.x() * direction.x() + origin.y() * direction.y();
+        let planar_origin2 = origin.x() * origin.x() + origin.y() * origin.y();
+
+        solve_quartic(
+            a * a,
+            2. * a * b,
+            b * b + 2. * a * g - 4. * major_radius2 * planar_direction2,
+            2. * b * g - 8. * major_radius2 * planar_dot,
+            g * g - 4. * major_radius2 * planar_origin2,
+        )
+        .into_iter()
+        .map(|distance| distance + start)
+        .find(|distance| *distance > 0.)
+    }
+}
+
+impl Intersectable for Torus {
+    fn intersect(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let distance = self.calculate_distance(ray)?;
+
+        let intersection_point = *ray.get_origin() + *ray.get_direction() * distance;
+        let local_point = self.calculate_local_direction(&(intersection_point - self.center));
+        let local_normal = self.calculate_local_normal(&local_point);
+        let normal = local_normal.x() * self.u_axis
+            + local_normal.y() * self.v_axis
+            + local_normal.z() * self.axis;
+
+        Some(Intersection::new(
+            intersection_point,
+            normal,
+            distance,
+            ray.get_direction().dot(&normal) < 0.,
+            Some(self.calculate_mapping_point(&local_point)),
+            Some(self),
+            *ray,
+        ))
+    }
+
+    fn calculate_bounding_box(&self) -> Option<BoundingBox> {
+        // The central circle, thickened by the tube
+        let circle_box = BoundingBox::new_from_disk(self.center, self.axis, self.major_radius);
+        Some(circle_box.calculate_padded(self.minor_radius))
+    }
+}
+
+impl Object for Torus {
+    fn is_opaque(&self) -> bool {
+        self.surface.is_opaque()
+    }
+
+    fn is_mirror(&self) -> bool {
+        self.surface.is_mirror()
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.surface.is_transparent()
+    }
+
+    fn is_light_source(&self) -> bool {
+        self.surface.is_light_source()
+    }
+
+    fn get_color(&self) -> &Vector3 {
+        self.surface.get_color()
+    }
+
+    fn calculate_color(&self, intersection: &Intersection) -> Vector3 {
+        self.surface.calculate_color(intersection)
+    }
+
+    fn get_refractive_index(&self) -> f64 {
+        self.surface.get_refractive_index()
+    }
+
+    fn get_light_intensity(&self) -> f64 {
+        self.surface.get_light_intensity()
+    }
+}
@@ -0,0 +1,772 @@
+use {
+    raytracing_rust::{
+        BVHTree, Camera, Csg, Mesh, MeshObjectBuilder, PlaneBuilder, PointLightSource,
+        QuadricBuilder, Quaternion, RenderSettings, Result, Scene, SceneDescription, SphereBuilder,
+        SurfaceBuilder, Texture, TorusBuilder, Vector3,
+    },
+    std::{f64::consts::PI, sync::Arc},
+};
+
+pub struct Demo {
+    name: &'static str,
+    description: &'static str,
+    build: fn(&RenderSettings) -> Result<Vec<SceneDescription>>,
+}
+
+impl Demo {
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Build the scenes of the demo, one per frame to render.
+    pub fn build(&self, settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+        (self.build)(settings)
+    }
+}
+
+pub const DEMOS: &[Demo] = &[
+    Demo {
+        name: "basic_spheres",
+        description: "A white sphere lit by a point light",
+        build: basic_spheres_demo,
+    },
+    Demo {
+        name: "mirror_glass",
+        description: "Mirror, glass and diffuse spheres",
+        build: mirror_glass_demo,
+    },
+    Demo {
+        name: "fresnel",
+        description: "Fresnel reflections on a glass sphere",
+        build: fresnel_demo,
+    },
+    Demo {
+        name: "indirect_lighting",
+        description: "Colored spheres lit by indirect lighting",
+        build: indirect_lighting_demo,
+    },
+    Demo {
+        name: "antialiasing",
+        description: "Several spheres showing anti-aliased edges",
+        build: antialiasing_demo,
+    },
+    Demo {
+        name: "spherical_lights",
+        description: "Glass and mirror spheres lit by spherical lights",
+        build: spherical_lights_demo,
+    },
+    Demo {
+        name: "quadrics",
+        description: "A box, a mirror cylinder, a glass cone and a disk",
+        build: quadrics_demo,
+    },
+    Demo {
+        name: "tori",
+        description: "Glass and colored tori, showing refraction through a quartic surface",
+        build: tori_demo,
+    },
+    Demo {
+        name: "csg",
+        description: "A glass lens, a hollowed-out cube and a capsule built with CSG",
+        build: csg_demo,
+    },
+    Demo {
+        name: "meshes",
+        description: "Mirror and glass cat meshes",
+        build: meshes_demo,
+    },
+    Demo {
+        name: "mesh_normals_and_texture_mapping",
+        description: "A textured cat mesh with interpolated normals",
+        build: mesh_normals_and_texture_mapping_demo,
+    },
+    Demo {
+        name: "cacodemon",
+        description: "The textured cacodemon among spheres",
+        build: custom_demo,
+    },
+    Demo {
+        name: "spinning_cat",
+        description: "16 frames of a cat spinning around its vertical axis",
+        build: spinning_cat,
+    },
+];
+
+pub fn find_demo(name: &str) -> Option<&'static Demo> {
+    DEMOS.iter().find(|demo| demo.name == name)
+}
+
+/// Enclose the scene in a colored box, with the floor at `z = -10`.
+fn add_walls(scene: &mut Scene) -> &mut Scene {
+    // Teal wall
+    let left_wall = PlaneBuilder::new(Vector3::new(-60., 0., 0.), Vector3::new(1., 0., 0.))
+        .with_color(Vector3::new(0., 1., 1.))
+        .build();
+    // Yellow wall
+    let right_wall = PlaneBuilder::new(Vector3::new(60., 0., 0.), Vector3::new(-1., 0., 0.))
+        .with_color(Vector3::new(1., 1., 0.))
+        .build();
+    // Red ceiling
+    let ceiling = PlaneBuilder::new(Vector3::new(0., 0., 60.), Vector3::new(0., 0., -1.))
+        .with_color(Vector3::new(1., 0., 0.))
+        .build();
+    // Blue floor
+    let floor = PlaneBuilder::new(Vector3::new(0., 0., -10.), Vector3::new(0., 0., 1.))
+        .with_color(Vector3::new(0., 0., 1.))
+        .build();
+    // Purple wall
+    let front_wall = PlaneBuilder::new(Vector3::new(0., -60., 0.), Vector3::new(0., 1., 0.))
+        .with_color(Vector3::new(1., 0., 1.))
+        .build();
+    // Green wall
+    let back_wall = PlaneBuilder::new(Vector3::new(0., 60., 0.), Vector3::new(0., -1., 0.))
+        .with_color(Vector3::new(0., 1., 0.))
+        .build();
+
+    scene
+        .add_object(Box::new(left_wall))
+        .add_object(Box::new(right_wall))
+        .add_object(Box::new(ceiling))
+        .add_object(Box::new(floor))
+        .add_object(Box::new(back_wall))
+        .add_object(Box::new(front_wall))
+}
+
+fn basic_spheres_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let white_sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
+        .with_color(Vector3::new(1., 1., 1.))
+        .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(20., 20., 20.), 5E9);
+
+    scene
+        .add_object(Box::new(white_sphere))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 55., 0.),
+        Vector3::new(0., 0., PI),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/basic_spheres_demo.png",
+        *settings,
+    )])
+}
+
+fn mirror_glass_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let mirror_sphere = SphereBuilder::new(Vector3::new(-25., 0., 0.), 10.)
+        .with_mirror(true)
+        .build();
+    let red_sphere = SphereBuilder::new(Vector3::new(25., 0., 0.), 10.)
+        .with_color(Vector3::new(1., 0., 0.))
+        .build();
+    let transparent_sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
+        .with_refractive_index(1.5)
+        .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(20., 20., 20.), 5E9);
+
+    scene
+        .add_object(Box::new(mirror_sphere))
+        .add_object(Box::new(red_sphere))
+        .add_object(Box::new(transparent_sphere))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 55., 0.),
+        Vector3::new(0., 0., PI),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/mirror_glass_spheres_demo.png",
+        *settings,
+    )])
+}
+
+fn fresnel_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let transparent_sphere = SphereBuilder::new(Vector3::new(0., 0., 10.), 15.)
+        .with_refractive_index(1.5)
+        .build();
+    let mirror_sphere = SphereBuilder::new(Vector3::new(25., 0., 0.), 10.)
+        .with_mirror(true)
+        .build();
+    let red_sphere = SphereBuilder::new(Vector3::new(-25., 15., 20.), 5.)
+        .with_color(Vector3::new(1., 0., 0.))
+        .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(20., 20., 30.), 5E9);
+
+    scene
+        .add_object(Box::new(mirror_sphere))
+        .add_object(Box::new(red_sphere))
+        .add_object(Box::new(transparent_sphere))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(30., 20., 5.),
+        Vector3::new(0., PI / 8., 3. * PI / 4.),
+        90. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/fresnel_demo.png",
+        *settings,
+    )])
+}
+
+fn indirect_lighting_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let red_sphere = SphereBuilder::new(Vector3::new(0., 10., 0.), 10.)
+        .with_color(Vector3::new(1., 0., 0.))
+        .build();
+    let pink_sphere = SphereBuilder::new(Vector3::new(0., 50., 0.), 10.)
+        .with_color(Vector3::new(1., 0.5, 0.5))
+        .build();
+    let orange_sphere = SphereBuilder::new(Vector3::new(-20., 30., 0.), 10.)
+        .with_color(Vector3::new(1., 0.5, 0.))
+        .build();
+    let yellow_sphere = SphereBuilder::new(Vector3::new(20., 30., 0.), 10.)
+        .with_color(Vector3::new(1., 1., 0.))
+        .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(0., 30., 0.), 5E9);
+
+    scene
+        .add_object(Box::new(red_sphere))
+        .add_object(Box::new(pink_sphere))
+        .add_object(Box::new(orange_sphere))
+        .add_object(Box::new(yellow_sphere))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(25., 55., 20.),
+        Vector3::new(-PI / 4., 0., 3. * PI / 4.),
+        90. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/indirect_lighting_demo.png",
+        *settings,
+    )])
+}
+
+fn antialiasing_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let red_sphere = SphereBuilder::new(Vector3::new(20., -40., 0.), 10.)
+        .with_color(Vector3::new(1., 0., 0.))
+        .build();
+    let glass_sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
+        .with_refractive_index(1.5)
+        .build();
+    let mirror_sphere = SphereBuilder::new(Vector3::new(-40., 20., 0.), 10.)
+        .with_mirror(true)
+        .build();
+
+    let white_sphere = SphereBuilder::new(Vector3::new(40., 10., 0.), 10.)
+        .with_color(Vector3::new(1., 1., 1.))
+        .build();
+    let purple_sphere = SphereBuilder::new(Vector3::new(10., 40., 0.), 10.)
+        .with_color(Vector3::new(1., 0., 1.))
+        .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(-30., -30., 40.), 5E9);
+
+    scene
+        .add_object(Box::new(red_sphere))
+        .add_object(Box::new(glass_sphere))
+        .add_object(Box::new(mirror_sphere))
+        .add_object(Box::new(white_sphere))
+        .add_object(Box::new(purple_sphere))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(50., 50., 40.),
+        Vector3::new(-PI / 4., 0., 3. * PI / 4.),
+        90. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/antialiasing_demo.png",
+        *settings,
+    )])
+}
+
+fn spherical_lights_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let glass_sphere = SphereBuilder::new(Vector3::new(-20., 10., 0.), 10.)
+        .with_refractive_index(1.5)
+        .build();
+    let mirror_sphere = SphereBuilder::new(Vector3::new(20., 10., 0.), 10.)
+        .with_mirror(true)
+        .build();
+    let light_sphere = SphereBuilder::new(Vector3::new(0., -30., 15.), 5.)
+        .with_light_intensity(5E9)
+        .build();
+    let light_sphere2 = SphereBuilder::new(Vector3::new(0., 40., 25.), 5.)
+        .with_light_intensity(5E9)
+        .build();
+
+    let mut scene = Scene::new();
+
+    scene
+        .add_object(Box::new(glass_sphere))
+        .add_object(Box::new(mirror_sphere))
+        .add_object(Box::new(light_sphere.clone()))
+        .add_object(Box::new(light_sphere2.clone()))
+        .add_light_source(Box::new(light_sphere))
+        .add_light_source(Box::new(light_sphere2));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(50., 50., 40.),
+        Vector3::new(-PI / 4., 0., 3. * PI / 4.),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/spherical_lights_demo.png",
+        *settings,
+    )])
+}
+
+fn quadrics_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let white_box =
+        QuadricBuilder::new_box(Vector3::new(-35., -10., -10.), Vector3::new(-15., 10., 10.))
+            .with_color(Vector3::new(1., 1., 1.))
+            .build();
+    let mirror_cylinder =
+        QuadricBuilder::new_cylinder(Vector3::new(-5., 0., -10.), Vector3::new(-5., 0., 15.), 7.)
+            .with_mirror(true)
+            .build();
+    let glass_cone =
+        QuadricBuilder::new_cone(Vector3::new(20., 0., -10.), Vector3::new(20., 0., 15.), 10.)
+            .with_refractive_index(1.5)
+            .build();
+    let orange_disk =
+        QuadricBuilder::new_disk(Vector3::new(0., -20., 25.), Vector3::new(0., 1., -1.), 8.)
+            .with_color(Vector3::new(1., 0.5, 0.))
+            .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(0., 30., 40.), 5E9);
+
+    scene
+        .add_object(Box::new(white_box))
+        .add_object(Box::new(mirror_cylinder))
+        .add_object(Box::new(glass_cone))
+        .add_object(Box::new(orange_disk))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 55., 10.),
+        Vector3::new(-PI / 18., 0., PI),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/quadrics_demo.png",
+        *settings,
+    )])
+}
+
+fn tori_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let glass_torus = TorusBuilder::new(
+        Vector3::new(-15., 0., 5.),
+        Vector3::new(0., 1., 0.5),
+        12.,
+        4.,
+    )
+    .with_refractive_index(1.5)
+    .build();
+    let orange_torus = TorusBuilder::new(
+        Vector3::new(20., 10., -6.),
+        Vector3::new(0., 0., 1.),
+        10.,
+        3.,
+    )
+    .with_color(Vector3::new(1., 0.5, 0.))
+    .build();
+    let mirror_sphere = SphereBuilder::new(Vector3::new(-15., 20., 0.), 8.)
+        .with_mirror(true)
+        .build();
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(0., 30., 40.), 5E9);
+
+    scene
+        .add_object(Box::new(glass_torus))
+        .add_object(Box::new(orange_torus))
+        .add_object(Box::new(mirror_sphere))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 55., 10.),
+        Vector3::new(-PI / 18., 0., PI),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/tori_demo.png",
+        *settings,
+    )])
+}
+
+fn csg_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    // Biconvex lens: the intersection of two overlapping glass spheres
+    let lens = Csg::intersection(
+        Box::new(
+            SphereBuilder::new(Vector3::new(-20., -15., 5.), 20.)
+                .with_refractive_index(1.5)
+                .build(),
+        ),
+        Box::new(
+            SphereBuilder::new(Vector3::new(-20., 15., 5.), 20.)
+                .with_refractive_index(1.5)
+                .build(),
+        ),
+    );
+
+    // White cube with a red spherical hollow carved into its corner
+    let hollow_cube = Csg::difference(
+        Box::new(
+            QuadricBuilder::new_box(Vector3::new(10., -10., -10.), Vector3::new(30., 10., 10.))
+                .with_color(Vector3::new(1., 1., 1.))
+                .build(),
+        ),
+        Box::new(
+            SphereBuilder::new(Vector3::new(30., 10., 10.), 12.)
+                .with_color(Vector3::new(1., 0., 0.))
+                .build(),
+        ),
+    );
+
+    // Mirror capsule: a cylinder with two spheres at its ends
+    let capsule = Csg::union(
+        Box::new(
+            QuadricBuilder::new_cylinder(
+                Vector3::new(-5., 25., -4.),
+                Vector3::new(5., 25., -4.),
+                5.,
+            )
+            .with_mirror(true)
+            .build(),
+        ),
+        Box::new(Csg::union(
+            Box::new(
+                SphereBuilder::new(Vector3::new(-5., 25., -4.), 5.)
+                    .with_mirror(true)
+                    .build(),
+            ),
+            Box::new(
+                SphereBuilder::new(Vector3::new(5., 25., -4.), 5.)
+                    .with_mirror(true)
+                    .build(),
+            ),
+        )),
+    );
+
+    let mut scene = Scene::new();
+
+    let point_light_source = PointLightSource::new(Vector3::new(0., 30., 40.), 5E9);
+
+    scene
+        .add_object(Box::new(lens))
+        .add_object(Box::new(hollow_cube))
+        .add_object(Box::new(capsule))
+        .add_light_source(Box::new(point_light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 55., 10.),
+        Vector3::new(-PI / 18., 0., PI),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/csg_demo.png",
+        *settings,
+    )])
+}
+
+fn meshes_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let cat_bvh = Arc::new(BVHTree::new_from_file("assets/cat/cat.obj", settings)?);
+
+    let mut builder = MeshObjectBuilder::new_instance(&cat_bvh);
+    builder
+        .with_rotation(Vector3::new(PI / 2., 0., 0.))
+        .with_translation(Vector3::new(-10., 25., -15.))
+        .with_scale(0.6)
+        .with_mirror(true);
+
+    let cat_object = builder.build();
+
+    let mut builder2 = MeshObjectBuilder::new_instance(&cat_bvh);
+    builder2
+        .with_rotation(Vector3::new(-PI / 2., 0., PI))
+        .with_translation(Vector3::new(10., 25., 50.))
+        .with_scale(0.6)
+        .with_refractive_index(1.5);
+
+    let cat_object2 = builder2.build();
+
+    let light_source = PointLightSource::new(Vector3::new(0., 30., 20.), 5E9);
+
+    let mut scene = Scene::new();
+
+    scene
+        .add_object(Box::new(cat_object))
+        .add_object(Box::new(cat_object2))
+        .add_light_source(Box::new(light_source));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 55., 10.),
+        Vector3::new(0., 0., PI),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/meshes_demo.png",
+        *settings,
+    )])
+}
+
+fn mesh_normals_and_texture_mapping_demo(
+    settings: &RenderSettings,
+) -> Result<Vec<SceneDescription>> {
+    let cat_obj_file = "assets/cat/cat.obj";
+
+    let cat_mesh = Mesh::from_obj_file(cat_obj_file)?;
+    let cat_texture = Texture::from_obj_file(cat_obj_file)?;
+
+    let mut builder = MeshObjectBuilder::new(&cat_mesh);
+    builder
+        .with_settings(settings)
+        .with_rotation(Vector3::new(PI / 2., 0., -PI))
+        .with_translation(Vector3::new(20., 20., -15.))
+        .with_scale(0.6)
+        .with_texture(cat_texture);
+
+    let cat_object = builder.build();
+
+    let intensity = 5E9;
+
+    let light_sphere = SphereBuilder::new(Vector3::new(10., 25., 10.), 5.)
+        .with_light_intensity(intensity)
+        .build();
+
+    let mirror_sphere = SphereBuilder::new(Vector3::new(-25., 20., 5.), 15.)
+        .with_mirror(true)
+        .build();
+
+    let mut scene = Scene::new();
+
+    scene
+        .add_object(Box::new(cat_object))
+        .add_object(Box::new(mirror_sphere))
+        .add_object(Box::new(light_sphere.clone()))
+        .add_light_source(Box::new(light_sphere));
+
+    add_walls(&mut scene);
+
+    let camera = Camera::new(
+        Vector3::new(0., 35., 5.),
+        Vector3::new(0., 0., 7. * PI / 8.),
+        90. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/mesh_normals_and_texture_mapping_demo.png",
+        *settings,
+    )])
+}
+
+fn custom_demo(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    let cacodemon_obj_file = "assets/cacodemon/cacodemon.obj";
+
+    let cacodemon_mesh = Mesh::from_obj_file(cacodemon_obj_file)?;
+    let cacodemon_texture = Texture::from_obj_file(cacodemon_obj_file)?;
+
+    let mut builder = MeshObjectBuilder::new(&cacodemon_mesh);
+    builder
+        .with_settings(settings)
+        .with_rotation(Vector3::new(PI / 2., 0., PI / 2.))
+        .with_scale(0.1)
+        .with_texture(cacodemon_texture);
+
+    let cacodemon_object = builder.build();
+
+    let intensity = 5E9;
+
+    let sphere_light = SphereBuilder::new(Vector3::new(-10., 40., 10.), 5.)
+        .with_light_intensity(intensity)
+        .build();
+
+    let mut scene = Scene::new();
+
+    let back_sphere = SphereBuilder::new(Vector3::new(-1000., -1000., 0.), 1400.)
+        .with_color(Vector3::new(0.3, 0.3, 0.3))
+        .build();
+
+    let dark_red = Vector3::new(0.5, 0., 0.);
+
+    let sphere_1 = SphereBuilder::new(Vector3::new(19., -3., 22.), 7.)
+        .with_color(dark_red)
+        .build();
+
+    let mirror_sphere = SphereBuilder::new(Vector3::new(-14., 14., -14.), 6.)
+        .with_mirror(true)
+        .build();
+
+    let glass_sphere = SphereBuilder::new(Vector3::new(-16., 16., 16.), 8.)
+        .with_refractive_index(1.5)
+        .build();
+
+    scene
+        .add_object(Box::new(cacodemon_object))
+        .add_object(Box::new(back_sphere))
+        .add_object(Box::new(sphere_1))
+        .add_object(Box::new(mirror_sphere))
+        .add_object(Box::new(glass_sphere))
+        .add_object(Box::new(sphere_light.clone()))
+        .add_light_source(Box::new(sphere_light));
+
+    let camera = Camera::new(
+        Vector3::new(15., 35., -17.),
+        Vector3::new(PI / 8., 0., 7. * PI / 8.),
+        75. * PI / 180.,
+    );
+
+    Ok(vec![SceneDescription::new(
+        scene,
+        camera,
+        512,
+        512,
+        "figures/cacodemon_demo.png",
+        *settings,
+    )])
+}
+
+fn spinning_cat(settings: &RenderSettings) -> Result<Vec<SceneDescription>> {
+    // The frames share the mesh of the cat and its BVH
+    let cat_bvh = Arc::new(BVHTree::new_from_file("assets/cat/cat.obj", settings)?);
+
+    let descriptions = (0..16)
+        .map(|theta| {
+            let mut builder = MeshObjectBuilder::new_instance(&cat_bvh);
+            let spin =
+                Quaternion::new_from_axis_angle(Vector3::new(0., 0., 1.), theta as f64 * PI / 8.);
+
+            builder
+                .with_rotation(Vector3::new(PI / 2., 0., PI / 2.))
+                .with_quaternion_rotation(&spin)
+                .with_translation(Vector3::new(0., 0., -21.5))
+                .with_scale(0.8)
+                .with_color(Vector3::new(0.71764705882, 0.25490196078, 0.05490196078));
+
+            let cat_object = builder.build();
+
+            let light_source = PointLightSource::new(Vector3::new(0., 55., 0.), 5E9);
+
+            let mut scene = Scene::new();
+
+            scene
+                .add_object(Box::new(cat_object))
+                .add_light_source(Box::new(light_source));
+
+            let camera = Camera::new(
+                Vector3::new(0., 55., 0.),
+                Vector3::new(0., 0., PI),
+                75. * PI / 180.,
+            );
+
+            SceneDescription::new(
+                scene,
+                camera,
+                512,
+                512,
+                &format!("spinning_cat/cat_{}.png", theta),
+                *settings,
+            )
+        })
+        .collect();
+
+    Ok(descriptions)
+}
@@ -0,0 +1,122 @@
+use std::{error, fmt, io};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors raised while loading scenes, meshes and textures, or while writing
+/// rendered images.
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be read or written.
+    Io { path: String, source: io::Error },
+    /// A text file (scene, OBJ, MTL) is malformed.
+    Parse(ParseError),
+    /// A material or texture referenced by a file could not be found.
+    MissingMaterial { path: String, message: String },
+    /// An image file could not be decoded.
+    ImageDecode {
+        path: String,
+        source: image::ImageError,
+    },
+    /// An image could not be encoded and written to a file.
+    ImageEncode {
+        path: String,
+        source: image::ImageError,
+    },
+}
+
+impl Error {
+    pub(crate) fn io(path: &str, source: io::Error) -> Self {
+        Error::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub(crate) fn parse(
+        file: &str,
+        line: Option<usize>,
+        key: Option<&str>,
+        message: String,
+    ) -> Self {
+        Error::Parse(ParseError {
+            file: file.to_string(),
+            line,
+            key: key.map(|key| key.to_string()),
+            message,
+        })
+    }
+
+    pub(crate) fn missing_material(path: &str, message: String) -> Self {
+        Error::MissingMaterial {
+            path: path.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Parse(parse_error) => write!(f, "{}", parse_error),
+            Error::MissingMaterial { path, message } => write!(f, "{}: {}", path, message),
+            Error::ImageDecode { path, source } => {
+                write!(f, "{}: could not decode image: {}", path, source)
+            }
+            Error::ImageEncode { path, source } => {
+                write!(f, "{}: could not write image: {}", path, source)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse(_) | Error::MissingMaterial { .. } => None,
+            Error::ImageDecode { source, .. } | Error::ImageEncode { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Location and description of a syntax error in a text file.
+#[derive(Debug)]
+pub struct ParseError {
+    file: String,
+    line: Option<usize>,
+    key: Option<String>,
+    message: String,
+}
+
+impl ParseError {
+    pub fn get_file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn get_line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// The offending key or directive, if the error is tied to one.
+    pub fn get_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, ": `{}`", key)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
@@ -0,0 +1,1017 @@
+//! Importer for binary glTF 2.0 (`.glb`) files.
+//!
+//! The meshes of the nodes of the default scene become `MeshObject`s, placed
+//! by the transforms of the nodes and their parents. Their triangles read
+//! the positions, normals (generated when missing) and first texture
+//! coordinates of the file. Each object is textured by the base color
+//! texture of its material, or else colored by its base color factor. The
+//! first perspective camera of the scene gives the `Camera`.
+//!
+//! Images and buffers may be embedded in the file or stored next to it.
+//! Animations, skins, other primitive modes and extensions are not supported:
+//! they are ignored and reported as warnings.
+
+use {
+    crate::{
+        core::{
+            BVHTree, IndexedMeshData, Mesh, MeshObject, MeshObjectBuilder, RenderSettings, Texture,
+            DEFAULT_GROUP_NAME,
+        },
+        error::{Error, Result},
+        io::json::JsonValue,
+        utils::{Matrix4, Quaternion, Transform, Vector3},
+        view::Camera,
+    },
+    std::{borrow::Cow, collections::HashMap, f64::consts::PI, fs, path::Path, str, sync::Arc},
+};
+
+const GLB_MAGIC: &[u8] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const GLB_HEADER_SIZE: usize = 12;
+const CHUNK_HEADER_SIZE: usize = 8;
+const JSON_CHUNK_TYPE: u32 = 0x4E4F_534A;
+const BIN_CHUNK_TYPE: u32 = 0x004E_4942;
+const TRIANGLES_MODE: usize = 4;
+const SUPPORTED_ATTRIBUTES: [&str; 3] = ["POSITION", "NORMAL", "TEXCOORD_0"];
+const SUPPORTED_IMAGE_TYPES: [&str; 2] = ["image/png", "image/jpeg"];
+const DEFAULT_BASE_COLOR: Vector3 = Vector3::new(1., 1., 1.);
+/// glTF cameras look along -Z, with Y pointing up
+const CAMERA_FORWARD: Vector3 = Vector3::new(0., 0., -1.);
+const CAMERA_UP: Vector3 = Vector3::new(0., 1., 0.);
+
+/// The objects and camera of a glTF file, ready to be added to a `Scene`.
+///
+/// glTF files use a Y up coordinate system: `with_transform` moves the
+/// whole scene, for instance to turn it Z up.
+pub struct GltfScene {
+    instances: Vec<MeshInstance>,
+    textures: Vec<Texture>,
+    camera: Option<GltfCamera>,
+    /// Applied after the transforms of the nodes
+    transform: Transform,
+    warnings: Vec<String>,
+}
+
+/// A primitive of a mesh placed by a node.
+struct MeshInstance {
+    bvh: Arc<BVHTree>,
+    transform: Transform,
+    material: Material,
+}
+
+#[derive(Clone, Copy)]
+struct Material {
+    color: Vector3,
+    /// Index in `GltfScene::textures`
+    texture: Option<usize>,
+}
+
+struct GltfCamera {
+    transform: Transform,
+    vertical_fov: f64,
+}
+
+impl GltfScene {
+    /// Load a `.glb` file, building the BVHs of its meshes with the settings.
+    pub fn from_glb_file(filename: &str, settings: &RenderSettings) -> Result<Self> {
+        let bytes = fs::read(filename).map_err(|err| Error::io(filename, err))?;
+        let parse_error = |message| Error::parse(filename, None, None, message);
+
+        let (json_chunk, bin_chunk) = split_chunks(&bytes).map_err(parse_error)?;
+        let json = str::from_utf8(json_chunk)
+            .map_err(|_| parse_error("the JSON chunk is not valid UTF-8".to_string()))?;
+        let root = JsonValue::parse(json)
+            .map_err(|message| parse_error(format!("invalid JSON chunk: {}", message)))?;
+
+        let mut loader = GltfLoader {
+            filename,
+            base_directory: Path::new(filename)
+                .parent()
+                .unwrap_or_else(|| Path::new("")),
+            root: &root,
+            settings,
+            buffers: Vec::new(),
+            meshes: HashMap::new(),
+            materials: HashMap::new(),
+            images: HashMap::new(),
+            textures: Vec::new(),
+            warnings: Vec::new(),
+        };
+        loader.check_version()?;
+        loader.report_unsupported_features();
+        loader.load_buffers(bin_chunk)?;
+        let (instances, camera) = loader.load_scene()?;
+
+        Ok(GltfScene {
+            instances,
+            textures: loader.textures,
+            camera,
+            transform: Transform::IDENTITY,
+            warnings: loader.warnings,
+        })
+    }
+
+    /// Move the whole scene, objects and camera. Transformations are applied
+    /// in the order of the calls.
+    pub fn with_transform(&mut self, transform: &Transform) -> &mut Self {
+        self.transform = self.transform.then(transform);
+        self
+    }
+
+    /// Build the mesh objects, one per primitive of each mesh node. Nodes
+    /// sharing a mesh share its BVH.
+    pub fn build_objects(&self) -> Vec<MeshObject> {
+        self.instances
+            .iter()
+            .map(|instance| {
+                let mut builder = MeshObjectBuilder::new_instance(&instance.bvh);
+                builder.with_transform(&instance.transform.then(&self.transform));
+                match instance.material.texture {
+                    Some(texture_index) => {
+                        builder.with_texture(self.textures[texture_index].clone())
+                    }
+                    None => builder.with_color(instance.material.color),
+                };
+                builder.build()
+            })
+            .collect()
+    }
+
+    pub fn has_camera(&self) -> bool {
+        self.camera.is_some()
+    }
+
+    /// The camera of the file, for an image of the given size. glTF cameras
+    /// have a vertical field of view, so the horizontal one depends on the
+    /// aspect ratio of the image.
+    pub fn calculate_camera(&self, width: u32, height: u32) -> Option<Camera> {
+        let camera = self.camera.as_ref()?;
+        let transform = camera.transform.then(&self.transform);
+
+        let position = transform.transform_point(&Vector3::new(0., 0., 0.));
+        let orientation = Quaternion::new_look_rotation(
+            transform.transform_direction(&CAMERA_FORWARD),
+            transform.transform_direction(&CAMERA_UP),
+        );
+        let aspect_ratio = width as f64 / height as f64;
+        let fov = 2. * ((camera.vertical_fov / 2.).tan() * aspect_ratio).atan();
+        Some(Camera::new_with_orientation(position, &orientation, fov))
+    }
+
+    /// Features of the file that were ignored.
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+}
+
+/// The JSON chunk and the optional binary chunk of a `.glb` file.
+fn split_chunks(bytes: &[u8]) -> std::result::Result<(&[u8], Option<&[u8]>), String> {
+    let read_u32 = |offset: usize| {
+        let value = &bytes[offset..offset + 4];
+        u32::from_le_bytes([value[0], value[1], value[2], value[3]])
+    };
+
+    if bytes.len() < GLB_HEADER_SIZE || !bytes.starts_with(GLB_MAGIC) {
+        return Err("not a binary glTF file".to_string());
+    }
+    let version = read_u32(4);
+    if version != GLB_VERSION {
+        return Err(format!("unsupported glTF version {}", version));
+    }
+    let length = read_u32(8) as usize;
+    if length > bytes.len() {
+        return Err(format!(
+            "truncated file, expected {} bytes, found {}",
+            length,
+            bytes.len()
+        ));
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = GLB_HEADER_SIZE;
+    while offset + CHUNK_HEADER_SIZE <= length {
+        let chunk_length = read_u32(offset) as usize;
+        let chunk_type = read_u32(offset + 4);
+        let start = offset + CHUNK_HEADER_SIZE;
+        if start + chunk_length > length {
+            return Err(format!("truncated chunk at byte {}", offset));
+        }
+        chunks.push((chunk_type, &bytes[start..start + chunk_length]));
+        offset = start + chunk_length;
+    }
+
+    // Chunks of unknown types are skipped, as the specification requires
+    match chunks[..] {
+        [(JSON_CHUNK_TYPE, json), ref others @ ..] => {
+            let bin = others
+                .first()
+                .filter(|(chunk_type, _)| *chunk_type == BIN_CHUNK_TYPE)
+                .map(|(_, bin)| *bin);
+            Ok((json, bin))
+        }
+        _ => Err("the first chunk is not a JSON chunk".to_string()),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ComponentType {
+    Byte,
+    UnsignedByte,
+    Short,
+    UnsignedShort,
+    UnsignedInt,
+    Float,
+}
+
+impl ComponentType {
+    fn from_code(code: usize) -> Option<Self> {
+        match code {
+            5120 => Some(ComponentType::Byte),
+            5121 => Some(ComponentType::UnsignedByte),
+            5122 => Some(ComponentType::Short),
+            5123 => Some(ComponentType::UnsignedShort),
+            5125 => Some(ComponentType::UnsignedInt),
+            5126 => Some(ComponentType::Float),
+            _ => None,
+        }
+    }
+
+    fn get_size(self) -> usize {
+        match self {
+            ComponentType::Byte | ComponentType::UnsignedByte => 1,
+            ComponentType::Short | ComponentType::UnsignedShort => 2,
+            ComponentType::UnsignedInt | ComponentType::Float => 4,
+        }
+    }
+
+    /// Read a little endian component. Normalized integers are mapped to
+    /// `[0, 1]`, or `[-1, 1]` if signed.
+    fn read(self, bytes: &[u8], normalized: bool) -> f64 {
+        let (value, max) = match self {
+            ComponentType::Byte => (bytes[0] as i8 as f64, i8::MAX as f64),
+            ComponentType::UnsignedByte => (bytes[0] as f64, u8::MAX as f64),
+            ComponentType::Short => (
+                i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                i16::MAX as f64,
+            ),
+            ComponentType::UnsignedShort => (
+                u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                u16::MAX as f64,
+            ),
+            ComponentType::UnsignedInt => (
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+                u32::MAX as f64,
+            ),
+            ComponentType::Float => {
+                return f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+        };
+        match normalized {
+            true => (value / max).max(-1.),
+            false => value,
+        }
+    }
+}
+
+struct GltfLoader<'a> {
+    filename: &'a str,
+    base_directory: &'a Path,
+    root: &'a JsonValue,
+    settings: &'a RenderSettings,
+    /// `None` for buffers that could not be loaded, which was reported
+    buffers: Vec<Option<Cow<'a, [u8]>>>,
+    /// BVH and material of each primitive of the loaded meshes
+    meshes: HashMap<usize, Vec<(Arc<BVHTree>, Material)>>,
+    materials: HashMap<usize, Material>,
+    /// Index in `textures` of the loaded images, `None` if not supported
+    images: HashMap<usize, Option<usize>>,
+    textures: Vec<Texture>,
+    warnings: Vec<String>,
+}
+
+impl<'a> GltfLoader<'a> {
+    fn error(&self, path: &str, message: String) -> Error {
+        Error::parse(self.filename, None, Some(path), message)
+    }
+
+    /// Record a warning, once even if the feature is used several times.
+    fn add_warning(&mut self, message: String) {
+        let warning = format!("{}: {}", self.filename, message);
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// An element of one of the top level arrays, such as `meshes`.
+    fn get_element(&self, array: &str, index: usize) -> Result<&'a JsonValue> {
+        self.root
+            .get(array)
+            .and_then(JsonValue::as_array)
+            .and_then(|elements| elements.get(index))
+            .ok_or_else(|| self.error(&format!("{}[{}]", array, index), "not found".to_string()))
+    }
+
+    fn get_index(&self, value: &JsonValue, key: &str, path: &str) -> Result<Option<usize>> {
+        match value.get(key) {
+            Some(index) => index.as_usize().map(Some).ok_or_else(|| {
+                self.error(
+                    &format!("{}.{}", path, key),
+                    "expected a non-negative integer".to_string(),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn get_required_index(&self, value: &JsonValue, key: &str, path: &str) -> Result<usize> {
+        self.get_index(value, key, path)?
+            .ok_or_else(|| self.error(&format!("{}.{}", path, key), "missing".to_string()))
+    }
+
+    fn get_numbers<const N: usize>(
+        &self,
+        value: &JsonValue,
+        key: &str,
+        path: &str,
+    ) -> Result<Option<[f64; N]>> {
+        let Some(array) = value.get(key) else {
+            return Ok(None);
+        };
+        let error = || {
+            self.error(
+                &format!("{}.{}", path, key),
+                format!("expected {} numbers", N),
+            )
+        };
+        let elements = array.as_array().ok_or_else(error)?;
+        if elements.len() != N {
+            return Err(error());
+        }
+        let mut numbers = [0.; N];
+        for (number, element) in numbers.iter_mut().zip(elements) {
+            *number = element.as_f64().ok_or_else(error)?;
+        }
+        Ok(Some(numbers))
+    }
+
+    fn check_version(&self) -> Result<()> {
+        let version = self
+            .root
+            .get("asset")
+            .and_then(|asset| asset.get("version"))
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| self.error("asset.version", "missing".to_string()))?;
+        match version.split('.').next() {
+            Some("2") => Ok(()),
+            _ => Err(self.error(
+                "asset.version",
+                format!("unsupported glTF version {}", version),
+            )),
+        }
+    }
+
+    fn report_unsupported_features(&mut self) {
+        let names = |key: &str| -> Vec<String> {
+            self.root
+                .get(key)
+                .and_then(JsonValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect()
+        };
+        let required_extensions = names("extensionsRequired");
+        for extension in names("extensionsUsed") {
+            match required_extensions.contains(&extension) {
+                true => self.add_warning(format!(
+                    "required extension `{}` is not supported, the scene may be incomplete",
+                    extension
+                )),
+                false => self.add_warning(format!(
+                    "extension `{}` is not supported, it is ignored",
+                    extension
+                )),
+            }
+        }
+
+        for feature in ["animations", "skins"] {
+            let count = self
+                .root
+                .get(feature)
+                .and_then(JsonValue::as_array)
+                .map_or(0, <[JsonValue]>::len);
+            if count > 0 {
+                self.add_warning(format!("{} are not supported, {} ignored", feature, count));
+            }
+        }
+    }
+
+    /// Load the buffers: the binary chunk, or files next to the glTF file.
+    fn load_buffers(&mut self, bin_chunk: Option<&'a [u8]>) -> Result<()> {
+        let buffers = self
+            .root
+            .get("buffers")
+            .and_then(JsonValue::as_array)
+            .unwrap_or_default();
+
+        for (index, buffer) in buffers.iter().enumerate() {
+            let path = format!("buffers[{}]", index);
+            let byte_length = self.get_required_index(buffer, "byteLength", &path)?;
+
+            let data = match buffer.get("uri").and_then(JsonValue::as_str) {
+                None => match (index, bin_chunk) {
+                    (0, Some(bin_chunk)) => Some(Cow::Borrowed(bin_chunk)),
+                    _ => {
+                        return Err(self.error(
+                            &path,
+                            "no `uri`, and not the binary chunk of the file".to_string(),
+                        ))
+                    }
+                },
+                Some(uri) if uri.starts_with("data:") => {
+                    self.add_warning(format!("`{}` is a data URI, which is not supported", path));
+                    None
+                }
+                Some(uri) => {
+                    let buffer_file = self.base_directory.join(uri).display().to_string();
+                    let data =
+                        fs::read(&buffer_file).map_err(|err| Error::io(&buffer_file, err))?;
+                    Some(Cow::Owned(data))
+                }
+            };
+
+            if let Some(data) = &data {
+                if data.len() < byte_length {
+                    return Err(self.error(
+                        &path,
+                        format!("expected {} bytes, found {}", byte_length, data.len()),
+                    ));
+                }
+            }
+            self.buffers.push(data);
+        }
+        Ok(())
+    }
+
+    /// The bytes of a buffer view, and its stride if it has one. `None` if
+    /// its buffer could not be loaded.
+    fn get_buffer_view(&self, index: usize) -> Result<Option<(&[u8], Option<usize>)>> {
+        let path = format!("bufferViews[{}]", index);
+        let view = self.get_element("bufferViews", index)?;
+        let buffer_index = self.get_required_index(view, "buffer", &path)?;
+        let offset = self.get_index(view, "byteOffset", &path)?.unwrap_or(0);
+        let length = self.get_required_index(view, "byteLength", &path)?;
+        let stride = self.get_index(view, "byteStride", &path)?;
+
+        let buffer = self
+            .buffers
+            .get(buffer_index)
+            .ok_or_else(|| self.error(&path, format!("buffer {} not found", buffer_index)))?;
+        let Some(buffer) = buffer else {
+            return Ok(None);
+        };
+        let bytes = buffer
+            .get(offset..offset + length)
+            .ok_or_else(|| self.error(&path, "goes past the end of its buffer".to_string()))?;
+        Ok(Some((bytes, stride)))
+    }
+
+    /// Read the elements of an accessor of the given type (`SCALAR`, `VEC2`,
+    /// ...), one after the other. `None` if the accessor is not supported,
+    /// which is reported. Attributes other than the positions pass the
+    /// vertex count, which bounds their number of elements.
+    fn read_accessor(
+        &mut self,
+        index: usize,
+        expected_type: &str,
+        vertex_count: Option<usize>,
+    ) -> Result<Option<Vec<f64>>> {
+        let path = format!("accessors[{}]", index);
+        let accessor = self.get_element("accessors", index)?;
+
+        let count = self.get_required_index(accessor, "count", &path)?;
+        if let Some(vertex_count) = vertex_count.filter(|vertex_count| count > *vertex_count) {
+            return Err(self.error(
+                &path,
+                format!("{} elements for {} vertices", count, vertex_count),
+            ));
+        }
+        let accessor_type = accessor.get("type").and_then(JsonValue::as_str);
+        if accessor_type != Some(expected_type) {
+            return Err(self.error(
+                &path,
+                format!(
+                    "expected a {} accessor, found {}",
+                    expected_type,
+                    accessor_type.unwrap_or("none")
+                ),
+            ));
+        }
+        let component_count = match expected_type {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            _ => 4,
+        };
+        let code = self.get_required_index(accessor, "componentType", &path)?;
+        let component_type = ComponentType::from_code(code)
+            .ok_or_else(|| self.error(&path, format!("unknown component type {}", code)))?;
+        let normalized = accessor
+            .get("normalized")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false);
+
+        if accessor.get("sparse").is_some() {
+            self.add_warning(format!("`{}` is sparse, which is not supported", path));
+            return Ok(None);
+        }
+        // Accessors without buffer view are filled with zeros, as many as
+        // the vertices at most. Zero positions would only make degenerate
+        // triangles, and nothing would bound their count.
+        let Some(view_index) = self.get_index(accessor, "bufferView", &path)? else {
+            return match vertex_count {
+                Some(_) => Ok(Some(vec![0.; count * component_count])),
+                None => Err(self.error(&path, "has no buffer view".to_string())),
+            };
+        };
+        let Some((bytes, stride)) = self.get_buffer_view(view_index)? else {
+            self.add_warning(format!(
+                "`{}` is skipped, as its buffer could not be loaded",
+                path
+            ));
+            return Ok(None);
+        };
+
+        let offset = self.get_index(accessor, "byteOffset", &path)?.unwrap_or(0);
+        let component_size = component_type.get_size();
+        let element_size = component_count * component_size;
+        let stride = stride.unwrap_or(element_size);
+        if stride < element_size {
+            return Err(self.error(
+                &path,
+                format!(
+                    "stride of {} bytes for elements of {}",
+                    stride, element_size
+                ),
+            ));
+        }
+        let end = (count.max(1) - 1)
+            .checked_mul(stride)
+            .and_then(|size| size.checked_add(offset)?.checked_add(element_size));
+        if count > 0 && end.is_none_or(|end| end > bytes.len()) {
+            return Err(self.error(&path, "goes past the end of its buffer view".to_string()));
+        }
+
+        let mut values = Vec::with_capacity(count * component_count);
+        for element in 0..count {
+            let start = offset + element * stride;
+            for component in bytes[start..start + element_size].chunks_exact(component_size) {
+                values.push(component_type.read(component, normalized));
+            }
+        }
+        Ok(Some(values))
+    }
+
+    /// The BVH and material of each primitive of a mesh, loaded once.
+    fn load_mesh(&mut self, mesh_index: usize) -> Result<Vec<(Arc<BVHTree>, Material)>> {
+        if let Some(primitives) = self.meshes.get(&mesh_index) {
+            return Ok(primitives.clone());
+        }
+
+        let mesh = self.get_element("meshes", mesh_index)?;
+        let name = mesh
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .unwrap_or(DEFAULT_GROUP_NAME);
+        let primitives = mesh
+            .get("primitives")
+            .and_then(JsonValue::as_array)
+            .unwrap_or_default();
+
+        let mut loaded_primitives = Vec::new();
+        for (primitive_index, primitive) in primitives.iter().enumerate() {
+            let path = format!("meshes[{}].primitives[{}]", mesh_index, primitive_index);
+            let Some(data) = self.load_primitive(primitive, name, &path)? else {
+                continue;
+            };
+            let material = match self.get_index(primitive, "material", &path)? {
+                Some(material_index) => self.load_material(material_index)?,
+                None => Material {
+                    color: DEFAULT_BASE_COLOR,
+                    texture: None,
+                },
+            };
+
+            let mesh = Mesh::from_indexed_data(data, self.settings.get_crease_angle());
+            let bvh = BVHTree::new_from_mesh_with_settings(mesh, self.settings);
+            loaded_primitives.push((Arc::new(bvh), material));
+        }
+
+        self.meshes.insert(mesh_index, loaded_primitives.clone());
+        Ok(loaded_primitives)
+    }
+
+    /// The triangles of a primitive, or `None` if it is not supported or
+    /// empty.
+    fn load_primitive(
+        &mut self,
+        primitive: &JsonValue,
+        name: &str,
+        path: &str,
+    ) -> Result<Option<IndexedMeshData>> {
+        let mode = self.get_index(primitive, "mode", path)?;
+        if let Some(mode) = mode.filter(|mode| *mode != TRIANGLES_MODE) {
+            self.add_warning(format!(
+                "primitives of mode {} are not supported, only triangles are",
+                mode
+            ));
+            return Ok(None);
+        }
+        if primitive.get("targets").is_some() {
+            self.add_warning("morph targets are not supported".to_string());
+        }
+
+        let attributes_path = format!("{}.attributes", path);
+        let attributes = primitive
+            .get("attributes")
+            .filter(|attributes| attributes.as_object().is_some())
+            .ok_or_else(|| self.error(&attributes_path, "missing".to_string()))?;
+        for (attribute, _) in attributes.as_object().unwrap_or_default() {
+            if !SUPPORTED_ATTRIBUTES.contains(&attribute.as_str()) {
+                self.add_warning(format!("attribute `{}` is not supported", attribute));
+            }
+        }
+        let position_index = self.get_required_index(attributes, "POSITION", &attributes_path)?;
+        let normal_index = self.get_index(attributes, "NORMAL", &attributes_path)?;
+        let uv_index = self.get_index(attributes, "TEXCOORD_0", &attributes_path)?;
+        let indices_index = self.get_index(primitive, "indices", path)?;
+
+        let Some(positions) = self.read_accessor(position_index, "VEC3", None)? else {
+            return Ok(None);
+        };
+        let to_vectors = |values: Vec<f64>| -> Vec<Vector3> {
+            values
+                .chunks_exact(3)
+                .map(|value| Vector3::new(value[0], value[1], value[2]))
+                .collect()
+        };
+        let vertices = to_vectors(positions);
+
+        let mut data = IndexedMeshData::default();
+        if let Some(normal_index) = normal_index {
+            let Some(normals) = self.read_accessor(normal_index, "VEC3", Some(vertices.len()))?
+            else {
+                return Ok(None);
+            };
+            data.normals = to_vectors(normals);
+        }
+        if let Some(uv_index) = uv_index {
+            let Some(uvs) = self.read_accessor(uv_index, "VEC2", Some(vertices.len()))? else {
+                return Ok(None);
+            };
+            // glTF texture coordinates start from the top of the image
+            data.uvs = uvs
+                .chunks_exact(2)
+                .map(|uv| Vector3::new(uv[0], 1. - uv[1], 0.))
+                .collect();
+        }
+        for (attribute, count) in [
+            ("NORMAL", data.normals.len()),
+            ("TEXCOORD_0", data.uvs.len()),
+        ] {
+            if count != 0 && count != vertices.len() {
+                return Err(self.error(
+                    &format!("{}.{}", attributes_path, attribute),
+                    format!("{} elements for {} vertices", count, vertices.len()),
+                ));
+            }
+        }
+
+        let indices: Vec<usize> = match indices_index {
+            Some(indices_index) => {
+                let Some(indices) = self.read_accessor(indices_index, "SCALAR", None)? else {
+                    return Ok(None);
+                };
+                indices.into_iter().map(|index| index as usize).collect()
+            }
+            None => (0..vertices.len()).collect(),
+        };
+        if !indices.len().is_multiple_of(3) {
+            return Err(self.error(
+                path,
+                format!("{} indices do not make whole triangles", indices.len()),
+            ));
+        }
+        if let Some(index) = indices.iter().find(|index| **index >= vertices.len()) {
+            return Err(self.error(
+                path,
+                format!(
+                    "index {} out of range, there are {} vertices",
+                    index,
+                    vertices.len()
+                ),
+            ));
+        }
+        if indices.is_empty() {
+            return Ok(None);
+        }
+
+        data.vertices = vertices;
+        data.triangles = indices
+            .chunks_exact(3)
+            .map(|triangle| (triangle[0], triangle[1], triangle[2]))
+            .collect();
+        data.triangle_groups = vec![0; data.triangles.len()];
+        data.groups = vec![name.to_string()];
+        Ok(Some(data))
+    }
+
+    fn load_material(&mut self, index: usize) -> Result<Material> {
+        if let Some(material) = self.materials.get(&index) {
+            return Ok(*material);
+        }
+
+        let path = format!("materials[{}]", index);
+        let material = self.get_element("materials", index)?;
+        let mut loaded_material = Material {
+            color: DEFAULT_BASE_COLOR,
+            texture: None,
+        };
+        if let Some(pbr) = material.get("pbrMetallicRoughness") {
+            let pbr_path = format!("{}.pbrMetallicRoughness", path);
+            if let Some(factor) = self.get_numbers::<4>(pbr, "baseColorFactor", &pbr_path)? {
+                loaded_material.color = Vector3::new(factor[0], factor[1], factor[2]);
+            }
+            if let Some(texture_info) = pbr.get("baseColorTexture") {
+                let texture_path = format!("{}.baseColorTexture", pbr_path);
+                let texture_index =
+                    self.get_required_index(texture_info, "index", &texture_path)?;
+                match self.get_index(texture_info, "texCoord", &texture_path)? {
+                    Some(tex_coord) if tex_coord != 0 => self.add_warning(format!(
+                        "`{}` uses TEXCOORD_{}, only TEXCOORD_0 is supported",
+                        texture_path, tex_coord
+                    )),
+                    _ => loaded_material.texture = self.load_texture(texture_index)?,
+                }
+            }
+        }
+
+        self.materials.insert(index, loaded_material);
+        Ok(loaded_material)
+    }
+
+    fn load_texture(&mut self, index: usize) -> Result<Option<usize>> {
+        let path = format!("textures[{}]", index);
+        let texture = self.get_element("textures", index)?;
+        match self.get_index(texture, "source", &path)? {
+            Some(image_index) => self.load_image(image_index),
+            None => {
+                // The image is then given by an extension
+                self.add_warning(format!("`{}` has no supported image", path));
+                Ok(None)
+            }
+        }
+    }
+
+    fn load_image(&mut self, index: usize) -> Result<Option<usize>> {
+        if let Some(texture_index) = self.images.get(&index) {
+            return Ok(*texture_index);
+        }
+
+        let path = format!("images[{}]", index);
+        let image = self.get_element("images", index)?;
+        let texture = match image.get("uri").and_then(JsonValue::as_str) {
+            Some(uri) if uri.starts_with("data:") => {
+                self.add_warning(format!("`{}` is a data URI, which is not supported", path));
+                None
+            }
+            Some(uri) => {
+                let image_file = self.base_directory.join(uri).display().to_string();
+                Some(Texture::from_img_file(&image_file)?)
+            }
+            None => {
+                let view_index = self.get_required_index(image, "bufferView", &path)?;
+                let mime_type = image.get("mimeType").and_then(JsonValue::as_str);
+                match mime_type {
+                    Some(mime_type) if SUPPORTED_IMAGE_TYPES.contains(&mime_type) => {
+                        match self.get_buffer_view(view_index)? {
+                            Some((bytes, _)) => Some(Texture::from_memory(
+                                bytes,
+                                &format!("{}: {}", self.filename, path),
+                            )?),
+                            None => None,
+                        }
+                    }
+                    _ => {
+                        self.add_warning(format!(
+                            "`{}` has type {}, only PNG and JPEG images are supported",
+                            path,
+                            mime_type.unwrap_or("none")
+                        ));
+                        None
+                    }
+                }
+            }
+        };
+
+        let texture_index = texture.map(|texture| {
+            self.textures.push(texture);
+            self.textures.len() - 1
+        });
+        self.images.insert(index, texture_index);
+        Ok(texture_index)
+    }
+
+    /// The vertical field of view of a camera, or `None` if it is not a
+    /// perspective camera.
+    fn load_camera(&mut self, index: usize) -> Result<Option<f64>> {
+        let path = format!("cameras[{}]", index);
+        let camera = self.get_element("cameras", index)?;
+        match camera.get("type").and_then(JsonValue::as_str) {
+            Some("perspective") => {
+                let vertical_fov = camera
+                    .get("perspective")
+                    .and_then(|perspective| perspective.get("yfov"))
+                    .and_then(JsonValue::as_f64)
+                    .filter(|yfov| *yfov > 0. && *yfov < PI)
+                    .ok_or_else(|| {
+                        self.error(
+                            &format!("{}.perspective.yfov", path),
+                            "expected an angle between 0 and pi radians".to_string(),
+                        )
+                    })?;
+                Ok(Some(vertical_fov))
+            }
+            camera_type => {
+                self.add_warning(format!(
+                    "{} cameras are not supported",
+                    camera_type.unwrap_or("untyped")
+                ));
+                Ok(None)
+            }
+        }
+    }
+
+    /// The transform of a node relative to its parent, or `None` if it is
+    /// not invertible.
+    fn calculate_node_transform(&self, node: &JsonValue, path: &str) -> Result<Option<Transform>> {
+        if let Some(matrix) = self.get_numbers::<16>(node, "matrix", path)? {
+            // The matrix is stored column by column
+            let mut rows = [[0.; 4]; 4];
+            for (column, values) in matrix.chunks_exact(4).enumerate() {
+                for (row, value) in values.iter().enumerate() {
+                    rows[row][column] = *value;
+                }
+            }
+            return Ok(Transform::new(Matrix4::new(rows)));
+        }
+
+        let scale = self.get_numbers::<3>(node, "scale", path)?;
+        let rotation = self.get_numbers::<4>(node, "rotation", path)?;
+        let translation = self.get_numbers::<3>(node, "translation", path)?;
+
+        let mut transform = Transform::IDENTITY;
+        if let Some([x, y, z]) = scale {
+            if x == 0. || y == 0. || z == 0. {
+                return Ok(None);
+            }
+            transform = transform.then(&Transform::new_scale(Vector3::new(x, y, z)));
+        }
+        if let Some([x, y, z, w]) = rotation {
+            let quaternion = Quaternion::new(w, x, y, z);
+            if quaternion.norm() == 0. {
+                return Err(self.error(
+                    &format!("{}.rotation", path),
+                    "expected a unit quaternion".to_string(),
+                ));
+            }
+            transform = transform.then(&Transform::new_quaternion_rotation(
+                &quaternion.normalized(),
+            ));
+        }
+        if let Some([x, y, z]) = translation {
+            transform = transform.then(&Transform::new_translation(Vector3::new(x, y, z)));
+        }
+        Ok(Some(transform))
+    }
+
+    /// The node indices listed under a key, such as the `children` of a
+    /// node.
+    fn get_node_indices(
+        &self,
+        value: &JsonValue,
+        key: &str,
+        path: &str,
+        node_count: usize,
+    ) -> Result<Vec<usize>> {
+        let Some(indices) = value.get(key) else {
+            return Ok(Vec::new());
+        };
+        let error = || {
+            self.error(
+                &format!("{}.{}", path, key),
+                "expected a list of nodes".to_string(),
+            )
+        };
+        indices
+            .as_array()
+            .ok_or_else(error)?
+            .iter()
+            .map(|index| {
+                index
+                    .as_usize()
+                    .filter(|index| *index < node_count)
+                    .ok_or_else(error)
+            })
+            .collect()
+    }
+
+    /// Walk the node trees of the default scene, placing their meshes and
+    /// finding the first camera.
+    fn load_scene(&mut self) -> Result<(Vec<MeshInstance>, Option<GltfCamera>)> {
+        let nodes = self
+            .root
+            .get("nodes")
+            .and_then(JsonValue::as_array)
+            .unwrap_or_default();
+
+        let scene_index = match self.root.get("scene") {
+            Some(scene_index) => Some(scene_index.as_usize().ok_or_else(|| {
+                self.error("scene", "expected a non-negative integer".to_string())
+            })?),
+            None if self.root.get("scenes").is_some() => Some(0),
+            None => None,
+        };
+        let roots = match scene_index {
+            Some(scene_index) => {
+                let scene = self.get_element("scenes", scene_index)?;
+                let path = format!("scenes[{}]", scene_index);
+                self.get_node_indices(scene, "nodes", &path, nodes.len())?
+            }
+            // Without scene, every node without parent is a root
+            None => {
+                let mut has_parent = vec![false; nodes.len()];
+                for (index, node) in nodes.iter().enumerate() {
+                    let path = format!("nodes[{}]", index);
+                    for child in self.get_node_indices(node, "children", &path, nodes.len())? {
+                        has_parent[child] = true;
+                    }
+                }
+                (0..nodes.len()).filter(|node| !has_parent[*node]).collect()
+            }
+        };
+
+        let mut instances = Vec::new();
+        let mut camera = None;
+        let mut visited = vec![false; nodes.len()];
+        let mut stack: Vec<(usize, Transform)> = roots
+            .into_iter()
+            .rev()
+            .map(|root| (root, Transform::IDENTITY))
+            .collect();
+        while let Some((index, parent_transform)) = stack.pop() {
+            let path = format!("nodes[{}]", index);
+            if visited[index] {
+                return Err(self.error(&path, "the nodes do not form trees".to_string()));
+            }
+            visited[index] = true;
+            let node = &nodes[index];
+
+            let Some(transform) = self.calculate_node_transform(node, &path)? else {
+                self.add_warning(format!(
+                    "`{}` has a transform that cannot be inverted, it is skipped",
+                    path
+                ));
+                continue;
+            };
+            let transform = transform.then(&parent_transform);
+
+            if let Some(mesh_index) = self.get_index(node, "mesh", &path)? {
+                for (bvh, material) in self.load_mesh(mesh_index)? {
+                    instances.push(MeshInstance {
+                        bvh,
+                        transform,
+                        material,
+                    });
+                }
+            }
+            if camera.is_none() {
+                if let Some(camera_index) = self.get_index(node, "camera", &path)? {
+                    if let Some(vertical_fov) = self.load_camera(camera_index)? {
+                        camera = Some(GltfCamera {
+                            transform,
+                            vertical_fov,
+                        });
+                    }
+                }
+            }
+
+            let children = self.get_node_indices(node, "children", &path, nodes.len())?;
+            for child in children.into_iter().rev() {
+                stack.push((child, transform));
+            }
+        }
+
+        Ok((instances, camera))
+    }
+}
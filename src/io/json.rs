@@ -0,0 +1,342 @@
+//! A small JSON parser, enough for the JSON chunk of glTF files.
+//!
+//! Objects keep their keys in the order of the file. Numbers are read as
+//! `f64`, which holds exactly every integer a glTF file may contain.
+
+use std::{char, result, str};
+
+/// Nesting deeper than this is rejected rather than overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parse a JSON document. Errors give the line and column where parsing
+    /// stopped.
+    pub(crate) fn parse(text: &str) -> result::Result<JsonValue, String> {
+        let mut parser = Parser {
+            bytes: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser
+            .parse_value(0)
+            .and_then(|value| {
+                parser.skip_whitespace();
+                match parser.position < parser.bytes.len() {
+                    true => Err("unexpected characters after the document".to_string()),
+                    false => Ok(value),
+                }
+            })
+            .map_err(|message| {
+                let (line, column) = parser.calculate_line_and_column();
+                format!("line {}, column {}: {}", line, column, message)
+            })?;
+        Ok(value)
+    }
+
+    /// The value of a key of an object, or `None` if the key is missing or
+    /// this is not an object.
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a number that is a non-negative integer.
+    pub(crate) fn as_usize(&self) -> Option<usize> {
+        let value = self.as_f64()?;
+        match value >= 0. && value.fract() == 0. && value <= u32::MAX as f64 {
+            true => Some(value as usize),
+            false => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn calculate_line_and_column(&self) -> (usize, usize) {
+        let consumed = &self.bytes[..self.position.min(self.bytes.len())];
+        let line_start = consumed
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |index| index + 1);
+        let line = consumed.iter().filter(|&&byte| byte == b'\n').count() + 1;
+        (line, self.position - line_start + 1)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> result::Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(found) if found == byte => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(found) => Err(format!(
+                "expected `{}`, found `{}`",
+                byte as char, found as char
+            )),
+            None => Err(format!("expected `{}`, found the end", byte as char)),
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> result::Result<JsonValue, String> {
+        if depth > MAX_DEPTH {
+            return Err("values are nested too deeply".to_string());
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.parse_object(depth),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b'-' | b'0'..=b'9') => self.parse_number().map(JsonValue::Number),
+            Some(b't') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.parse_literal("null", JsonValue::Null),
+            Some(found) => Err(format!("unexpected character `{}`", found as char)),
+            None => Err("unexpected end of the document".to_string()),
+        }
+    }
+
+    fn parse_literal(
+        &mut self,
+        literal: &str,
+        value: JsonValue,
+    ) -> result::Result<JsonValue, String> {
+        match self.bytes[self.position..].starts_with(literal.as_bytes()) {
+            true => {
+                self.position += literal.len();
+                Ok(value)
+            }
+            false => Err(format!("expected `{}`", literal)),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> result::Result<JsonValue, String> {
+        self.position += 1;
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(JsonValue::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err("expected a string key".to_string());
+            }
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            let value = self.parse_value(depth + 1)?;
+            members.push((key, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                _ => return Err("expected `,` or `}` in object".to_string()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> result::Result<JsonValue, String> {
+        self.position += 1;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(JsonValue::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value(depth + 1)?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                _ => return Err("expected `,` or `]` in array".to_string()),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> result::Result<f64, String> {
+        let start = self.position;
+        let skip_digits = |parser: &mut Self| {
+            let digits_start = parser.position;
+            while let Some(b'0'..=b'9') = parser.peek() {
+                parser.position += 1;
+            }
+            parser.position > digits_start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        // Leading zeros are not allowed
+        if self.peek() == Some(b'0') {
+            self.position += 1;
+        } else if !skip_digits(self) {
+            return Err("expected a digit".to_string());
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if !skip_digits(self) {
+                return Err("expected a digit after `.`".to_string());
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            if !skip_digits(self) {
+                return Err("expected a digit in the exponent".to_string());
+            }
+        }
+
+        // The slice only holds ASCII characters, checked above
+        let text = str::from_utf8(&self.bytes[start..self.position]).unwrap_or_default();
+        text.parse::<f64>()
+            .map_err(|_| format!("invalid number `{}`", text))
+    }
+
+    fn parse_string(&mut self) -> result::Result<String, String> {
+        self.position += 1;
+        let mut string = Vec::new();
+
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err("unterminated string".to_string());
+            };
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(escape) = self.peek() else {
+                        return Err("unterminated string".to_string());
+                    };
+                    self.position += 1;
+                    let unescaped = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.parse_unicode_escape()?,
+                        _ => return Err(format!("invalid escape `\\{}`", escape as char)),
+                    };
+                    let mut buffer = [0; 4];
+                    string.extend_from_slice(unescaped.encode_utf8(&mut buffer).as_bytes());
+                }
+                0..=0x1F => return Err("control character in string".to_string()),
+                _ => string.push(byte),
+            }
+        }
+
+        // The document is valid UTF-8, and only split at ASCII characters
+        String::from_utf8(string).map_err(|_| "invalid UTF-8 in string".to_string())
+    }
+
+    /// The character of a `\u` escape, whose `\u` has been read. Characters
+    /// outside the basic plane are written as two escaped surrogates.
+    fn parse_unicode_escape(&mut self) -> result::Result<char, String> {
+        let high = self.parse_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| "invalid unicode escape".to_string());
+        }
+
+        if !self.bytes[self.position..].starts_with(b"\\u") {
+            return Err("unpaired surrogate in unicode escape".to_string());
+        }
+        self.position += 2;
+        let low = self.parse_hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err("unpaired surrogate in unicode escape".to_string());
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| "invalid unicode escape".to_string())
+    }
+
+    fn parse_hex4(&mut self) -> result::Result<u32, String> {
+        let digits = self
+            .bytes
+            .get(self.position..self.position + 4)
+            .and_then(|digits| str::from_utf8(digits).ok())
+            .ok_or_else(|| "expected four hexadecimal digits".to_string())?;
+        if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err("expected four hexadecimal digits".to_string());
+        }
+        self.position += 4;
+        u32::from_str_radix(digits, 16).map_err(|_| "expected four hexadecimal digits".to_string())
+    }
+}
@@ -0,0 +1,7 @@
+mod gltf;
+mod json;
+pub(crate) mod ply;
+mod scene_file;
+pub(crate) mod stl;
+
+pub use {gltf::GltfScene, scene_file::SceneDescription};
@@ -0,0 +1,568 @@
+//! Loader for Stanford PLY files, in ASCII or binary (little or big endian)
+//! format, and writer of ASCII PLY files.
+//!
+//! The `vertex` element provides the positions (`x`, `y`, `z`) and optionally
+//! normals (`nx`, `ny`, `nz`), texture coordinates (`u`, `v`, or `s`, `t`) and
+//! colors (`red`, `green`, `blue`). The `face` element provides polygons
+//! through a `vertex_indices` list. Other elements and properties are read
+//! and ignored, with a warning.
+
+use {
+    crate::{
+        core::{IndexedMeshData, Mesh, DEFAULT_CREASE_ANGLE, DEFAULT_GROUP_NAME},
+        error::{Error, Result},
+        utils::Vector3,
+    },
+    std::{collections::HashMap, fs, result},
+};
+
+const PLY_MAGIC: &[u8] = b"ply";
+const END_HEADER: &str = "end_header";
+
+const POSITION_PROPERTIES: [&str; 3] = ["x", "y", "z"];
+const NORMAL_PROPERTIES: [&str; 3] = ["nx", "ny", "nz"];
+const COLOR_PROPERTIES: [&str; 3] = ["red", "green", "blue"];
+const UV_PROPERTIES: [[&str; 2]; 3] = [["u", "v"], ["s", "t"], ["texture_u", "texture_v"]];
+const FACE_INDICES_PROPERTIES: [&str; 2] = ["vertex_indices", "vertex_index"];
+
+impl Mesh {
+    /// Load a PLY file. Faces are triangulated, and missing normals are
+    /// generated like for OBJ files.
+    pub fn from_ply_file(filename: &str) -> Result<Mesh> {
+        load_ply_file(filename, DEFAULT_CREASE_ANGLE)
+    }
+
+    /// Write the mesh to an ASCII PLY file, with its normals, texture
+    /// coordinates and vertex colors. PLY files only have attributes per
+    /// vertex, so vertices are duplicated where their normal or texture
+    /// coordinates differ between triangles. Groups and materials are lost.
+    pub fn write_ply_file(&self, filename: &str) -> Result<()> {
+        write_ply_file(self, filename)
+    }
+}
+
+/// Whether the first bytes of a file are those of a PLY file.
+pub(crate) fn is_ply_header(bytes: &[u8]) -> bool {
+    bytes.starts_with(PLY_MAGIC) && matches!(bytes.get(PLY_MAGIC.len()), Some(b'\n') | Some(b'\r'))
+}
+
+pub(crate) fn load_ply_file(filename: &str, crease_angle: f64) -> Result<Mesh> {
+    let bytes = fs::read(filename).map_err(|err| Error::io(filename, err))?;
+    let parse_error = |line, message| Error::parse(filename, line, None, message);
+
+    let (header, body_start) =
+        parse_header(&bytes).map_err(|(line, message)| parse_error(Some(line), message))?;
+
+    let mut data = match header.format {
+        PlyFormat::Ascii => {
+            let body = String::from_utf8_lossy(&bytes[body_start..]);
+            let mut reader = AsciiReader::new(&body, header.line_count + 1);
+            read_elements(&header, &mut reader)
+                .map_err(|message| parse_error(Some(reader.get_line()), message))?
+        }
+        PlyFormat::BinaryLittleEndian | PlyFormat::BinaryBigEndian => {
+            let mut reader = BinaryReader {
+                bytes: &bytes[body_start..],
+                big_endian: header.format == PlyFormat::BinaryBigEndian,
+            };
+            read_elements(&header, &mut reader).map_err(|message| parse_error(None, message))?
+        }
+    };
+
+    for warning in &mut data.warnings {
+        *warning = format!("{}: {}", filename, warning);
+    }
+    Ok(Mesh::from_indexed_data(data, crease_angle))
+}
+
+#[derive(PartialEq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum PropertyType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PropertyType {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(PropertyType::Int8),
+            "uchar" | "uint8" => Some(PropertyType::UInt8),
+            "short" | "int16" => Some(PropertyType::Int16),
+            "ushort" | "uint16" => Some(PropertyType::UInt16),
+            "int" | "int32" => Some(PropertyType::Int32),
+            "uint" | "uint32" => Some(PropertyType::UInt32),
+            "float" | "float32" => Some(PropertyType::Float32),
+            "double" | "float64" => Some(PropertyType::Float64),
+            _ => None,
+        }
+    }
+
+    /// The value that stands for full intensity in a color of this type.
+    fn get_color_scale(&self) -> f64 {
+        match self {
+            PropertyType::UInt8 => u8::MAX as f64,
+            PropertyType::UInt16 => u16::MAX as f64,
+            _ => 1.,
+        }
+    }
+}
+
+enum PropertyKind {
+    Scalar(PropertyType),
+    /// Type of the count, then of the items
+    List(PropertyType, PropertyType),
+}
+
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn find_property(&self, name: &str) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| property.name == name)
+    }
+
+    fn find_properties<const N: usize>(&self, names: [&str; N]) -> Option<[usize; N]> {
+        let indices = names.map(|name| self.find_property(name));
+        indices
+            .iter()
+            .all(Option::is_some)
+            .then(|| indices.map(Option::unwrap_or_default))
+    }
+}
+
+struct Header {
+    format: PlyFormat,
+    elements: Vec<Element>,
+    line_count: usize,
+}
+
+/// Parse the header, and return it with the offset of the body. Errors come
+/// with their line number.
+fn parse_header(bytes: &[u8]) -> result::Result<(Header, usize), (usize, String)> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut offset = 0;
+    let mut line_count = 0;
+
+    loop {
+        let line_length = bytes[offset..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .ok_or_else(|| (line_count + 1, format!("missing `{}`", END_HEADER)))?;
+        let line = String::from_utf8_lossy(&bytes[offset..offset + line_length]);
+        offset += line_length + 1;
+        line_count += 1;
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let error = |message: String| (line_count, message);
+        if line_count == 1 {
+            if parts != ["ply"] {
+                return Err(error("not a PLY file, expected `ply`".to_string()));
+            }
+            continue;
+        }
+        match parts.first().copied() {
+            Some("format") => {
+                format = Some(match parts.get(1).copied() {
+                    Some("ascii") => PlyFormat::Ascii,
+                    Some("binary_little_endian") => PlyFormat::BinaryLittleEndian,
+                    Some("binary_big_endian") => PlyFormat::BinaryBigEndian,
+                    _ => return Err(error(format!("unknown format `{}`", line.trim()))),
+                });
+            }
+            Some("element") => {
+                let (Some(name), Some(count)) = (parts.get(1), parts.get(2)) else {
+                    return Err(error(format!("invalid element line `{}`", line.trim())));
+                };
+                let count = count
+                    .parse::<usize>()
+                    .map_err(|_| error(format!("invalid element count `{}`", count)))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            Some("property") => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("property defined before any element".to_string()))?;
+                let parse_type = |name: &str| {
+                    PropertyType::from_name(name)
+                        .ok_or_else(|| error(format!("unknown property type `{}`", name)))
+                };
+                let (kind, name) = match parts[1..] {
+                    ["list", count_type, item_type, name] => (
+                        PropertyKind::List(parse_type(count_type)?, parse_type(item_type)?),
+                        name,
+                    ),
+                    [property_type, name] => {
+                        (PropertyKind::Scalar(parse_type(property_type)?), name)
+                    }
+                    _ => return Err(error(format!("invalid property line `{}`", line.trim()))),
+                };
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind,
+                });
+            }
+            Some("comment") | Some("obj_info") | None => {}
+            Some(END_HEADER) => break,
+            Some(keyword) => return Err(error(format!("unknown header keyword `{}`", keyword))),
+        }
+    }
+
+    let format = format.ok_or_else(|| (line_count, "missing `format` line".to_string()))?;
+    Ok((
+        Header {
+            format,
+            elements,
+            line_count,
+        },
+        offset,
+    ))
+}
+
+/// Reads the values of the properties, converted to `f64`.
+trait ValueReader {
+    fn read_value(&mut self, property_type: PropertyType) -> result::Result<f64, String>;
+}
+
+struct AsciiReader<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    tokens: std::str::SplitWhitespace<'a>,
+    first_line: usize,
+    line: usize,
+}
+
+impl<'a> AsciiReader<'a> {
+    fn new(body: &'a str, first_line: usize) -> Self {
+        AsciiReader {
+            lines: body.lines().enumerate(),
+            tokens: "".split_whitespace(),
+            first_line,
+            line: first_line,
+        }
+    }
+
+    fn get_line(&self) -> usize {
+        self.line
+    }
+}
+
+impl ValueReader for AsciiReader<'_> {
+    fn read_value(&mut self, _property_type: PropertyType) -> result::Result<f64, String> {
+        loop {
+            if let Some(token) = self.tokens.next() {
+                return token
+                    .parse::<f64>()
+                    .map_err(|_| format!("expected a number, found `{}`", token));
+            }
+            let (index, line) = self
+                .lines
+                .next()
+                .ok_or_else(|| "unexpected end of file".to_string())?;
+            self.line = self.first_line + index;
+            self.tokens = line.split_whitespace();
+        }
+    }
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl BinaryReader<'_> {
+    fn read_bytes<const N: usize>(&mut self) -> result::Result<[u8; N], String> {
+        if self.bytes.len() < N {
+            return Err("unexpected end of file".to_string());
+        }
+        let (read, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        let mut value = [0; N];
+        value.copy_from_slice(read);
+        if !self.big_endian {
+            value.reverse();
+        }
+        Ok(value)
+    }
+}
+
+impl ValueReader for BinaryReader<'_> {
+    /// Values are read as big endian, after reversing little endian ones.
+    fn read_value(&mut self, property_type: PropertyType) -> result::Result<f64, String> {
+        Ok(match property_type {
+            PropertyType::Int8 => i8::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::UInt8 => u8::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::Int16 => i16::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::UInt16 => u16::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::Int32 => i32::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::UInt32 => u32::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::Float32 => f32::from_be_bytes(self.read_bytes()?) as f64,
+            PropertyType::Float64 => f64::from_be_bytes(self.read_bytes()?),
+        })
+    }
+}
+
+/// Read one instance of an element: the value of each scalar property, and
+/// the items of each list property.
+fn read_element<R: ValueReader>(
+    element: &Element,
+    reader: &mut R,
+    scalars: &mut [f64],
+    lists: &mut [Vec<f64>],
+) -> result::Result<(), String> {
+    for (index, property) in element.properties.iter().enumerate() {
+        match property.kind {
+            PropertyKind::Scalar(property_type) => {
+                scalars[index] = reader.read_value(property_type)?
+            }
+            PropertyKind::List(count_type, item_type) => {
+                let count = reader.read_value(count_type)?;
+                if count < 0. || count.fract() != 0. {
+                    return Err(format!("invalid list length {}", count));
+                }
+                lists[index].clear();
+                for _ in 0..count as usize {
+                    lists[index].push(reader.read_value(item_type)?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_elements<R: ValueReader>(
+    header: &Header,
+    reader: &mut R,
+) -> result::Result<IndexedMeshData, String> {
+    let mut data = IndexedMeshData::default();
+    let mut vertex_count = None;
+
+    for element in &header.elements {
+        let property_count = element.properties.len();
+        let mut scalars = vec![0.; property_count];
+        let mut lists = vec![Vec::new(); property_count];
+        let mut used_properties = vec![false; property_count];
+
+        match element.name.as_str() {
+            "vertex" => {
+                let position = element
+                    .find_properties(POSITION_PROPERTIES)
+                    .ok_or_else(|| {
+                        "the `vertex` element has no `x`, `y` and `z` properties".to_string()
+                    })?;
+                let normal = element.find_properties(NORMAL_PROPERTIES);
+                let color = element.find_properties(COLOR_PROPERTIES);
+                let uv = UV_PROPERTIES
+                    .iter()
+                    .find_map(|names| element.find_properties(*names));
+                let uv_indices = uv.iter().flatten();
+                for index in [Some(position), normal, color]
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .chain(uv_indices)
+                {
+                    used_properties[*index] = true;
+                }
+                let color_scales = color.map(|indices| {
+                    indices.map(|index| match element.properties[index].kind {
+                        PropertyKind::Scalar(property_type) => property_type.get_color_scale(),
+                        PropertyKind::List(..) => 1.,
+                    })
+                });
+
+                for _ in 0..element.count {
+                    read_element(element, reader, &mut scalars, &mut lists)?;
+                    let vector =
+                        |[a, b, c]: [usize; 3]| Vector3::new(scalars[a], scalars[b], scalars[c]);
+                    data.vertices.push(vector(position));
+                    if let Some(normal) = normal {
+                        data.normals.push(vector(normal).normalized());
+                    }
+                    if let Some([u, v]) = uv {
+                        data.uvs.push(Vector3::new(scalars[u], scalars[v], 0.));
+                    }
+                    if let (Some(color), Some([red, green, blue])) = (color, color_scales) {
+                        let color = vector(color);
+                        data.colors.push(Vector3::new(
+                            color.x() / red,
+                            color.y() / green,
+                            color.z() / blue,
+                        ));
+                    }
+                }
+                vertex_count = Some(element.count);
+            }
+            "face" => {
+                let indices_property = FACE_INDICES_PROPERTIES
+                    .iter()
+                    .find_map(|name| element.find_property(name))
+                    .ok_or_else(|| "the `face` element has no `vertex_indices` list".to_string())?;
+                used_properties[indices_property] = true;
+                let vertex_count = vertex_count
+                    .ok_or_else(|| "faces are defined before the vertices".to_string())?;
+
+                for _ in 0..element.count {
+                    read_element(element, reader, &mut scalars, &mut lists)?;
+                    let polygon = lists[indices_property]
+                        .iter()
+                        .map(|index| match *index {
+                            index if index.fract() != 0. => {
+                                Err(format!("vertex index {} is not an integer", index))
+                            }
+                            index if index >= 0. && (index as usize) < vertex_count => {
+                                Ok(index as usize)
+                            }
+                            index => Err(format!(
+                                "vertex index {} is out of range, only {} vertices are defined",
+                                index, vertex_count
+                            )),
+                        })
+                        .collect::<result::Result<Vec<usize>, String>>()?;
+                    if polygon.len() < 3 {
+                        return Err(format!("face with only {} vertices", polygon.len()));
+                    }
+
+                    let positions: Vec<Vector3> =
+                        polygon.iter().map(|index| data.vertices[*index]).collect();
+                    for (a, b, c) in Mesh::triangulate_polygon(&positions) {
+                        data.triangles.push((polygon[a], polygon[b], polygon[c]));
+                        data.triangle_groups.push(0);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    read_element(element, reader, &mut scalars, &mut lists)?;
+                }
+                data.warnings.push(format!(
+                    "`{}` elements are not supported, {} ignored",
+                    element.name, element.count
+                ));
+                continue;
+            }
+        }
+
+        for (property, used) in element.properties.iter().zip(used_properties) {
+            if !used {
+                data.warnings.push(format!(
+                    "`{}` property of `{}` elements is not supported, ignored",
+                    property.name, element.name
+                ));
+            }
+        }
+    }
+
+    if !data.triangles.is_empty() {
+        data.groups.push(DEFAULT_GROUP_NAME.to_string());
+    }
+    Ok(data)
+}
+
+pub(crate) fn write_ply_file(mesh: &Mesh, filename: &str) -> Result<()> {
+    let vertices = mesh.get_vertices();
+    let normals = mesh.get_normals();
+    let uvs = mesh.get_uvs();
+    let colors = mesh.get_vertex_colors();
+
+    // Index of the written vertex of each vertex, normal and uv combination
+    let mut corner_indices: HashMap<(usize, usize, usize), usize> = HashMap::new();
+    let mut corners = Vec::new();
+    let faces: Vec<[usize; 3]> = mesh
+        .get_triangles()
+        .iter()
+        .map(|triangle| {
+            let (v, n, uv) = (
+                triangle.get_vertex_indices(),
+                triangle.get_normal_indices(),
+                triangle.get_uv_indices(),
+            );
+            [(v.0, n.0, uv.0), (v.1, n.1, uv.1), (v.2, n.2, uv.2)].map(|corner| {
+                *corner_indices.entry(corner).or_insert_with(|| {
+                    corners.push(corner);
+                    corners.len() - 1
+                })
+            })
+        })
+        .collect();
+
+    let mut lines = vec![
+        "ply".to_string(),
+        "format ascii 1.0".to_string(),
+        format!("element vertex {}", corners.len()),
+    ];
+    for property in POSITION_PROPERTIES
+        .iter()
+        .chain(&NORMAL_PROPERTIES)
+        .chain(&UV_PROPERTIES[0])
+    {
+        lines.push(format!("property double {}", property));
+    }
+    if !colors.is_empty() {
+        for property in COLOR_PROPERTIES {
+            lines.push(format!("property uchar {}", property));
+        }
+    }
+    lines.push(format!("element face {}", faces.len()));
+    lines.push(format!(
+        "property list uchar uint {}",
+        FACE_INDICES_PROPERTIES[0]
+    ));
+    lines.push(END_HEADER.to_string());
+
+    for (vertex_index, normal_index, uv_index) in corners {
+        let (vertex, normal, uv) = (vertices[vertex_index], normals[normal_index], uvs[uv_index]);
+        let mut line = format!(
+            "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+            vertex.x(),
+            vertex.y(),
+            vertex.z(),
+            normal.x(),
+            normal.y(),
+            normal.z(),
+            uv.x(),
+            uv.y()
+        );
+        if let Some(color) = colors.get(vertex_index) {
+            let channel = |value: f64| (value.clamp(0., 1.) * u8::MAX as f64).round() as u8;
+            line += &format!(
+                " {} {} {}",
+                channel(color.x()),
+                channel(color.y()),
+                channel(color.z())
+            );
+        }
+        lines.push(line);
+    }
+    for [a, b, c] in faces {
+        lines.push(format!("3 {} {} {}", a, b, c));
+    }
+
+    lines.push(String::new());
+    fs::write(filename, lines.join("\n")).map_err(|err| Error::io(filename, err))
+}
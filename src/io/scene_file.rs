@@ -0,0 +1,1136 @@
+//! Loader for `.scene` description files.
+//!
+//! A scene file is a plain text file made of sections. Each section starts
+//! with a `[name]` header and is followed by `key = value` lines. Blank lines
+//! and everything after a `#` are ignored. Vectors are written as three
+//! whitespace separated numbers, angles are given in degrees and relative
+//! paths are resolved against the directory containing the scene file.
+//!
+//! ```text
+//! [image]
+//! width = 512
+//! height = 512
+//! output = ../figures/basic_spheres_demo.png
+//!
+//! [camera]
+//! position = 0 55 0
+//! rotation = 0 0 180
+//! fov = 75
+//!
+//! [sphere]
+//! center = 0 0 0
+//! radius = 10
+//! color = 1 1 1
+//!
+//! [point_light]
+//! position = 20 20 20
+//! intensity = 5e9
+//! ```
+//!
+//! See the README for the full list of sections and keys. Errors are reported
+//! as `Error::Parse`, with the file, line and offending key.
+
+use {
+    crate::{
+        core::{
+            BVHBuildMethod, BVHTree, MaterialLibrary, Mesh, MeshObjectBuilder, PlaneBuilder,
+            PointLightSource, QuadricBuilder, RenderSettings, Scene, SphereBuilder, SurfaceBuilder,
+            Texture, TorusBuilder,
+        },
+        error::{Error, Result},
+        io::GltfScene,
+        utils::{Transform, Vector3},
+        view::{Camera, Image},
+    },
+    std::{collections::HashMap, fs, path::Path, sync::Arc},
+};
+
+const DEFAULT_WIDTH: u32 = 512;
+const DEFAULT_HEIGHT: u32 = 512;
+const DEFAULT_OUTPUT: &str = "output.png";
+const MTL_TEXTURE: &str = "mtl";
+const DEFAULT_TORUS_AXIS: Vector3 = Vector3::new(0., 0., 1.);
+const DEFAULT_CAMERA_UP: Vector3 = Vector3::new(0., 0., 1.);
+
+/// Everything needed to render a scene: the scene itself, the point of view
+/// and the output image parameters.
+pub struct SceneDescription {
+    scene: Scene,
+    camera: Camera,
+    width: u32,
+    height: u32,
+    output: String,
+    settings: RenderSettings,
+    warnings: Vec<String>,
+    /// The glTF file giving the camera, if there is no [camera] section. Its
+    /// field of view depends on the aspect ratio of the image.
+    gltf_camera: Option<GltfScene>,
+}
+
+impl SceneDescription {
+    pub fn new(
+        scene: Scene,
+        camera: Camera,
+        width: u32,
+        height: u32,
+        output: &str,
+        settings: RenderSettings,
+    ) -> Self {
+        SceneDescription {
+            scene,
+            camera,
+            width,
+            height,
+            output: output.to_string(),
+            settings,
+            warnings: Vec::new(),
+            gltf_camera: None,
+        }
+    }
+
+    pub fn get_scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    pub fn get_camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn get_settings(&self) -> &RenderSettings {
+        &self.settings
+    }
+
+    /// Problems found while loading the scene and its meshes that did not
+    /// prevent it from loading.
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    /// Change the size of the image. A camera taken from a glTF file is
+    /// rebuilt for the new aspect ratio.
+    pub fn set_size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        if let Some(camera) = self
+            .gltf_camera
+            .as_ref()
+            .and_then(|gltf_scene| gltf_scene.calculate_camera(width, height))
+        {
+            self.camera = camera;
+        }
+        self
+    }
+
+    pub fn set_output(&mut self, output: &str) -> &mut Self {
+        self.output = output.to_string();
+        self
+    }
+
+    /// Replace the render settings. Settings that apply to meshes (normal
+    /// mapping, BVH leaf size) are only read while loading, so changing them
+    /// here has no effect on the already built scene.
+    pub fn set_settings(&mut self, settings: RenderSettings) -> &mut Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn into_image(self) -> Image {
+        Image::new(
+            self.width,
+            self.height,
+            self.camera,
+            self.scene,
+            self.settings,
+        )
+    }
+}
+
+impl SceneDescription {
+    pub fn from_file(filename: &str) -> Result<Self> {
+        Self::from_file_with_settings(filename, RenderSettings::default())
+    }
+
+    /// Load a scene file, starting from the given settings. The `[render]`
+    /// section of the file, if any, overrides them.
+    pub fn from_file_with_settings(filename: &str, settings: RenderSettings) -> Result<Self> {
+        Self::from_file_with_overrides(filename, settings, |_| {})
+    }
+
+    /// Load a scene file like `from_file_with_settings`, then let `overrides`
+    /// change the settings read from the `[render]` section, before the
+    /// meshes are built.
+    pub fn from_file_with_overrides(
+        filename: &str,
+        settings: RenderSettings,
+        overrides: impl FnOnce(&mut RenderSettings),
+    ) -> Result<Self> {
+        let contents = fs::read_to_string(filename).map_err(|err| Error::io(filename, err))?;
+        let base_directory = Path::new(filename)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+
+        let sections = parse_sections(filename, &contents)?;
+
+        let mut loader = SceneLoader {
+            filename,
+            base_directory,
+            scene: Scene::new(),
+            camera: None,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            output: base_directory.join(DEFAULT_OUTPUT).display().to_string(),
+            meshes: HashMap::new(),
+            obj_meshes: HashMap::new(),
+            warnings: Vec::new(),
+            gltf_camera: None,
+            image_seen: false,
+            settings,
+        };
+
+        // The render settings affect how meshes are built, so they are loaded
+        // before the other sections.
+        let (render_sections, other_sections): (Vec<&Section>, Vec<&Section>) = sections
+            .iter()
+            .partition(|section| section.name == "render");
+        if let Some(section) = render_sections.get(1) {
+            return Err(loader.duplicate_section(section));
+        }
+        for section in render_sections {
+            loader.load_section(section)?;
+        }
+        overrides(&mut loader.settings);
+        for section in other_sections {
+            loader.load_section(section)?;
+        }
+
+        let gltf_camera = loader.gltf_camera.filter(|_| loader.camera.is_none());
+        let camera = match loader.camera {
+            Some(camera) => camera,
+            None => gltf_camera
+                .as_ref()
+                .and_then(|gltf_scene| gltf_scene.calculate_camera(loader.width, loader.height))
+                .ok_or_else(|| {
+                    Error::parse(filename, None, None, "missing [camera] section".to_string())
+                })?,
+        };
+
+        Ok(SceneDescription {
+            scene: loader.scene,
+            camera,
+            width: loader.width,
+            height: loader.height,
+            output: loader.output,
+            settings: loader.settings,
+            warnings: loader.warnings,
+            gltf_camera,
+        })
+    }
+}
+
+struct Entry {
+    key: String,
+    value: String,
+    line: usize,
+}
+
+struct Section {
+    name: String,
+    line: usize,
+    entries: Vec<Entry>,
+}
+
+fn parse_sections(filename: &str, contents: &str) -> Result<Vec<Section>> {
+    let mut sections: Vec<Section> = Vec::new();
+
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header.strip_suffix(']').ok_or_else(|| {
+                Error::parse(
+                    filename,
+                    Some(line_number),
+                    None,
+                    format!("malformed section header `{}`", line),
+                )
+            })?;
+            sections.push(Section {
+                name: name.trim().to_string(),
+                line: line_number,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            Error::parse(
+                filename,
+                Some(line_number),
+                None,
+                format!("expected `key = value`, found `{}`", line),
+            )
+        })?;
+        let key = key.trim();
+        let value = value.trim();
+
+        let section = sections.last_mut().ok_or_else(|| {
+            Error::parse(
+                filename,
+                Some(line_number),
+                Some(key),
+                "key outside of any section".to_string(),
+            )
+        })?;
+
+        if section.entries.iter().any(|entry| entry.key == key) {
+            return Err(Error::parse(
+                filename,
+                Some(line_number),
+                Some(key),
+                format!("duplicate key in [{}] section", section.name),
+            ));
+        }
+
+        section.entries.push(Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            line: line_number,
+        });
+    }
+
+    Ok(sections)
+}
+
+/// Typed access to the entries of a section. Every key read is marked as
+/// used, so that leftover keys can be reported as unknown by `finish`.
+struct SectionReader<'a> {
+    filename: &'a str,
+    section: &'a Section,
+    used: Vec<bool>,
+}
+
+impl<'a> SectionReader<'a> {
+    fn new(filename: &'a str, section: &'a Section) -> Self {
+        SectionReader {
+            filename,
+            section,
+            used: vec![false; section.entries.len()],
+        }
+    }
+
+    fn error(&self, entry: &Entry, message: String) -> Error {
+        Error::parse(self.filename, Some(entry.line), Some(&entry.key), message)
+    }
+
+    fn missing(&self, key: &str) -> Error {
+        Error::parse(
+            self.filename,
+            Some(self.section.line),
+            Some(key),
+            format!("missing required key in [{}] section", self.section.name),
+        )
+    }
+
+    fn entry(&mut self, key: &str) -> Option<&'a Entry> {
+        let index = self
+            .section
+            .entries
+            .iter()
+            .position(|entry| entry.key == key)?;
+        self.used[index] = true;
+        Some(&self.section.entries[index])
+    }
+
+    fn string(&mut self, key: &str) -> Option<&'a str> {
+        self.entry(key).map(|entry| entry.value.as_str())
+    }
+
+    fn f64(&mut self, key: &str) -> Result<Option<f64>> {
+        match self.entry(key) {
+            Some(entry) => entry.value.parse::<f64>().map(Some).map_err(|_| {
+                self.error(entry, format!("expected a number, found `{}`", entry.value))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn positive_f64(&mut self, key: &str) -> Result<Option<f64>> {
+        match self.entry(key) {
+            Some(entry) => match entry.value.parse::<f64>() {
+                Ok(value) if value > 0. => Ok(Some(value)),
+                _ => Err(self.error(
+                    entry,
+                    format!("expected a positive number, found `{}`", entry.value),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn u32(&mut self, key: &str) -> Result<Option<u32>> {
+        match self.entry(key) {
+            Some(entry) => entry.value.parse::<u32>().map(Some).map_err(|_| {
+                self.error(
+                    entry,
+                    format!("expected a non-negative integer, found `{}`", entry.value),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn positive_u32(&mut self, key: &str) -> Result<Option<u32>> {
+        match self.entry(key) {
+            Some(entry) => match entry.value.parse::<u32>() {
+                Ok(value) if value > 0 => Ok(Some(value)),
+                _ => Err(self.error(
+                    entry,
+                    format!("expected a positive integer, found `{}`", entry.value),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn u64(&mut self, key: &str) -> Result<Option<u64>> {
+        match self.entry(key) {
+            Some(entry) => entry.value.parse::<u64>().map(Some).map_err(|_| {
+                self.error(
+                    entry,
+                    format!("expected a non-negative integer, found `{}`", entry.value),
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn bool(&mut self, key: &str) -> Result<Option<bool>> {
+        match self.entry(key) {
+            Some(entry) => match entry.value.as_str() {
+                "true" => Ok(Some(true)),
+                "false" => Ok(Some(false)),
+                _ => Err(self.error(
+                    entry,
+                    format!("expected `true` or `false`, found `{}`", entry.value),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn vector3(&mut self, key: &str) -> Result<Option<Vector3>> {
+        match self.entry(key) {
+            Some(entry) => {
+                let components: Vec<f64> = entry
+                    .value
+                    .split_whitespace()
+                    .map(|component| component.parse::<f64>())
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|_| {
+                        self.error(
+                            entry,
+                            format!("expected three numbers, found `{}`", entry.value),
+                        )
+                    })?;
+                if components.len() != 3 {
+                    return Err(self.error(
+                        entry,
+                        format!("expected three numbers, found `{}`", entry.value),
+                    ));
+                }
+                Ok(Some(Vector3::new(
+                    components[0],
+                    components[1],
+                    components[2],
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Either a single number, for a uniform scale, or one number per axis.
+    fn scale(&mut self, key: &str) -> Result<Option<Vector3>> {
+        let Some(entry) = self.entry(key) else {
+            return Ok(None);
+        };
+        let components: Vec<f64> = entry
+            .value
+            .split_whitespace()
+            .map(|component| component.parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| {
+                self.error(
+                    entry,
+                    format!("expected one or three numbers, found `{}`", entry.value),
+                )
+            })?;
+        let scale = match components[..] {
+            [scale] => Vector3::new(scale, scale, scale),
+            [x, y, z] => Vector3::new(x, y, z),
+            _ => {
+                return Err(self.error(
+                    entry,
+                    format!("expected one or three numbers, found `{}`", entry.value),
+                ))
+            }
+        };
+        if scale.x() == 0. || scale.y() == 0. || scale.z() == 0. {
+            return Err(self.error(entry, "scale factors must not be zero".to_string()));
+        }
+        Ok(Some(scale))
+    }
+
+    fn required_f64(&mut self, key: &str) -> Result<f64> {
+        self.f64(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    fn required_positive_f64(&mut self, key: &str) -> Result<f64> {
+        self.positive_f64(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    /// A point that must differ from the one read for `other_key`, such as
+    /// the two ends of an axis.
+    fn required_distinct_vector3(
+        &mut self,
+        key: &str,
+        other_key: &str,
+        other: &Vector3,
+    ) -> Result<Vector3> {
+        let vector = self.required_vector3(key)?;
+        if (vector - *other).norm2() == 0. {
+            if let Some(entry) = self.entry(key) {
+                return Err(self.error(entry, format!("must differ from `{}`", other_key)));
+            }
+        }
+        Ok(vector)
+    }
+
+    fn required_vector3(&mut self, key: &str) -> Result<Vector3> {
+        self.vector3(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.used.iter().position(|used| !used) {
+            Some(index) => {
+                let entry = &self.section.entries[index];
+                Err(self.error(
+                    entry,
+                    format!("unknown key in [{}] section", self.section.name),
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// The BVHs of the meshes of a scene file are shared by file, group and
+/// subdivision levels.
+type MeshKey = (String, Option<String>, usize);
+
+struct SceneLoader<'a> {
+    filename: &'a str,
+    base_directory: &'a Path,
+    scene: Scene,
+    camera: Option<Camera>,
+    width: u32,
+    height: u32,
+    output: String,
+    meshes: HashMap<MeshKey, Arc<BVHTree>>,
+    /// Files whose groups are loaded separately, parsed once
+    obj_meshes: HashMap<String, Mesh>,
+    warnings: Vec<String>,
+    /// The first glTF file with a camera, used if there is no [camera]
+    /// section
+    gltf_camera: Option<GltfScene>,
+    image_seen: bool,
+    settings: RenderSettings,
+}
+
+impl SceneLoader<'_> {
+    fn load_section(&mut self, section: &Section) -> Result<()> {
+        let mut reader = SectionReader::new(self.filename, section);
+
+        match section.name.as_str() {
+            "render" => self.load_render(&mut reader)?,
+            "image" => self.load_image(section, &mut reader)?,
+            "camera" => self.load_camera(section, &mut reader)?,
+            "sphere" => self.load_sphere(&mut reader)?,
+            "plane" => self.load_plane(&mut reader)?,
+            "box" => self.load_box(&mut reader)?,
+            "cylinder" => self.load_cylinder(&mut reader)?,
+            "cone" => self.load_cone(&mut reader)?,
+            "disk" => self.load_disk(&mut reader)?,
+            "torus" => self.load_torus(&mut reader)?,
+            "mesh" => self.load_mesh(&mut reader)?,
+            "gltf" => self.load_gltf(&mut reader)?,
+            "point_light" => self.load_point_light(&mut reader)?,
+            "spherical_light" => self.load_spherical_light(&mut reader)?,
+            _ => {
+                return Err(Error::parse(
+                    self.filename,
+                    Some(section.line),
+                    None,
+                    format!("unknown section [{}]", section.name),
+                ))
+            }
+        }
+
+        reader.finish()
+    }
+
+    fn resolve_path(&self, path: &str) -> String {
+        self.base_directory.join(path).display().to_string()
+    }
+
+    fn duplicate_section(&self, section: &Section) -> Error {
+        Error::parse(
+            self.filename,
+            Some(section.line),
+            None,
+            format!("duplicate [{}] section", section.name),
+        )
+    }
+
+    fn load_image(&mut self, section: &Section, reader: &mut SectionReader) -> Result<()> {
+        if self.image_seen {
+            return Err(self.duplicate_section(section));
+        }
+        self.image_seen = true;
+
+        if let Some(width) = reader.positive_u32("width")? {
+            self.width = width;
+        }
+        if let Some(height) = reader.positive_u32("height")? {
+            self.height = height;
+        }
+        if let Some(output) = reader.string("output") {
+            self.output = self.resolve_path(output);
+        }
+        Ok(())
+    }
+
+    fn load_render(&mut self, reader: &mut SectionReader) -> Result<()> {
+        if let Some(preset_entry) = reader.entry("preset") {
+            self.settings =
+                RenderSettings::from_preset_name(&preset_entry.value).ok_or_else(|| {
+                    reader.error(
+                        preset_entry,
+                        format!(
+                            "unknown preset `{}`, expected `preview` or `final`",
+                            preset_entry.value
+                        ),
+                    )
+                })?;
+        }
+        if let Some(samples_per_pixel) = reader.positive_u32("samples_per_pixel")? {
+            self.settings.with_samples_per_pixel(samples_per_pixel);
+        }
+        if let Some(max_recursion_depth) = reader.positive_u32("max_recursion_depth")? {
+            let Ok(max_recursion_depth) = i32::try_from(max_recursion_depth) else {
+                let entry = reader.entry("max_recursion_depth").unwrap();
+                return Err(reader.error(
+                    entry,
+                    format!("the maximum recursion depth must be at most {}", i32::MAX),
+                ));
+            };
+            self.settings.with_max_recursion_depth(max_recursion_depth);
+        }
+        if let Some(fresnel) = reader.bool("fresnel")? {
+            self.settings.with_fresnel(fresnel);
+        }
+        if let Some(indirect_lighting) = reader.bool("indirect_lighting")? {
+            self.settings.with_indirect_lighting(indirect_lighting);
+        }
+        if let Some(antialiasing) = reader.bool("antialiasing")? {
+            self.settings.with_antialiasing(antialiasing);
+        }
+        if let Some(normal_mapping) = reader.bool("normal_mapping")? {
+            self.settings.with_normal_mapping(normal_mapping);
+        }
+        if let Some(crease_angle) = reader.f64("crease_angle")? {
+            self.settings.with_crease_angle(crease_angle.to_radians());
+        }
+        if let Some(subdivision_levels) = reader.u32("subdivision")? {
+            self.settings
+                .with_subdivision_levels(subdivision_levels as usize);
+        }
+        if let Some(bvh_leaf_size) = reader.positive_u32("bvh_leaf_size")? {
+            self.settings.with_bvh_leaf_size(bvh_leaf_size as usize);
+        }
+        if let Some(builder_entry) = reader.entry("bvh_builder") {
+            let method = BVHBuildMethod::from_name(&builder_entry.value).ok_or_else(|| {
+                reader.error(
+                    builder_entry,
+                    format!(
+                        "unknown BVH builder `{}`, expected `midpoint` or `sah`",
+                        builder_entry.value
+                    ),
+                )
+            })?;
+            self.settings.with_bvh_build_method(method);
+        }
+        if let Some(bvh_traversal_cost) = reader.f64("bvh_traversal_cost")? {
+            if bvh_traversal_cost < 0. {
+                if let Some(entry) = reader.entry("bvh_traversal_cost") {
+                    return Err(reader.error(
+                        entry,
+                        "the BVH traversal cost must not be negative".to_string(),
+                    ));
+                }
+            }
+            self.settings.with_bvh_traversal_cost(bvh_traversal_cost);
+        }
+        if let Some(bvh_cache) = reader.bool("bvh_cache")? {
+            self.settings.with_bvh_cache(bvh_cache);
+        }
+        if let Some(seed) = reader.u64("seed")? {
+            self.settings.with_seed(seed);
+        }
+        Ok(())
+    }
+
+    fn load_camera(&mut self, section: &Section, reader: &mut SectionReader) -> Result<()> {
+        if self.camera.is_some() {
+            return Err(self.duplicate_section(section));
+        }
+
+        let position = reader.required_vector3("position")?;
+        let fov = reader.required_f64("fov")?;
+        if !(fov > 0. && fov < 180.) {
+            if let Some(entry) = reader.entry("fov") {
+                return Err(reader.error(
+                    entry,
+                    "the field of view must be between 0 and 180 degrees, exclusive".to_string(),
+                ));
+            }
+        }
+        let fov = fov.to_radians();
+
+        if let Some(look_at_entry) = reader.entry("look_at") {
+            if let Some(rotation_entry) = reader.entry("rotation") {
+                return Err(reader.error(
+                    rotation_entry,
+                    "`rotation` and `look_at` cannot be used together".to_string(),
+                ));
+            }
+            let target = reader.required_vector3("look_at")?;
+            let up = reader.vector3("up")?.unwrap_or(DEFAULT_CAMERA_UP);
+            if (target - position).norm2() == 0. {
+                return Err(reader.error(
+                    look_at_entry,
+                    "the camera cannot look at its own position".to_string(),
+                ));
+            }
+            self.camera = Some(Camera::new_looking_at(position, target, up, fov));
+            return Ok(());
+        }
+
+        let rotation = reader
+            .vector3("rotation")?
+            .unwrap_or(Vector3::new(0., 0., 0.));
+        self.camera = Some(Camera::new(position, degrees_to_radians(rotation), fov));
+        Ok(())
+    }
+
+    fn load_sphere(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let center = reader.required_vector3("center")?;
+        let radius = reader.required_positive_f64("radius")?;
+
+        let mut builder = SphereBuilder::new(center, radius);
+        let scale = reader.scale("scale")?;
+        let rotation = reader.vector3("rotation")?;
+        if scale.is_some() || rotation.is_some() {
+            // Scale and rotate the sphere around its center
+            let mut transform = Transform::new_translation(-center);
+            if let Some(scale) = scale {
+                transform = transform.then(&Transform::new_scale(scale));
+            }
+            if let Some(rotation) = rotation {
+                transform = transform.then(&Transform::new_rotation(degrees_to_radians(rotation)));
+            }
+            builder.with_transform(&transform.then(&Transform::new_translation(center)));
+        }
+        if let Some(color) = reader.vector3("color")? {
+            builder.with_color(color);
+        }
+        if let Some(opaque) = reader.bool("opaque")? {
+            builder.with_opaque(opaque);
+        }
+        if let Some(mirror) = reader.bool("mirror")? {
+            builder.with_mirror(mirror);
+        }
+        if let Some(transparent) = reader.bool("transparent")? {
+            builder.with_transparent(transparent);
+        }
+        if let Some(refractive_index) = reader.f64("refractive_index")? {
+            builder.with_refractive_index(refractive_index);
+        }
+
+        self.scene.add_object(Box::new(builder.build()));
+        Ok(())
+    }
+
+    fn load_plane(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let point = reader.required_vector3("point")?;
+        let normal = reader.required_vector3("normal")?;
+        if normal.norm2() == 0. {
+            if let Some(entry) = reader.entry("normal") {
+                return Err(reader.error(entry, "the normal must not be zero".to_string()));
+            }
+        }
+
+        let mut builder = PlaneBuilder::new(point, normal);
+        self.load_surface(reader, &mut builder)?;
+        if let Some(tile_size) = reader.positive_f64("tile_size")? {
+            builder.with_tile_size(tile_size);
+        }
+
+        self.scene.add_object(Box::new(builder.build()));
+        Ok(())
+    }
+
+    /// Read the material keys shared by planes, quadrics and tori.
+    fn load_surface(
+        &self,
+        reader: &mut SectionReader,
+        builder: &mut impl SurfaceBuilder,
+    ) -> Result<()> {
+        if let Some(color) = reader.vector3("color")? {
+            builder.with_color(color);
+        }
+        if let Some(opaque) = reader.bool("opaque")? {
+            builder.with_opaque(opaque);
+        }
+        if let Some(mirror) = reader.bool("mirror")? {
+            builder.with_mirror(mirror);
+        }
+        if let Some(transparent) = reader.bool("transparent")? {
+            builder.with_transparent(transparent);
+        }
+        if let Some(refractive_index) = reader.f64("refractive_index")? {
+            builder.with_refractive_index(refractive_index);
+        }
+        if let Some(light_intensity) = reader.f64("light_intensity")? {
+            builder.with_light_intensity(light_intensity);
+        }
+        if let Some(texture_entry) = reader.entry("texture") {
+            let texture_file = self.resolve_path(&texture_entry.value);
+            if !Path::new(&texture_file).is_file() {
+                return Err(reader.error(
+                    texture_entry,
+                    format!("texture file `{}` not found", texture_file),
+                ));
+            }
+            builder.with_texture(Texture::from_img_file(&texture_file)?);
+        }
+        Ok(())
+    }
+
+    fn load_box(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let min = reader.required_vector3("min")?;
+        let max = reader.required_vector3("max")?;
+        let size = max - min;
+        if size.x() == 0. || size.y() == 0. || size.z() == 0. {
+            if let Some(entry) = reader.entry("max") {
+                return Err(reader.error(
+                    entry,
+                    "the corners must differ along every axis".to_string(),
+                ));
+            }
+        }
+
+        let builder = QuadricBuilder::new_box(min, max);
+        self.load_quadric(reader, builder)
+    }
+
+    fn load_cylinder(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let base = reader.required_vector3("base")?;
+        let top = reader.required_distinct_vector3("top", "base", &base)?;
+        let radius = reader.required_positive_f64("radius")?;
+
+        let mut builder = QuadricBuilder::new_cylinder(base, top, radius);
+        if let Some(capped) = reader.bool("capped")? {
+            builder.with_caps(capped);
+        }
+        self.load_quadric(reader, builder)
+    }
+
+    fn load_cone(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let base = reader.required_vector3("base")?;
+        let apex = reader.required_distinct_vector3("apex", "base", &base)?;
+        let radius = reader.required_positive_f64("radius")?;
+
+        let mut builder = QuadricBuilder::new_cone(base, apex, radius);
+        if let Some(capped) = reader.bool("capped")? {
+            builder.with_caps(capped);
+        }
+        self.load_quadric(reader, builder)
+    }
+
+    fn load_disk(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let center = reader.required_vector3("center")?;
+        let normal = reader.required_vector3("normal")?;
+        if normal.norm2() == 0. {
+            if let Some(entry) = reader.entry("normal") {
+                return Err(reader.error(entry, "the normal must not be zero".to_string()));
+            }
+        }
+        let radius = reader.required_positive_f64("radius")?;
+
+        let builder = QuadricBuilder::new_disk(center, normal, radius);
+        self.load_quadric(reader, builder)
+    }
+
+    fn load_torus(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let center = reader.required_vector3("center")?;
+        let axis = reader.vector3("axis")?.unwrap_or(DEFAULT_TORUS_AXIS);
+        if axis.norm2() == 0. {
+            if let Some(entry) = reader.entry("axis") {
+                return Err(reader.error(entry, "the axis must not be zero".to_string()));
+            }
+        }
+        let major_radius = reader.required_positive_f64("major_radius")?;
+        let minor_radius = reader.required_positive_f64("minor_radius")?;
+
+        let mut builder = TorusBuilder::new(center, axis, major_radius, minor_radius);
+        self.load_surface(reader, &mut builder)?;
+
+        self.scene.add_object(Box::new(builder.build()));
+        Ok(())
+    }
+
+    /// Read the material keys of boxes, cylinders, cones and disks.
+    fn load_quadric(
+        &mut self,
+        reader: &mut SectionReader,
+        mut builder: QuadricBuilder,
+    ) -> Result<()> {
+        self.load_surface(reader, &mut builder)?;
+
+        self.scene.add_object(Box::new(builder.build()));
+        Ok(())
+    }
+
+    fn load_mesh(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let file_entry = reader.entry("file").ok_or_else(|| reader.missing("file"))?;
+        let file = self.resolve_path(&file_entry.value);
+        if !Path::new(&file).is_file() {
+            return Err(reader.error(file_entry, format!("mesh file `{}` not found", file)));
+        }
+
+        let group_entry = reader.entry("group");
+        let mut settings = self.settings;
+        if let Some(subdivision_levels) = reader.u32("subdivision")? {
+            settings.with_subdivision_levels(subdivision_levels as usize);
+        }
+        let key = (
+            file.clone(),
+            group_entry.map(|entry| entry.value.clone()),
+            settings.get_subdivision_levels(),
+        );
+
+        if !self.meshes.contains_key(&key) {
+            let bvh = match group_entry {
+                Some(group_entry) => {
+                    if !self.obj_meshes.contains_key(&file) {
+                        let mesh = Mesh::from_file_with_crease_angle(
+                            &file,
+                            self.settings.get_crease_angle(),
+                        )?;
+                        self.add_warnings(mesh.get_warnings());
+                        self.obj_meshes.insert(file.clone(), mesh);
+                    }
+                    let mesh = &self.obj_meshes[&file];
+                    let group_index = mesh
+                        .get_groups()
+                        .iter()
+                        .position(|group| *group == group_entry.value)
+                        .ok_or_else(|| {
+                            reader.error(
+                                group_entry,
+                                format!(
+                                    "mesh file `{}` has no group `{}`",
+                                    file, group_entry.value
+                                ),
+                            )
+                        })?;
+                    BVHTree::new_from_mesh_with_settings(mesh.extract_group(group_index), &settings)
+                }
+                None => {
+                    let bvh = BVHTree::new_from_file(&file, &settings)?;
+                    self.add_warnings(bvh.get_mesh().get_warnings());
+                    bvh
+                }
+            };
+            self.meshes.insert(key.clone(), Arc::new(bvh));
+        }
+
+        let mut builder = MeshObjectBuilder::new_instance(&self.meshes[&key]);
+        if let Some(rotation) = reader.vector3("rotation")? {
+            builder.with_rotation(degrees_to_radians(rotation));
+        }
+        if let Some(translation) = reader.vector3("translation")? {
+            builder.with_translation(translation);
+        }
+        if let Some(scale) = reader.scale("scale")? {
+            builder.with_transform(&Transform::new_scale(scale));
+        }
+        if let Some(color) = reader.vector3("color")? {
+            builder.with_color(color);
+        }
+        if let Some(opaque) = reader.bool("opaque")? {
+            builder.with_opaque(opaque);
+        }
+        if let Some(mirror) = reader.bool("mirror")? {
+            builder.with_mirror(mirror);
+        }
+        if let Some(transparent) = reader.bool("transparent")? {
+            builder.with_transparent(transparent);
+        }
+        if let Some(refractive_index) = reader.f64("refractive_index")? {
+            builder.with_refractive_index(refractive_index);
+        }
+        if let Some(texture_entry) = reader.entry("texture") {
+            let texture_file = self.resolve_path(&texture_entry.value);
+            let texture = if texture_entry.value == MTL_TEXTURE || texture_file.ends_with(".mtl") {
+                let library = self.load_material_library(reader, texture_entry, &key)?;
+                let library_file = match texture_entry.value == MTL_TEXTURE {
+                    true => &file,
+                    false => &texture_file,
+                };
+                Texture::from_material_library(&library, library_file)?
+            } else {
+                if !Path::new(&texture_file).is_file() {
+                    return Err(reader.error(
+                        texture_entry,
+                        format!("texture file `{}` not found", texture_file),
+                    ));
+                }
+                Texture::from_img_file(&texture_file)?
+            };
+            builder.with_texture(texture);
+        }
+        if let Some(materials_entry) = reader.entry("materials") {
+            let library = self.load_material_library(reader, materials_entry, &key)?;
+            builder.with_material_library(&library);
+        }
+
+        self.scene.add_object(Box::new(builder.build()));
+        Ok(())
+    }
+
+    /// Load the material libraries of a mesh: the ones referenced by its OBJ
+    /// file for `mtl`, or else the MTL file of the entry.
+    fn load_material_library(
+        &mut self,
+        reader: &SectionReader,
+        entry: &Entry,
+        key: &MeshKey,
+    ) -> Result<MaterialLibrary> {
+        let library = if entry.value == MTL_TEXTURE {
+            let mesh = self.meshes[key].get_mesh();
+            if mesh.get_material_libraries().is_empty() {
+                return Err(reader.error(
+                    entry,
+                    format!("mesh file `{}` has no material library (`mtllib`)", key.0),
+                ));
+            }
+            MaterialLibrary::from_mesh(mesh)?
+        } else {
+            let library_file = self.resolve_path(&entry.value);
+            if !Path::new(&library_file).is_file() {
+                return Err(
+                    reader.error(entry, format!("material file `{}` not found", library_file))
+                );
+            }
+            MaterialLibrary::from_mtl_file(&library_file)?
+        };
+        self.add_warnings(library.get_warnings());
+        Ok(library)
+    }
+
+    fn load_gltf(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let file_entry = reader.entry("file").ok_or_else(|| reader.missing("file"))?;
+        let file = self.resolve_path(&file_entry.value);
+        if !Path::new(&file).is_file() {
+            return Err(reader.error(file_entry, format!("glTF file `{}` not found", file)));
+        }
+
+        let mut gltf_scene = GltfScene::from_glb_file(&file, &self.settings)?;
+        self.add_warnings(gltf_scene.get_warnings());
+        if let Some(rotation) = reader.vector3("rotation")? {
+            gltf_scene.with_transform(&Transform::new_rotation(degrees_to_radians(rotation)));
+        }
+        if let Some(translation) = reader.vector3("translation")? {
+            gltf_scene.with_transform(&Transform::new_translation(translation));
+        }
+        if let Some(scale) = reader.scale("scale")? {
+            gltf_scene.with_transform(&Transform::new_scale(scale));
+        }
+
+        for object in gltf_scene.build_objects() {
+            self.scene.add_object(Box::new(object));
+        }
+        if self.gltf_camera.is_none() && gltf_scene.has_camera() {
+            self.gltf_camera = Some(gltf_scene);
+        }
+        Ok(())
+    }
+
+    /// Record warnings, once even if a file is loaded several times.
+    fn add_warnings(&mut self, warnings: &[String]) {
+        for warning in warnings {
+            if !self.warnings.contains(warning) {
+                self.warnings.push(warning.clone());
+            }
+        }
+    }
+
+    fn load_point_light(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let position = reader.required_vector3("position")?;
+        let intensity = reader.required_f64("intensity")?;
+
+        self.scene
+            .add_light_source(Box::new(PointLightSource::new(position, intensity)));
+        Ok(())
+    }
+
+    fn load_spherical_light(&mut self, reader: &mut SectionReader) -> Result<()> {
+        let center = reader.required_vector3("center")?;
+        let radius = reader.required_positive_f64("radius")?;
+        let intensity = reader.required_f64("intensity")?;
+
+        let light_sphere = SphereBuilder::new(center, radius)
+            .with_light_intensity(intensity)
+            .build();
+
+        self.scene
+            .add_object(Box::new(light_sphere.clone()))
+            .add_light_source(Box::new(light_sphere));
+        Ok(())
+    }
+}
+
+fn degrees_to_radians(angles: Vector3) -> Vector3 {
+    Vector3::new(
+        angles.x().to_radians(),
+        angles.y().to_radians(),
+        angles.z().to_radians(),
+    )
+}
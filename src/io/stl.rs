@@ -0,0 +1,173 @@
+//! Loader for STL files, in ASCII or binary format.
+//!
+//! STL files list independent triangles, so vertices at the same position are
+//! merged to let normals be smoothed across triangles. The facet normals of
+//! the file are ignored, and normals are generated instead. Each `solid` of
+//! an ASCII file becomes a group of the mesh.
+
+use {
+    crate::{
+        core::{IndexedMeshData, Mesh, VertexWelder, DEFAULT_CREASE_ANGLE, DEFAULT_GROUP_NAME},
+        error::{Error, Result},
+        utils::Vector3,
+    },
+    std::{fs, result},
+};
+
+pub(crate) const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_TRIANGLE_SIZE: u64 = 50;
+const ASCII_MAGIC: &[u8] = b"solid";
+
+impl Mesh {
+    /// Load an STL file, generating its normals like for OBJ files without
+    /// normals.
+    pub fn from_stl_file(filename: &str) -> Result<Mesh> {
+        load_stl_file(filename, DEFAULT_CREASE_ANGLE)
+    }
+}
+
+/// Whether the first bytes and size of a file are those of an STL file. A
+/// binary file is recognized by its size matching its triangle count.
+pub(crate) fn is_stl_header(bytes: &[u8], file_size: u64) -> bool {
+    bytes.starts_with(ASCII_MAGIC) || is_binary_stl(bytes, file_size)
+}
+
+/// Binary files may also start with `solid`, so their size tells them apart.
+fn is_binary_stl(bytes: &[u8], file_size: u64) -> bool {
+    let Some(count) = bytes.get(BINARY_HEADER_SIZE - 4..BINARY_HEADER_SIZE) else {
+        return false;
+    };
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as u64;
+    file_size == BINARY_HEADER_SIZE as u64 + count * BINARY_TRIANGLE_SIZE
+}
+
+pub(crate) fn load_stl_file(filename: &str, crease_angle: f64) -> Result<Mesh> {
+    let bytes = fs::read(filename).map_err(|err| Error::io(filename, err))?;
+
+    let mut builder = StlBuilder::default();
+    if is_binary_stl(&bytes, bytes.len() as u64) {
+        for triangle in bytes[BINARY_HEADER_SIZE..].chunks_exact(BINARY_TRIANGLE_SIZE as usize) {
+            // The facet normal comes first, then the three vertices
+            let read_vertex = |offset: usize| {
+                let read_f32 = |offset: usize| {
+                    let value = &triangle[offset..offset + 4];
+                    f32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                };
+                Vector3::new(read_f32(offset), read_f32(offset + 4), read_f32(offset + 8))
+            };
+            builder.add_facet(&[read_vertex(12), read_vertex(24), read_vertex(36)]);
+        }
+    } else {
+        let contents = String::from_utf8_lossy(&bytes);
+        parse_ascii(&contents, &mut builder).map_err(|(line, keyword, message)| {
+            Error::parse(filename, Some(line), keyword.as_deref(), message)
+        })?;
+    }
+
+    builder.data.vertices = builder.welder.vertices;
+    Ok(Mesh::from_indexed_data(builder.data, crease_angle))
+}
+
+/// Parse an ASCII file. Errors come with their line and keyword.
+fn parse_ascii(
+    contents: &str,
+    builder: &mut StlBuilder,
+) -> result::Result<(), (usize, Option<String>, String)> {
+    let mut facet: Option<Vec<Vector3>> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some(keyword) = parts.first() else {
+            continue;
+        };
+        let error = |message: String| (index + 1, Some(keyword.to_string()), message);
+
+        match *keyword {
+            "solid" => builder.start_solid(&parts[1..].join(" ")),
+            "facet" => facet = Some(Vec::new()),
+            "vertex" => {
+                let vertices = facet
+                    .as_mut()
+                    .ok_or_else(|| error("vertex outside of a facet".to_string()))?;
+                if parts.len() != 4 {
+                    return Err(error(format!("invalid vertex line: {:?}", parts)));
+                }
+                let mut coordinates = [0.; 3];
+                for (coordinate, token) in coordinates.iter_mut().zip(&parts[1..]) {
+                    *coordinate = token
+                        .parse::<f64>()
+                        .map_err(|_| error(format!("expected a number, found `{}`", token)))?;
+                }
+                vertices.push(Vector3::new(coordinates[0], coordinates[1], coordinates[2]));
+            }
+            "endfacet" => {
+                let vertices = facet
+                    .take()
+                    .ok_or_else(|| error("`endfacet` without `facet`".to_string()))?;
+                if vertices.len() < 3 {
+                    return Err(error(format!(
+                        "facet with only {} vertices",
+                        vertices.len()
+                    )));
+                }
+                builder.add_facet(&vertices);
+            }
+            "outer" | "endloop" | "endsolid" => {}
+            _ => return Err(error("unknown keyword".to_string())),
+        }
+    }
+
+    if facet.is_some() {
+        return Err((
+            contents.lines().count(),
+            None,
+            "missing `endfacet`".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Merges the vertices of the facets, and groups them by solid.
+#[derive(Default)]
+struct StlBuilder {
+    data: IndexedMeshData,
+    welder: VertexWelder,
+    solid_name: Option<String>,
+    /// Groups are only created once they hold a facet
+    group_index: Option<usize>,
+}
+
+impl StlBuilder {
+    fn start_solid(&mut self, name: &str) {
+        self.solid_name = Some(name.to_string());
+        self.group_index = None;
+    }
+
+    fn add_facet(&mut self, vertices: &[Vector3]) {
+        let group_index = *self.group_index.get_or_insert_with(|| {
+            let name = match self.solid_name.as_deref() {
+                None | Some("") => DEFAULT_GROUP_NAME,
+                Some(name) => name,
+            };
+            match self.data.groups.iter().position(|group| group == name) {
+                Some(group_index) => group_index,
+                None => {
+                    self.data.groups.push(name.to_string());
+                    self.data.groups.len() - 1
+                }
+            }
+        });
+
+        let polygon: Vec<usize> = vertices
+            .iter()
+            .map(|vertex| self.welder.weld(*vertex, None))
+            .collect();
+
+        for (a, b, c) in Mesh::triangulate_polygon(vertices) {
+            self.data
+                .triangles
+                .push((polygon[a], polygon[b], polygon[c]));
+            self.data.triangle_groups.push(group_index);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! A simple, CPU-bound ray tracer.
+//!
+//! Scenes are made of objects (`Sphere`, `Plane`, `Quadric`, `Torus`,
+//! `MeshObject`, or any type implementing `Object`) and light sources
+//! (`PointLightSource`, light emitting spheres, or any type implementing
+//! `LightSource`). An `Image` ties a `Scene` to a `Camera` and some
+//! `RenderSettings`, and renders it.
+//!
+//! ```no_run
+//! # fn main() -> raytracing_rust::Result<()> {
+//! use raytracing_rust::{
+//!     Camera, Image, PointLightSource, RenderSettings, Scene, SphereBuilder, Vector3,
+//! };
+//!
+//! let sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
+//!     .with_color(Vector3::new(1., 0., 0.))
+//!     .build();
+//! let light = PointLightSource::new(Vector3::new(20., 20., 20.), 5E9);
+//!
+//! let mut scene = Scene::new();
+//! scene
+//!     .add_object(Box::new(sphere))
+//!     .add_light_source(Box::new(light));
+//!
+//! let camera = Camera::new(
+//!     Vector3::new(0., 55., 0.),
+//!     Vector3::new(0., 0., std::f64::consts::PI),
+//!     75_f64.to_radians(),
+//! );
+//!
+//! let mut image = Image::new(512, 512, camera, scene, RenderSettings::preview());
+//! image.draw();
+//! image.save("sphere.png")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Scenes can also be loaded from scene files with `SceneDescription`.
+//!
+//! Loading scenes, meshes and textures, and saving images, report failures
+//! with `Error` rather than panicking.
+
+pub mod core;
+mod error;
+pub mod io;
+pub mod utils;
+pub mod view;
+
+pub use crate::{
+    core::{
+        BVHBuildMethod, BVHTree, Csg, CsgOperation, Intersectable, Intersection, LightSource,
+        Material, MaterialLibrary, Mesh, MeshObject, MeshObjectBuilder, Object, Plane,
+        PlaneBuilder, PointLightSource, Quadric, QuadricBuilder, Ray, RenderSettings, Scene,
+        Sphere, SphereBuilder, SurfaceBuilder, Texture, Torus, TorusBuilder,
+    },
+    error::{Error, ParseError, Result},
+    io::{GltfScene, SceneDescription},
+    utils::{Quaternion, Transform, Vector3},
+    view::{Camera, Image},
+};
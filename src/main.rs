@@ -1,488 +1,231 @@
-mod core;
-mod utils;
-mod view;
+mod cli;
+mod demos;
 
 use {
     crate::{
-        core::{Mesh, MeshObjectBuilder, PointLightSource, Scene, SphereBuilder},
-        utils::Vector3,
-        view::{Camera, Image},
+        cli::{BVHReportOptions, Command, RenderOptions, USAGE},
+        demos::DEMOS,
     },
-    core::Texture,
-    std::{f64::consts::PI, time::Instant},
+    raytracing_rust::{BVHBuildMethod, BVHTree, Mesh, RenderSettings, SceneDescription},
+    std::{fs, path::Path, process::ExitCode, time::Instant},
 };
 
-fn add_walls(scene: &mut Scene) -> &mut Scene {
-    // Teal sphere
-    let left_sphere = SphereBuilder::new(Vector3::new(-1000., 0., 0.), 940.)
-        .with_color(Vector3::new(0., 1., 1.))
-        .build();
-    // Yellow Sphere
-    let right_sphere = SphereBuilder::new(Vector3::new(1000., 0., 0.), 940.)
-        .with_color(Vector3::new(1., 1., 0.))
-        .build();
-    // Red Sphere
-    let top_sphere = SphereBuilder::new(Vector3::new(0., 0., 1000.), 940.)
-        .with_color(Vector3::new(1., 0., 0.))
-        .build();
-    // Blue Sphere
-    let bottom_sphere = SphereBuilder::new(Vector3::new(0., 0., -1000.), 990.)
-        .with_color(Vector3::new(0., 0., 1.))
-        .build();
-    // Purple Sphere
-    let front_sphere = SphereBuilder::new(Vector3::new(0., -1000., 0.), 940.)
-        .with_color(Vector3::new(1., 0., 1.))
-        .build();
-    // Green Sphere
-    let back_sphere = SphereBuilder::new(Vector3::new(0., 1000., 0.), 940.)
-        .with_color(Vector3::new(0., 1., 0.))
-        .build();
-
-    scene
-        .add_object(Box::new(left_sphere))
-        .add_object(Box::new(right_sphere))
-        .add_object(Box::new(top_sphere))
-        .add_object(Box::new(bottom_sphere))
-        .add_object(Box::new(back_sphere))
-        .add_object(Box::new(front_sphere))
-}
-
-fn time_function(f: fn() -> ()) -> u128 {
-    let start_time = Instant::now();
-
-    f();
-
-    start_time.elapsed().as_millis()
-}
-
-fn benchmark(f: fn() -> (), n: u32) {
-    let mut times: Vec<u128> = vec![0; n as usize];
-
-    for i in 0..n {
-        println!("Running iteration {}", i + 1);
-        let time = time_function(f);
-        times.push(time);
-        println!("Time: {} ms", time);
+const EXIT_FAILURE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+fn create_output_directory(output: &str) -> Result<(), String> {
+    if let Some(parent) = Path::new(output).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!(
+                    "could not create output directory {}: {}",
+                    parent.display(),
+                    err
+                )
+            })?;
+        }
     }
-
-    let average_time = times.iter().sum::<u128>() / n as u128;
-    println!("Average time: {} ms", average_time);
-}
-
-#[allow(dead_code)]
-fn basic_spheres_demo() {
-    let white_sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
-        .with_color(Vector3::new(1., 1., 1.))
-        .build();
-
-    let mut scene = Scene::new();
-
-    let point_light_source = PointLightSource::new(Vector3::new(20., 20., 20.), 5E9);
-
-    scene
-        .add_object(Box::new(white_sphere))
-        .add_light_source(Box::new(point_light_source));
-
-    add_walls(&mut scene);
-
-    let camera = Camera::new(
-        Vector3::new(0., 55., 0.),
-        Vector3::new(0., 0., PI),
-        75. * PI / 180.,
-    );
-
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/basic_spheres_demo.png");
-}
-
-#[allow(dead_code)]
-fn mirror_glass_demo() {
-    let mirror_sphere = SphereBuilder::new(Vector3::new(-25., 0., 0.), 10.)
-        .with_mirror(true)
-        .build();
-    let red_sphere = SphereBuilder::new(Vector3::new(25., 0., 0.), 10.)
-        .with_color(Vector3::new(1., 0., 0.))
-        .build();
-    let transparent_sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
-        .with_refractive_index(1.5)
-        .build();
-
-    let mut scene = Scene::new();
-
-    let point_light_source = PointLightSource::new(Vector3::new(20., 20., 20.), 5E9);
-
-    scene
-        .add_object(Box::new(mirror_sphere))
-        .add_object(Box::new(red_sphere))
-        .add_object(Box::new(transparent_sphere))
-        .add_light_source(Box::new(point_light_source));
-
-    add_walls(&mut scene);
-
-    let camera = Camera::new(
-        Vector3::new(0., 55., 0.),
-        Vector3::new(0., 0., PI),
-        75. * PI / 180.,
-    );
-
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/mirror_glass_spheres_demo.png");
+    Ok(())
 }
 
-#[allow(dead_code)]
-fn fresnel_demo() {
-    let transparent_sphere = SphereBuilder::new(Vector3::new(0., 0., 10.), 15.)
-        .with_refractive_index(1.5)
-        .build();
-    let mirror_sphere = SphereBuilder::new(Vector3::new(25., 0., 0.), 10.)
-        .with_mirror(true)
-        .build();
-    let red_sphere = SphereBuilder::new(Vector3::new(-25., 15., 20.), 5.)
-        .with_color(Vector3::new(1., 0., 0.))
-        .build();
-
-    let mut scene = Scene::new();
-
-    let point_light_source = PointLightSource::new(Vector3::new(20., 20., 30.), 5E9);
-
-    scene
-        .add_object(Box::new(mirror_sphere))
-        .add_object(Box::new(red_sphere))
-        .add_object(Box::new(transparent_sphere))
-        .add_light_source(Box::new(point_light_source));
-
-    add_walls(&mut scene);
-
-    let camera = Camera::new(
-        Vector3::new(30., 20., 5.),
-        Vector3::new(0., PI / 8., 3. * PI / 4.),
-        90. * PI / 180.,
-    );
-
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/fresnel_demo.png");
-}
-
-#[allow(dead_code)]
-fn indirect_lighting_demo() {
-    let red_sphere = SphereBuilder::new(Vector3::new(0., 10., 0.), 10.)
-        .with_color(Vector3::new(1., 0., 0.))
-        .build();
-    let pink_sphere = SphereBuilder::new(Vector3::new(0., 50., 0.), 10.)
-        .with_color(Vector3::new(1., 0.5, 0.5))
-        .build();
-    let orange_sphere = SphereBuilder::new(Vector3::new(-20., 30., 0.), 10.)
-        .with_color(Vector3::new(1., 0.5, 0.))
-        .build();
-    let yellow_sphere = SphereBuilder::new(Vector3::new(20., 30., 0.), 10.)
-        .with_color(Vector3::new(1., 1., 0.))
-        .build();
-
-    let mut scene = Scene::new();
-
-    let point_light_source = PointLightSource::new(Vector3::new(0., 30., 0.), 5E9);
-
-    scene
-        .add_object(Box::new(red_sphere))
-        .add_object(Box::new(pink_sphere))
-        .add_object(Box::new(orange_sphere))
-        .add_object(Box::new(yellow_sphere))
-        .add_light_source(Box::new(point_light_source));
-
-    add_walls(&mut scene);
-
-    let camera = Camera::new(
-        Vector3::new(25., 55., 20.),
-        Vector3::new(-PI / 4., 0., 3. * PI / 4.),
-        90. * PI / 180.,
-    );
-
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/indirect_lighting_demo.png");
-}
-
-#[allow(dead_code)]
-fn antialiasing_demo() {
-    let red_sphere = SphereBuilder::new(Vector3::new(20., -40., 0.), 10.)
-        .with_color(Vector3::new(1., 0., 0.))
-        .build();
-    let glass_sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
-        .with_refractive_index(1.5)
-        .build();
-    let mirror_sphere = SphereBuilder::new(Vector3::new(-40., 20., 0.), 10.)
-        .with_mirror(true)
-        .build();
-
-    let white_sphere = SphereBuilder::new(Vector3::new(40., 10., 0.), 10.)
-        .with_color(Vector3::new(1., 1., 1.))
-        .build();
-    let purple_sphere = SphereBuilder::new(Vector3::new(10., 40., 0.), 10.)
-        .with_color(Vector3::new(1., 0., 1.))
-        .build();
-
-    let mut scene = Scene::new();
-
-    let point_light_source = PointLightSource::new(Vector3::new(-30., -30., 40.), 5E9);
-
-    scene
-        .add_object(Box::new(red_sphere))
-        .add_object(Box::new(glass_sphere))
-        .add_object(Box::new(mirror_sphere))
-        .add_object(Box::new(white_sphere))
-        .add_object(Box::new(purple_sphere))
-        .add_light_source(Box::new(point_light_source));
-
-    add_walls(&mut scene);
-
-    let camera = Camera::new(
-        Vector3::new(50., 50., 40.),
-        Vector3::new(-PI / 4., 0., 3. * PI / 4.),
-        90. * PI / 180.,
-    );
-
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/antialiasing_demo.png");
-}
-
-#[allow(dead_code)]
-fn spherical_lights_demo() {
-    let glass_sphere = SphereBuilder::new(Vector3::new(-20., 10., 0.), 10.)
-        .with_refractive_index(1.5)
-        .build();
-    let mirror_sphere = SphereBuilder::new(Vector3::new(20., 10., 0.), 10.)
-        .with_mirror(true)
-        .build();
-    let light_sphere = SphereBuilder::new(Vector3::new(0., -30., 15.), 5.)
-        .with_light_intensity(5E9)
-        .build();
-    let light_sphere2 = SphereBuilder::new(Vector3::new(0., 40., 25.), 5.)
-        .with_light_intensity(5E9)
-        .build();
-
-    let mut scene = Scene::new();
-
-    scene
-        .add_object(Box::new(glass_sphere))
-        .add_object(Box::new(mirror_sphere))
-        .add_object(Box::new(light_sphere.clone()))
-        .add_object(Box::new(light_sphere2.clone()))
-        .add_light_source(Box::new(light_sphere))
-        .add_light_source(Box::new(light_sphere2));
+fn render(description: SceneDescription) -> Result<(), String> {
+    for warning in description.get_warnings() {
+        eprintln!("warning: {}", warning);
+    }
 
-    add_walls(&mut scene);
+    let output = description.get_output().to_string();
+    create_output_directory(&output)?;
 
-    let camera = Camera::new(
-        Vector3::new(50., 50., 40.),
-        Vector3::new(-PI / 4., 0., 3. * PI / 4.),
-        75. * PI / 180.,
-    );
+    let mut image = description.into_image();
 
-    let mut image = Image::new(512, 512, camera, scene);
+    let start_time = Instant::now();
     image.draw();
-    image.save("figures/spherical_lights_demo.png");
-}
-
-#[allow(dead_code)]
-fn meshes_demo() {
-    let cat_mesh = Mesh::from_obj_file("assets/cat/cat.obj");
-
-    let mut builder = MeshObjectBuilder::new(&cat_mesh);
-    builder
-        .with_rotation(Vector3::new(PI / 2., 0., 0.))
-        .with_translation(Vector3::new(-10., 25., -15.))
-        .with_scale(0.6)
-        .with_mirror(true);
-
-    let cat_object = builder.build();
-
-    let mut builder2 = MeshObjectBuilder::new(&cat_mesh);
-    builder2
-        .with_rotation(Vector3::new(-PI / 2., 0., PI))
-        .with_translation(Vector3::new(10., 25., 50.))
-        .with_scale(0.6)
-        .with_refractive_index(1.5);
-
-    let cat_object2 = builder2.build();
-
-    let light_source = PointLightSource::new(Vector3::new(0., 30., 20.), 5E9);
-
-    let mut scene = Scene::new();
-
-    scene
-        .add_object(Box::new(cat_object))
-        .add_object(Box::new(cat_object2))
-        .add_light_source(Box::new(light_source));
-
-    add_walls(&mut scene);
-
-    let camera = Camera::new(
-        Vector3::new(0., 55., 10.),
-        Vector3::new(0., 0., PI),
-        75. * PI / 180.,
+    println!(
+        "Rendered {} in {} ms",
+        output,
+        start_time.elapsed().as_millis()
     );
 
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/meshes_demo.png");
+    image.save(&output).map_err(|err| err.to_string())
 }
 
-#[allow(dead_code)]
-fn mesh_normals_and_texture_mapping_demo() {
-    let cat_obj_file = "assets/cat/cat.obj";
-
-    let cat_mesh = Mesh::from_obj_file(cat_obj_file);
-    let cat_texture = Texture::from_obj_file(cat_obj_file);
-
-    let mut builder = MeshObjectBuilder::new(&cat_mesh);
-    builder
-        .with_rotation(Vector3::new(PI / 2., 0., -PI))
-        .with_translation(Vector3::new(20., 20., -15.))
-        .with_scale(0.6)
-        .with_texture(cat_texture);
-
-    let cat_object = builder.build();
-
-    let intensity = 5E9;
-
-    let light_sphere = SphereBuilder::new(Vector3::new(10., 25., 10.), 5.)
-        .with_light_intensity(intensity)
-        .build();
-
-    let mirror_sphere = SphereBuilder::new(Vector3::new(-25., 20., 5.), 15.)
-        .with_mirror(true)
-        .build();
-
-    let mut scene = Scene::new();
-
-    scene
-        .add_object(Box::new(cat_object))
-        .add_object(Box::new(mirror_sphere))
-        .add_object(Box::new(light_sphere.clone()))
-        .add_light_source(Box::new(light_sphere));
-
-    add_walls(&mut scene);
+fn apply_options(
+    descriptions: &mut [SceneDescription],
+    options: &RenderOptions,
+) -> Result<(), String> {
+    for description in descriptions.iter_mut() {
+        let width = options.width.unwrap_or(description.get_width());
+        let height = options.height.unwrap_or(description.get_height());
+        description.set_size(width, height);
+    }
 
-    let camera = Camera::new(
-        Vector3::new(0., 35., 5.),
-        Vector3::new(0., 0., 7. * PI / 8.),
-        90. * PI / 180.,
-    );
+    if let Some(output) = &options.output {
+        match descriptions {
+            [description] => {
+                description.set_output(output);
+            }
+            _ => return Err("`--output` cannot be used when rendering several frames".to_string()),
+        }
+    }
 
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/mesh_normals_and_texture_mapping_demo.png");
+    Ok(())
 }
 
-#[allow(dead_code)]
-fn custom_demo() {
-    let cacodemon_obj_file = "assets/cacodemon/cacodemon.obj";
-
-    let cacodemon_mesh = Mesh::from_obj_file(cacodemon_obj_file);
-    let cacodemon_texture = Texture::from_obj_file(cacodemon_obj_file);
-
-    let mut builder = MeshObjectBuilder::new(&cacodemon_mesh);
-    builder
-        .with_rotation(Vector3::new(PI / 2., 0., PI / 2.))
-        .with_scale(0.1)
-        .with_texture(cacodemon_texture);
-
-    let cacodemon_object = builder.build();
-
-    let intensity = 5E9;
-
-    let sphere_light = SphereBuilder::new(Vector3::new(-10., 40., 10.), 5.)
-        .with_light_intensity(intensity)
-        .build();
-
-    let mut scene = Scene::new();
-
-    let back_sphere = SphereBuilder::new(Vector3::new(-1000., -1000., 0.), 1400.)
-        .with_color(Vector3::new(0.3, 0.3, 0.3))
-        .build();
+fn run_render<F>(load: F, options: &RenderOptions) -> Result<(), String>
+where
+    F: Fn(&RenderOptions) -> Result<Vec<SceneDescription>, String>,
+{
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|err| format!("could not start {} threads: {}", threads, err))?;
+    }
 
-    let dark_red = Vector3::new(0.5, 0., 0.);
+    let runs = options.benchmark_runs.unwrap_or(1);
+    let mut times: Vec<u128> = Vec::with_capacity(runs as usize);
 
-    let sphere_1 = SphereBuilder::new(Vector3::new(19., -3., 22.), 7.)
-        .with_color(dark_red)
-        .build();
+    for i in 0..runs {
+        if options.benchmark_runs.is_some() {
+            println!("Running iteration {}", i + 1);
+        }
 
-    let mirror_sphere = SphereBuilder::new(Vector3::new(-14., 14., -14.), 6.)
-        .with_mirror(true)
-        .build();
+        let start_time = Instant::now();
 
-    let glass_sphere = SphereBuilder::new(Vector3::new(-16., 16., 16.), 8.)
-        .with_refractive_index(1.5)
-        .build();
+        let mut descriptions = load(options)?;
+        apply_options(&mut descriptions, options)?;
+        for description in descriptions {
+            render(description)?;
+        }
 
-    scene
-        .add_object(Box::new(cacodemon_object))
-        .add_object(Box::new(back_sphere))
-        .add_object(Box::new(sphere_1))
-        .add_object(Box::new(mirror_sphere))
-        .add_object(Box::new(glass_sphere))
-        .add_object(Box::new(sphere_light.clone()))
-        .add_light_source(Box::new(sphere_light));
+        let time = start_time.elapsed().as_millis();
+        times.push(time);
+        if options.benchmark_runs.is_some() {
+            println!("Time: {} ms", time);
+        }
+    }
 
-    let camera = Camera::new(
-        Vector3::new(15., 35., -17.),
-        Vector3::new(PI / 8., 0., 7. * PI / 8.),
-        75. * PI / 180.,
-    );
+    if options.benchmark_runs.is_some() {
+        let average_time = times.iter().sum::<u128>() / runs as u128;
+        println!("Average time: {} ms", average_time);
+    }
 
-    let mut image = Image::new(512, 512, camera, scene);
-    image.draw();
-    image.save("figures/cacodemon_demo.png");
+    Ok(())
 }
 
-#[allow(dead_code)]
-fn spinning_cat() {
-    let cat_mesh = Mesh::from_obj_file("assets/cat/cat.obj");
-
-    for theta in 0..16 {
-        let mut builder = MeshObjectBuilder::new(&cat_mesh);
-
-        builder
-            .with_rotation(Vector3::new(PI / 2., 0., PI / 2. + theta as f64 * PI / 8.))
-            .with_translation(Vector3::new(0., 0., -21.5))
-            .with_scale(0.8)
-            .with_color(Vector3::new(0.71764705882, 0.25490196078, 0.05490196078));
-
-        let cat_object = builder.build();
-
-        let light_source = PointLightSource::new(Vector3::new(0., 55., 0.), 5E9);
-
-        let mut scene = Scene::new();
-
-        scene
-            .add_object(Box::new(cat_object))
-            .add_light_source(Box::new(light_source));
-
-        let camera = Camera::new(
-            Vector3::new(0., 55., 0.),
-            Vector3::new(0., 0., PI),
-            75. * PI / 180.,
+/// Build the BVH of a mesh with each method, and print their quality reports.
+fn run_bvh_report(mesh_file: &str, options: &BVHReportOptions) -> Result<(), String> {
+    let defaults = RenderSettings::default();
+    let leaf_size = options.leaf_size.unwrap_or(defaults.get_bvh_leaf_size());
+    let traversal_cost = options
+        .traversal_cost
+        .unwrap_or(defaults.get_bvh_traversal_cost());
+
+    let mesh = Mesh::from_file(mesh_file).map_err(|err| err.to_string())?;
+    for method in [
+        BVHBuildMethod::Midpoint,
+        BVHBuildMethod::SurfaceAreaHeuristic,
+    ] {
+        let start_time = Instant::now();
+        let bvh =
+            BVHTree::new_from_mesh_with_method(mesh.clone(), method, leaf_size, traversal_cost);
+        println!(
+            "{} builder, built in {} ms:",
+            method.get_name(),
+            start_time.elapsed().as_millis()
         );
+        println!("{}\n", bvh.calculate_quality_report(traversal_cost));
+    }
 
-        let mut image = Image::new(512, 512, camera, scene);
-        image.draw();
+    Ok(())
+}
 
-        image.save(&format!("spinning_cat/cat_{}.png", theta));
+fn run(command: Command) -> Result<(), (u8, String)> {
+    let failure = |err| (EXIT_FAILURE, err);
+
+    match command {
+        Command::Render {
+            scene_file,
+            options,
+        } => run_render(
+            |options| {
+                // The settings of the command line are needed while loading,
+                // when meshes are built
+                SceneDescription::from_file_with_overrides(
+                    &scene_file,
+                    options.preset.unwrap_or_default(),
+                    |settings| options.apply_overrides(settings),
+                )
+                .map(|description| vec![description])
+                .map_err(|err| err.to_string())
+            },
+            &options,
+        )
+        .map_err(failure),
+        Command::Demo { name, options } => {
+            let demo = demos::find_demo(&name).ok_or_else(|| {
+                (
+                    EXIT_USAGE,
+                    format!(
+                        "unknown demo `{}`, see `list-demos` for the available demos",
+                        name
+                    ),
+                )
+            })?;
+            run_render(
+                |options| {
+                    let mut settings = options.preset.unwrap_or_default();
+                    options.apply_overrides(&mut settings);
+                    demo.build(&settings).map_err(|err| err.to_string())
+                },
+                &options,
+            )
+            .map_err(failure)
+        }
+        Command::ListDemos => {
+            for demo in DEMOS {
+                println!("{:<34} {}", demo.get_name(), demo.get_description());
+            }
+            Ok(())
+        }
+        Command::Export { scene_file, output } => {
+            let description =
+                SceneDescription::from_file(&scene_file).map_err(|err| failure(err.to_string()))?;
+            for warning in description.get_warnings() {
+                eprintln!("warning: {}", warning);
+            }
+            create_output_directory(&output).map_err(failure)?;
+            description
+                .get_scene()
+                .export(&output)
+                .map_err(|err| failure(err.to_string()))?;
+            println!("Exported {}", output);
+            Ok(())
+        }
+        Command::BVHReport { mesh_file, options } => {
+            run_bvh_report(&mesh_file, &options).map_err(failure)
+        }
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
     }
 }
 
-fn main() {
-    // benchmark(basic_spheres_demo, 5);
-    // benchmark(mirror_glass_demo, 5);
-    // benchmark(fresnel_demo, 1);
-    // benchmark(indirect_lighting_demo, 1);
-    // benchmark(antialiasing_demo, 1);
-    // benchmark(spherical_lights_demo, 1);
-    // benchmark(meshes_demo, 1);
-    // benchmark(mesh_normals_and_texture_mapping_demo, 1);
-    benchmark(custom_demo, 1);
+fn main() -> ExitCode {
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    match run(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err((code, err)) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(code)
+        }
+    }
 }
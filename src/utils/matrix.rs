@@ -14,6 +14,12 @@ pub struct Matrix {
 }
 
 impl Matrix {
+    pub const IDENTITY: Self = Self::new(
+        Vector3::new(1., 0., 0.),
+        Vector3::new(0., 1., 0.),
+        Vector3::new(0., 0., 1.),
+    );
+
     pub const fn new(a: Vector3, b: Vector3, c: Vector3) -> Self {
         Self { a, b, c }
     }
@@ -30,6 +36,10 @@ impl Matrix {
         &self.c
     }
 
+    pub fn determinant(&self) -> f64 {
+        self.a.dot(&self.b.cross(&self.c))
+    }
+
     pub fn transpose(&self) -> Self {
         Self {
             a: Vector3::new(self.a.x(), self.b.x(), self.c.x()),
@@ -0,0 +1,153 @@
+use {
+    crate::utils::{Matrix, Vector3},
+    std::{fmt, ops::Mul},
+};
+
+/// A 4x4 matrix acting on homogeneous coordinates, stored row by row.
+#[derive(Clone, Copy)]
+pub struct Matrix4 {
+    rows: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub const IDENTITY: Self = Self::new([
+        [1., 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [0., 0., 1., 0.],
+        [0., 0., 0., 1.],
+    ]);
+
+    pub const fn new(rows: [[f64; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    /// The matrix of the affine map `p -> linear * p + translation`.
+    pub fn new_affine(linear: &Matrix, translation: &Vector3) -> Self {
+        let row = |linear_row: &Vector3, translation: f64| {
+            [linear_row.x(), linear_row.y(), linear_row.z(), translation]
+        };
+        Self::new([
+            row(linear.a(), translation.x()),
+            row(linear.b(), translation.y()),
+            row(linear.c(), translation.z()),
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.rows[row][column]
+    }
+
+    pub fn get_rows(&self) -> &[[f64; 4]; 4] {
+        &self.rows
+    }
+
+    /// The upper left 3x3 block, which acts on directions.
+    pub fn calculate_linear(&self) -> Matrix {
+        let row = |i: usize| Vector3::new(self.rows[i][0], self.rows[i][1], self.rows[i][2]);
+        Matrix::new(row(0), row(1), row(2))
+    }
+
+    pub fn calculate_translation(&self) -> Vector3 {
+        Vector3::new(self.rows[0][3], self.rows[1][3], self.rows[2][3])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+        Self::new(rows)
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting. Returns `None` if the
+    /// matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut rows = self.rows;
+        let mut inverse = Self::IDENTITY.rows;
+
+        let largest_entry = rows
+            .iter()
+            .flatten()
+            .fold(0., |max: f64, x| max.max(x.abs()));
+        let epsilon = largest_entry * 1e-12;
+
+        for column in 0..4 {
+            let pivot_row = (column..4)
+                .max_by(|&i, &j| rows[i][column].abs().total_cmp(&rows[j][column].abs()))
+                .unwrap();
+            let pivot_magnitude = rows[pivot_row][column].abs();
+            if pivot_magnitude.is_nan() || pivot_magnitude <= epsilon {
+                return None;
+            }
+            rows.swap(column, pivot_row);
+            inverse.swap(column, pivot_row);
+
+            let pivot = rows[column][column];
+            for j in 0..4 {
+                rows[column][j] /= pivot;
+                inverse[column][j] /= pivot;
+            }
+
+            for i in 0..4 {
+                if i == column {
+                    continue;
+                }
+                let factor = rows[i][column];
+                if factor == 0. {
+                    continue;
+                }
+                for j in 0..4 {
+                    rows[i][j] -= factor * rows[column][j];
+                    inverse[i][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+
+        Some(Self::new(inverse))
+    }
+
+    /// Apply the matrix to a point, whose homogeneous coordinate is 1.
+    pub fn transform_point(&self, point: &Vector3) -> Vector3 {
+        let row = |r: &[f64; 4]| r[0] * point.x() + r[1] * point.y() + r[2] * point.z() + r[3];
+        Vector3::new(row(&self.rows[0]), row(&self.rows[1]), row(&self.rows[2]))
+    }
+
+    /// Apply the matrix to a direction, whose homogeneous coordinate is 0.
+    pub fn transform_direction(&self, direction: &Vector3) -> Vector3 {
+        let row = |r: &[f64; 4]| r[0] * direction.x() + r[1] * direction.y() + r[2] * direction.z();
+        Vector3::new(row(&self.rows[0]), row(&self.rows[1]), row(&self.rows[2]))
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * other.rows[k][j]).sum();
+            }
+        }
+        Self::new(rows)
+    }
+}
+
+impl fmt::Display for Matrix4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Matrix4 {{")?;
+        for row in &self.rows {
+            writeln!(f, "  {} {} {} {}", row[0], row[1], row[2], row[3])?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -1,15 +1,22 @@
 mod constants;
 mod matrix;
+mod matrix4;
+mod polynomial;
+mod quaternion;
 mod random;
+mod transform;
 mod vector3;
 
 pub use {
-    constants::{
-        ANTIALIASING_RAYS, ENABLE_ANTIALIASING, ENABLE_FRESNEL, ENABLE_INDIRECT_LIGHTING,
-        ENABLE_NORMAL_MAPPING, FRESNEL_RAYS, GAMMA_CORRECTION, INDIRECT_LIGHTING_RAYS,
-        MAX_RECURSION_DEPTH, MESH_EPSILON, MIN_BVH_NODE_SIZE, RAY_OFFSET_EPSILON,
-    },
+    constants::{GAMMA_CORRECTION, MESH_EPSILON, RAY_OFFSET_EPSILON},
     matrix::{calculate_rotation_matrix, Matrix},
-    random::{box_muller, random_cos, random_f64},
+    matrix4::Matrix4,
+    polynomial::{solve_cubic, solve_quadratic, solve_quartic},
+    quaternion::Quaternion,
+    random::{
+        box_muller, random_cos, random_f64, random_index, reseed_thread_rng_from_entropy,
+        seed_thread_rng,
+    },
+    transform::Transform,
     vector3::Vector3,
 };
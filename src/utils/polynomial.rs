@@ -0,0 +1,255 @@
+//! Real roots of low degree polynomials, used to intersect rays with analytic
+//! surfaces. Coefficients are given from the highest degree down, and roots
+//! are returned in increasing order.
+
+use std::f64::consts::PI;
+
+/// Leading coefficients smaller than this are treated as zero, and the
+/// polynomial as one of lower degree.
+const DEGENERATE_EPSILON: f64 = 1E-12;
+/// Maximum relative residual accepted for a root of the analytic quartic
+/// solution before falling back to root isolation.
+const QUARTIC_RESIDUAL_EPSILON: f64 = 1E-6;
+const NEWTON_ITERATIONS: usize = 4;
+const BISECTION_ITERATIONS: usize = 100;
+
+/// Real roots of `a t² + b t + c`.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < DEGENERATE_EPSILON {
+        return if b.abs() < DEGENERATE_EPSILON {
+            Vec::new()
+        } else {
+            vec![-c / b]
+        };
+    }
+
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return Vec::new();
+    }
+
+    // Avoid the cancellation of `-b + sqrt(discriminant)` when `b` is large
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0. {
+        return vec![0., 0.];
+    }
+
+    let t1 = q / a;
+    let t2 = c / q;
+    if t1 < t2 {
+        vec![t1, t2]
+    } else {
+        vec![t2, t1]
+    }
+}
+
+/// Real roots of `a t³ + b t² + c t + d`.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < DEGENERATE_EPSILON {
+        return solve_quadratic(b, c, d);
+    }
+
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // Depressed cubic x³ + p x + q, with t = x - b / 3
+    let shift = b / 3.;
+    let p = c - b * b / 3.;
+    let q = 2. * b * b * b / 27. - b * c / 3. + d;
+
+    let discriminant = q * q / 4. + p * p * p / 27.;
+    let mut roots = if discriminant > 0. {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(-q / 2. + sqrt_discriminant).cbrt() + (-q / 2. - sqrt_discriminant).cbrt()]
+    } else if p == 0. {
+        vec![0.]
+    } else {
+        // Three real roots, with the trigonometric method
+        let radius = 2. * (-p / 3.).sqrt();
+        let angle = ((3. * q / (p * radius)).clamp(-1., 1.)).acos() / 3.;
+        (0..3)
+            .map(|k| radius * (angle - 2. * PI * k as f64 / 3.).cos())
+            .collect()
+    };
+
+    for root in roots.iter_mut() {
+        *root = polish_root(&[1., b, c, d], *root - shift);
+    }
+    sort(roots)
+}
+
+/// Real roots of `a t⁴ + b t³ + c t² + d t + e`.
+///
+/// The roots are computed with Ferrari's method, then refined with a few
+/// Newton iterations. If the analytic solution is not accurate enough, which
+/// happens with nearly repeated roots or badly scaled coefficients, the roots
+/// are instead isolated between the extrema of the polynomial and found by
+/// bisection.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < DEGENERATE_EPSILON {
+        return solve_cubic(b, c, d, e);
+    }
+
+    let coefficients = [1., b / a, c / a, d / a, e / a];
+
+    match solve_quartic_ferrari(&coefficients) {
+        Some(roots)
+            if roots.iter().all(|root| {
+                root.is_finite()
+                    && evaluate(&coefficients, *root).abs()
+                        <= QUARTIC_RESIDUAL_EPSILON * evaluate_magnitude(&coefficients, *root)
+            }) =>
+        {
+            roots
+        }
+        _ => solve_by_isolation(&coefficients),
+    }
+}
+
+fn solve_quartic_ferrari(coefficients: &[f64; 5]) -> Option<Vec<f64>> {
+    let [_, b, c, d, e] = *coefficients;
+
+    // Depressed quartic y⁴ + p y² + q y + r, with t = y - b / 4
+    let shift = b / 4.;
+    let b2 = b * b;
+    let p = c - 3. * b2 / 8.;
+    let q = d - b * c / 2. + b2 * b / 8.;
+    let r = e - b * d / 4. + b2 * c / 16. - 3. * b2 * b2 / 256.;
+
+    let mut roots = Vec::with_capacity(4);
+    if q.abs() < DEGENERATE_EPSILON {
+        // Biquadratic equation in y²
+        for z in solve_quadratic(1., p, r) {
+            if z >= 0. {
+                roots.push(z.sqrt());
+                roots.push(-z.sqrt());
+            }
+        }
+    } else {
+        // Any positive root m of the resolvent cubic splits the quartic into
+        // two quadratics: (y² + p / 2 + m)² = 2 m (y - q / (4 m))². The
+        // largest one is the most accurate.
+        let m = *solve_cubic(1., p, p * p / 4. - r, -q * q / 8.)
+            .last()
+            .filter(|m| **m > 0.)?;
+        let sqrt_2m = (2. * m).sqrt();
+        for side in [-1., 1.] {
+            roots.extend(solve_quadratic(
+                1.,
+                -side * sqrt_2m,
+                p / 2. + m + side * q / (2. * sqrt_2m),
+            ));
+        }
+    }
+
+    Some(sort(
+        roots
+            .into_iter()
+            .map(|root| polish_root(coefficients, root - shift))
+            .collect(),
+    ))
+}
+
+/// Find the roots of a monic quartic by bisection, between consecutive
+/// extrema, where the polynomial is monotonic.
+fn solve_by_isolation(coefficients: &[f64; 5]) -> Vec<f64> {
+    let [_, b, c, d, e] = *coefficients;
+
+    // Cauchy's bound on the magnitude of the roots
+    let bound = 1. + [b, c, d, e].iter().fold(0_f64, |max, x| max.max(x.abs()));
+
+    let mut bounds = vec![-bound];
+    bounds.extend(
+        solve_cubic(4., 3. * b, 2. * c, d)
+            .into_iter()
+            .filter(|x| x.abs() < bound),
+    );
+    bounds.push(bound);
+
+    let mut roots: Vec<f64> = Vec::with_capacity(4);
+    for interval in bounds.windows(2) {
+        let (mut low, mut high) = (interval[0], interval[1]);
+        let (low_value, high_value) = (evaluate(coefficients, low), evaluate(coefficients, high));
+
+        if low_value == 0. {
+            roots.push(low);
+            continue;
+        }
+        if low_value.signum() == high_value.signum() {
+            continue;
+        }
+
+        for _ in 0..BISECTION_ITERATIONS {
+            let middle = (low + high) / 2.;
+            if middle == low || middle == high {
+                break;
+            }
+            if evaluate(coefficients, middle).signum() == low_value.signum() {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        roots.push((low + high) / 2.);
+    }
+
+    // Extrema touching zero are double roots, missed by the sign changes
+    for extremum in &bounds[1..bounds.len() - 1] {
+        let is_known = roots
+            .iter()
+            .any(|root| (root - extremum).abs() <= QUARTIC_RESIDUAL_EPSILON * (1. + root.abs()));
+        if !is_known
+            && evaluate(coefficients, *extremum).abs()
+                <= QUARTIC_RESIDUAL_EPSILON * evaluate_magnitude(coefficients, *extremum)
+        {
+            roots.push(*extremum);
+        }
+    }
+
+    sort(roots)
+}
+
+/// Refine a root with Newton's method, keeping the original estimate if an
+/// iteration makes it worse.
+fn polish_root(coefficients: &[f64], root: f64) -> f64 {
+    let mut root = root;
+    let mut value = evaluate(coefficients, root);
+    for _ in 0..NEWTON_ITERATIONS {
+        let derivative = evaluate_derivative(coefficients, root);
+        if derivative == 0. {
+            break;
+        }
+        let next_root = root - value / derivative;
+        let next_value = evaluate(coefficients, next_root);
+        if next_value.is_nan() || next_value.abs() >= value.abs() {
+            break;
+        }
+        root = next_root;
+        value = next_value;
+    }
+    root
+}
+
+fn evaluate(coefficients: &[f64], t: f64) -> f64 {
+    coefficients.iter().fold(0., |value, c| value * t + c)
+}
+
+fn evaluate_derivative(coefficients: &[f64], t: f64) -> f64 {
+    let degree = coefficients.len() - 1;
+    coefficients[..degree]
+        .iter()
+        .enumerate()
+        .fold(0., |value, (i, c)| value * t + (degree - i) as f64 * c)
+}
+
+/// Sum of the magnitudes of the terms of the polynomial, the scale against
+/// which the residual of a root is compared.
+fn evaluate_magnitude(coefficients: &[f64], t: f64) -> f64 {
+    coefficients
+        .iter()
+        .fold(0., |value, c| value * t.abs() + c.abs())
+}
+
+fn sort(mut roots: Vec<f64>) -> Vec<f64> {
+    roots.sort_by(|a, b| a.total_cmp(b));
+    roots
+}
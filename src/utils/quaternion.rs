@@ -0,0 +1,262 @@
+use {
+    crate::utils::{Matrix, Vector3},
+    std::{
+        fmt,
+        ops::{Mul, Neg},
+    },
+};
+
+const SLERP_LINEAR_THRESHOLD: f64 = 0.9995;
+
+/// A rotation, stored as a unit quaternion `w + xi + yj + zk`.
+///
+/// Unlike Euler angles, quaternions compose without gimbal lock and can be
+/// smoothly interpolated with `slerp`. Like matrices, `a * b` applies `b`
+/// first.
+#[derive(Clone, Copy)]
+pub struct Quaternion {
+    w: f64,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Self = Self::new(1., 0., 0., 0.);
+
+    /// A quaternion from its components. It is not normalized, see
+    /// `normalized`, but rotations only depend on its direction.
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Rotation by `angle` radians around `axis`, counterclockwise when the
+    /// axis points towards the viewer.
+    pub fn new_from_axis_angle(axis: Vector3, angle: f64) -> Self {
+        let axis = axis.normalized();
+        let (sin, cos) = (angle / 2.).sin_cos();
+        Self::new(cos, axis.x() * sin, axis.y() * sin, axis.z() * sin)
+    }
+
+    /// Rotation by Euler angles, like `calculate_rotation_matrix`: around the
+    /// X, then Y, then Z axes.
+    pub fn new_from_euler_angles(rotation: Vector3) -> Self {
+        let x_rotation = Self::new_from_axis_angle(Vector3::new(1., 0., 0.), rotation.x());
+        let y_rotation = Self::new_from_axis_angle(Vector3::new(0., 1., 0.), rotation.y());
+        let z_rotation = Self::new_from_axis_angle(Vector3::new(0., 0., 1.), rotation.z());
+        z_rotation * y_rotation * x_rotation
+    }
+
+    /// The rotation of a rotation matrix. The matrix must be orthogonal, with
+    /// a determinant of 1.
+    pub fn new_from_matrix(matrix: &Matrix) -> Self {
+        let (a, b, c) = (matrix.a(), matrix.b(), matrix.c());
+        let trace = a.x() + b.y() + c.z();
+
+        // Divide by the largest of the four possible denominators
+        let quaternion = if trace > 0. {
+            let s = (trace + 1.).sqrt() * 2.;
+            Self::new(
+                s / 4.,
+                (c.y() - b.z()) / s,
+                (a.z() - c.x()) / s,
+                (b.x() - a.y()) / s,
+            )
+        } else if a.x() > b.y() && a.x() > c.z() {
+            let s = (1. + a.x() - b.y() - c.z()).sqrt() * 2.;
+            Self::new(
+                (c.y() - b.z()) / s,
+                s / 4.,
+                (a.y() + b.x()) / s,
+                (a.z() + c.x()) / s,
+            )
+        } else if b.y() > c.z() {
+            let s = (1. + b.y() - a.x() - c.z()).sqrt() * 2.;
+            Self::new(
+                (a.z() - c.x()) / s,
+                (a.y() + b.x()) / s,
+                s / 4.,
+                (b.z() + c.y()) / s,
+            )
+        } else {
+            let s = (1. + c.z() - a.x() - b.y()).sqrt() * 2.;
+            Self::new(
+                (b.x() - a.y()) / s,
+                (a.z() + c.x()) / s,
+                (b.z() + c.y()) / s,
+                s / 4.,
+            )
+        };
+
+        quaternion.normalized()
+    }
+
+    /// The rotation turning a camera towards `forward`, with its top towards
+    /// `up`. Cameras look along the Y axis with the Z axis pointing up, so
+    /// this maps the Y axis to `forward`, and the Z axis to the direction
+    /// closest to `up` that is perpendicular to `forward`. If `up` is
+    /// parallel to `forward`, an arbitrary perpendicular direction is used.
+    pub fn new_look_rotation(forward: Vector3, up: Vector3) -> Self {
+        let forward = forward.normalized();
+        let right = forward.cross(&up);
+        let (right, up) = if right.norm2() > f64::EPSILON * up.norm2() {
+            let right = right.normalized();
+            (right, right.cross(&forward))
+        } else {
+            let (up, right) = forward.calculate_orthonormal_basis();
+            (right, up)
+        };
+
+        // The columns of the matrix are the images of the axes
+        Self::new_from_matrix(&Matrix::new(
+            Vector3::new(right.x(), forward.x(), up.x()),
+            Vector3::new(right.y(), forward.y(), up.y()),
+            Vector3::new(right.z(), forward.z(), up.z()),
+        ))
+    }
+
+    pub fn w(&self) -> f64 {
+        self.w
+    }
+
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    pub fn z(&self) -> f64 {
+        self.z
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(&self) -> Self {
+        let norm = self.norm();
+        Self::new(self.w / norm, self.x / norm, self.y / norm, self.z / norm)
+    }
+
+    /// The inverse rotation, for unit quaternions.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// The rotation axis, and the angle around it in `[0, 2π]`. The axis of
+    /// the identity is arbitrary.
+    pub fn calculate_axis_angle(&self) -> (Vector3, f64) {
+        let quaternion = self.normalized();
+        let axis = Vector3::new(quaternion.x, quaternion.y, quaternion.z);
+        let sin = axis.norm();
+        let angle = 2. * sin.atan2(quaternion.w);
+        if sin == 0. {
+            (Vector3::new(1., 0., 0.), angle)
+        } else {
+            (axis / sin, angle)
+        }
+    }
+
+    pub fn calculate_matrix(&self) -> Matrix {
+        let Self { w, x, y, z } = self.normalized();
+        Matrix::new(
+            Vector3::new(
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+            ),
+            Vector3::new(
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+            ),
+            Vector3::new(
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+            ),
+        )
+    }
+
+    pub fn rotate_vector(&self, vector: &Vector3) -> Vector3 {
+        // v + 2 u × (u × v + w v), with u the vector part
+        let Self { w, x, y, z } = self.normalized();
+        let u = Vector3::new(x, y, z);
+        let t = u.cross(vector) + *vector * w;
+        *vector + 2. * u.cross(&t)
+    }
+
+    /// Spherical linear interpolation: the rotation a fraction `t` of the way
+    /// from this rotation to `other`, along the shortest path and at constant
+    /// angular speed.
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        let mut other = *other;
+        let mut cos = self.dot(&other);
+        // q and -q are the same rotation, take the closest one
+        if cos < 0. {
+            other = -other;
+            cos = -cos;
+        }
+
+        if cos > SLERP_LINEAR_THRESHOLD {
+            // Nearly equal rotations, where the general formula is unstable
+            return Self::new(
+                self.w + t * (other.w - self.w),
+                self.x + t * (other.x - self.x),
+                self.y + t * (other.y - self.y),
+                self.z + t * (other.z - self.z),
+            )
+            .normalized();
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let self_weight = ((1. - t) * angle).sin() / sin;
+        let other_weight = (t * angle).sin() / sin;
+        Self::new(
+            self.w * self_weight + other.w * other_weight,
+            self.x * self_weight + other.x * other_weight,
+            self.y * self_weight + other.y * other_weight,
+            self.z * self_weight + other.z * other_weight,
+        )
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        )
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+impl fmt::Display for Quaternion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {}, {})", self.w, self.x, self.y, self.z)
+    }
+}
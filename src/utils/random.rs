@@ -1,8 +1,31 @@
-use {crate::utils::Vector3, rand::Rng, std::f64::consts::PI};
+use {
+    crate::utils::Vector3,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    std::{cell::RefCell, f64::consts::PI},
+};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseed the random number generator of the current thread, making the
+/// following random draws on this thread reproducible.
+pub fn seed_thread_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Reseed the random number generator of the current thread from the
+/// operating system, undoing a previous `seed_thread_rng`.
+pub fn reseed_thread_rng_from_entropy() {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::from_entropy());
+}
 
 pub fn random_f64() -> f64 {
-    let mut rng = rand::thread_rng();
-    rng.gen::<f64>()
+    RNG.with(|rng| rng.borrow_mut().gen::<f64>())
+}
+
+pub fn random_index(len: usize) -> usize {
+    RNG.with(|rng| rng.borrow_mut().gen_range(0..len))
 }
 
 pub fn box_muller(sd: f64) -> Vector3 {
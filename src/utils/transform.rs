@@ -0,0 +1,170 @@
+use {
+    crate::utils::{calculate_rotation_matrix, Matrix, Matrix4, Quaternion, Vector3},
+    std::ops::Mul,
+};
+
+const NO_TRANSLATION: Vector3 = Vector3::new(0., 0., 0.);
+
+/// An invertible affine transform, stored along with its inverse.
+///
+/// Points are moved by the whole transform, directions only by its linear
+/// part, and normals by the transpose of the inverse of its linear part, so
+/// that they stay perpendicular to transformed surfaces even under
+/// non-uniform scales and shears.
+///
+/// Transforms compose with `then`, or with `*` like matrices: `a * b` applies
+/// `b` first.
+#[derive(Clone, Copy)]
+pub struct Transform {
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        matrix: Matrix4::IDENTITY,
+        inverse: Matrix4::IDENTITY,
+    };
+
+    /// The transform of an affine matrix, or `None` if it is not invertible.
+    /// Only the first three rows of the matrix are used.
+    pub fn new(matrix: Matrix4) -> Option<Self> {
+        let matrix =
+            Matrix4::new_affine(&matrix.calculate_linear(), &matrix.calculate_translation());
+        let inverse = matrix.inverse()?;
+        Some(Self { matrix, inverse })
+    }
+
+    /// The transform `p -> linear * p`, or `None` if the matrix is singular.
+    pub fn new_linear(linear: &Matrix) -> Option<Self> {
+        Self::new(Matrix4::new_affine(linear, &NO_TRANSLATION))
+    }
+
+    pub fn new_translation(translation: Vector3) -> Self {
+        Self {
+            matrix: Matrix4::new_affine(&Matrix::IDENTITY, &translation),
+            inverse: Matrix4::new_affine(&Matrix::IDENTITY, &-translation),
+        }
+    }
+
+    /// Rotation by Euler angles, like `calculate_rotation_matrix`.
+    pub fn new_rotation(rotation: Vector3) -> Self {
+        let rotation_matrix = calculate_rotation_matrix(rotation);
+        Self {
+            matrix: Matrix4::new_affine(&rotation_matrix, &NO_TRANSLATION),
+            inverse: Matrix4::new_affine(&rotation_matrix.transpose(), &NO_TRANSLATION),
+        }
+    }
+
+    pub fn new_quaternion_rotation(rotation: &Quaternion) -> Self {
+        let rotation_matrix = rotation.calculate_matrix();
+        Self {
+            matrix: Matrix4::new_affine(&rotation_matrix, &NO_TRANSLATION),
+            inverse: Matrix4::new_affine(&rotation_matrix.transpose(), &NO_TRANSLATION),
+        }
+    }
+
+    /// Scale by a different factor along each axis. The factors must not be
+    /// zero.
+    pub fn new_scale(scale: Vector3) -> Self {
+        let diagonal = |v: Vector3| {
+            Matrix4::new([
+                [v.x(), 0., 0., 0.],
+                [0., v.y(), 0., 0.],
+                [0., 0., v.z(), 0.],
+                [0., 0., 0., 1.],
+            ])
+        };
+        Self {
+            matrix: diagonal(scale),
+            inverse: diagonal(Vector3::new(1. / scale.x(), 1. / scale.y(), 1. / scale.z())),
+        }
+    }
+
+    pub fn new_uniform_scale(scale: f64) -> Self {
+        Self::new_scale(Vector3::new(scale, scale, scale))
+    }
+
+    /// Shear that adds `factor` times the coordinate along the `from` axis to
+    /// the coordinate along the `axis` axis, with axes numbered from 0 to 2.
+    /// For instance `new_shear(0, 1, 0.5)` maps `(x, y, z)` to
+    /// `(x + 0.5 y, y, z)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the axes are equal or greater than 2.
+    pub fn new_shear(axis: usize, from: usize, factor: f64) -> Self {
+        assert!(
+            axis < 3 && from < 3 && axis != from,
+            "shear axes must be two different axes among 0, 1 and 2"
+        );
+        let shear = |factor: f64| {
+            let mut rows = Matrix4::IDENTITY.get_rows().to_owned();
+            rows[axis][from] = factor;
+            Matrix4::new(rows)
+        };
+        Self {
+            matrix: shear(factor),
+            inverse: shear(-factor),
+        }
+    }
+
+    pub fn get_matrix(&self) -> &Matrix4 {
+        &self.matrix
+    }
+
+    pub fn get_inverse_matrix(&self) -> &Matrix4 {
+        &self.inverse
+    }
+
+    /// The transform undoing this one.
+    pub fn inverse(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    /// The transform applying this one, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            matrix: next.matrix * self.matrix,
+            inverse: self.inverse * next.inverse,
+        }
+    }
+
+    /// The matrix transforming normals: the transpose of the inverse of the
+    /// linear part.
+    pub fn calculate_inverse_transpose(&self) -> Matrix {
+        self.inverse.calculate_linear().transpose()
+    }
+
+    pub fn transform_point(&self, point: &Vector3) -> Vector3 {
+        self.matrix.transform_point(point)
+    }
+
+    /// Transform a direction. Its length changes with the scale of the
+    /// transform.
+    pub fn transform_direction(&self, direction: &Vector3) -> Vector3 {
+        self.matrix.transform_direction(direction)
+    }
+
+    /// Transform a surface normal, and normalize the result.
+    pub fn transform_normal(&self, normal: &Vector3) -> Vector3 {
+        (self.calculate_inverse_transpose() * *normal).normalized()
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        other.then(&self)
+    }
+}
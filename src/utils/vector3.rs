@@ -67,6 +67,20 @@ impl Vector3 {
         normalized
     }
 
+    /// Two unit vectors, orthogonal to each other and to this unit vector,
+    /// completing it into a right-handed basis.
+    pub fn calculate_orthonormal_basis(&self) -> (Self, Self) {
+        // Any axis that is not parallel to the vector will do
+        let reference_axis = if self.x.abs() < 0.9 {
+            Vector3::new(1., 0., 0.)
+        } else {
+            Vector3::new(0., 1., 0.)
+        };
+        let u = reference_axis.cross(self).normalized();
+        let v = self.cross(&u);
+        (u, v)
+    }
+
     pub fn greatest_component(&self) -> usize {
         if self.x > self.y && self.x > self.z {
             0
@@ -1,16 +1,38 @@
-use crate::utils::{calculate_rotation_matrix, Matrix, Vector3};
+use crate::utils::{Matrix, Quaternion, Transform, Vector3};
 
 pub struct Camera {
     position: Vector3,
     rotation_matrix: Matrix,
+    transform: Transform,
     fov: f64,
 }
 
 impl Camera {
     pub fn new(position: Vector3, rotation: Vector3, fov: f64) -> Self {
+        let transform =
+            Transform::new_rotation(rotation).then(&Transform::new_translation(position));
+        Self::new_with_transform(&transform, fov)
+    }
+
+    pub fn new_with_orientation(position: Vector3, orientation: &Quaternion, fov: f64) -> Self {
+        let transform = Transform::new_quaternion_rotation(orientation)
+            .then(&Transform::new_translation(position));
+        Self::new_with_transform(&transform, fov)
+    }
+
+    /// A camera at `position` looking at `target`, with its top towards `up`.
+    pub fn new_looking_at(position: Vector3, target: Vector3, up: Vector3, fov: f64) -> Self {
+        let orientation = Quaternion::new_look_rotation(target - position, up);
+        Self::new_with_orientation(position, &orientation, fov)
+    }
+
+    /// A camera placed by any transform of the default camera, which sits at
+    /// the origin and looks along the y axis with z pointing up.
+    pub fn new_with_transform(transform: &Transform, fov: f64) -> Self {
         Self {
-            position,
-            rotation_matrix: calculate_rotation_matrix(rotation),
+            position: transform.transform_point(&Vector3::new(0., 0., 0.)),
+            rotation_matrix: transform.get_matrix().calculate_linear(),
+            transform: *transform,
             fov,
         }
     }
@@ -19,10 +41,16 @@ impl Camera {
         &self.position
     }
 
+    /// The linear part of the transform of the camera, which is a rotation
+    /// unless the camera was built with `new_with_transform`.
     pub fn get_rotation_matrix(&self) -> &Matrix {
         &self.rotation_matrix
     }
 
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
     pub fn get_fov(&self) -> f64 {
         self.fov
     }
@@ -1,9 +1,9 @@
 use {
     crate::{
-        core::{Intersectable, Ray, Scene},
+        core::{Intersectable, Ray, RenderSettings, Scene},
+        error::{Error, Result},
         utils::{
-            box_muller, Vector3, ANTIALIASING_RAYS, ENABLE_ANTIALIASING, ENABLE_FRESNEL,
-            ENABLE_INDIRECT_LIGHTING, FRESNEL_RAYS, GAMMA_CORRECTION, INDIRECT_LIGHTING_RAYS,
+            box_muller, reseed_thread_rng_from_entropy, seed_thread_rng, Vector3, GAMMA_CORRECTION,
         },
         view::Camera,
     },
@@ -23,10 +23,17 @@ pub struct Image {
     data: Vec<u8>,
     camera: Camera,
     scene: Scene,
+    settings: RenderSettings,
 }
 
 impl Image {
-    pub fn new(width: u32, height: u32, camera: Camera, scene: Scene) -> Self {
+    pub fn new(
+        width: u32,
+        height: u32,
+        camera: Camera,
+        scene: Scene,
+        settings: RenderSettings,
+    ) -> Self {
         let data = vec![0; (width * height * COLOR_CHANNELS) as usize];
         Self {
             width,
@@ -34,8 +41,13 @@ impl Image {
             data,
             camera,
             scene,
+            settings,
         }
     }
+
+    pub fn get_settings(&self) -> &RenderSettings {
+        &self.settings
+    }
 }
 
 impl Image {
@@ -50,6 +62,14 @@ impl Image {
         data.par_chunks_mut((self.width * COLOR_CHANNELS) as usize)
             .enumerate()
             .for_each(|(i, row)| {
+                // Rows are reseeded either way, as the threads may have been
+                // seeded by an earlier render
+                match self.settings.get_seed() {
+                    Some(seed) => {
+                        seed_thread_rng(seed ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+                    }
+                    None => reseed_thread_rng_from_entropy(),
+                }
                 for (j, pixel) in (0..row.len()).step_by(COLOR_CHANNELS as usize).enumerate() {
                     let color = self.calculate_pixel_color(i, j);
                     row[pixel] = color.0;
@@ -62,29 +82,21 @@ impl Image {
     }
 
     fn calculate_pixel_color(&self, i: usize, j: usize) -> (u8, u8, u8) {
-        let ray_paths = if ENABLE_ANTIALIASING {
-            ANTIALIASING_RAYS
-        } else if ENABLE_FRESNEL {
-            FRESNEL_RAYS
-        } else if ENABLE_INDIRECT_LIGHTING {
-            INDIRECT_LIGHTING_RAYS
-        } else {
-            1
-        };
+        let samples_per_pixel = self.settings.get_samples_per_pixel();
 
         let mut color = Vector3::new(0., 0., 0.);
-        for _ in 0..ray_paths {
-            let ray = if ENABLE_ANTIALIASING {
+        for _ in 0..samples_per_pixel {
+            let ray = if self.settings.is_antialiasing_enabled() {
                 self.calculate_random_pixel_ray(i, j)
             } else {
                 self.calculate_pixel_ray(i, j)
             };
 
             if let Some(intersection) = self.scene.intersect(&ray) {
-                color += self.scene.calculate_color(&intersection);
+                color += self.scene.calculate_color(&intersection, &self.settings);
             }
         }
-        color /= ray_paths as f64;
+        color /= samples_per_pixel as f64;
 
         self.gamma_correct_color(color)
     }
@@ -111,8 +123,6 @@ impl Image {
     }
 
     fn calculate_pixel_position(&self, i: usize, j: usize) -> Vector3 {
-        let camera_position = *self.camera.get_position();
-
         let fov_scale = f64::tan(self.camera.get_fov() / 2.);
 
         let x = (j as f64 + 0.5) - (self.width as f64 / 2.);
@@ -121,19 +131,19 @@ impl Image {
 
         let pixel_position = Vector3::new(x, y, z);
 
-        let rotation_matrix = *self.camera.get_rotation_matrix();
-
-        rotation_matrix * pixel_position + camera_position
+        self.camera.get_transform().transform_point(&pixel_position)
     }
 
-    pub fn save(&self, filename: &str) {
-        let file = std::fs::File::create(filename)
-            .unwrap_or_else(|err| panic!("Error creating file {}: {}", filename, err));
+    pub fn save(&self, filename: &str) -> Result<()> {
+        let file = std::fs::File::create(filename).map_err(|err| Error::io(filename, err))?;
 
         let encoder = png::PngEncoder::new(file);
 
         encoder
             .write_image(&self.data, self.width, self.height, ColorType::Rgb8)
-            .unwrap_or_else(|err| panic!("Error writing image to file {}: {}", filename, err));
+            .map_err(|err| Error::ImageEncode {
+                path: filename.to_string(),
+                source: err,
+            })
     }
 }
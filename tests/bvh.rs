@@ -0,0 +1,156 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::{
+        BVHBuildMethod, BVHTree, Intersectable, Mesh, Ray, SphereBuilder, Transform, Vector3,
+    },
+};
+
+const LEAF_SIZE: usize = 4;
+const TRAVERSAL_COST: f64 = 1.;
+
+/// A finely tessellated ellipsoid next to a few coarse spheres, so that
+/// triangle sizes are uneven.
+fn build_mesh() -> Mesh {
+    let mut builder = SphereBuilder::new(Vector3::new(0., 0., 0.), 1.);
+    builder.with_transform(&Transform::new_scale(Vector3::new(3., 1., 1.)));
+    let mut meshes = vec![builder.build().calculate_tessellation(24, 48)];
+    for x in [-4., 4.] {
+        for z in [-2., 2.] {
+            let sphere = SphereBuilder::new(Vector3::new(x, 0., z), 0.5).build();
+            meshes.push(sphere.calculate_tessellation(4, 6));
+        }
+    }
+    Mesh::merge(&meshes)
+}
+
+/// Rays towards the mesh from around it, hitting and missing it, and rays
+/// starting inside its bounding box, where the nearest child of a node is
+/// not always the one holding the closest hit.
+fn build_rays() -> Vec<Ray> {
+    let mut rays = Vec::new();
+    for i in 0..1000 {
+        let angle = i as f64 * 0.37;
+        let origin = Vector3::new(10. * angle.cos(), 10. * angle.sin(), (i % 13) as f64 - 6.);
+        let target = Vector3::new(
+            (i % 11) as f64 - 5.,
+            (i % 3) as f64 * 0.5 - 0.5,
+            (i % 7) as f64 * 0.5 - 1.5,
+        );
+        rays.push(Ray::new(origin, (target - origin).normalized()));
+    }
+    for i in 0..500 {
+        let origin = Vector3::new(
+            (i % 17) as f64 * 0.5 - 4.,
+            (i % 5) as f64 * 0.4 - 0.8,
+            (i % 9) as f64 * 0.5 - 2.,
+        );
+        let angle = i as f64 * 0.61;
+        let direction = Vector3::new(angle.cos(), angle.sin(), ((i % 7) as f64 - 3.) * 0.3);
+        rays.push(Ray::new(origin, direction.normalized()));
+    }
+    rays
+}
+
+fn build_bvh(method: BVHBuildMethod) -> BVHTree {
+    BVHTree::new_from_mesh_with_method(build_mesh(), method, LEAF_SIZE, TRAVERSAL_COST)
+}
+
+#[test]
+fn finds_the_same_hits_with_every_build_method() {
+    let mesh = build_mesh();
+    let midpoint = build_bvh(BVHBuildMethod::Midpoint);
+    let sah = build_bvh(BVHBuildMethod::SurfaceAreaHeuristic);
+
+    let mut hit_count = 0;
+    for ray in build_rays() {
+        let expected = mesh.intersect(&ray);
+        for bvh in [&midpoint, &sah] {
+            match (bvh.intersect(&ray), &expected) {
+                (Some(actual), Some(expected)) => {
+                    assert!((actual.get_distance() - expected.get_distance()).abs() < EPSILON);
+                    assert_close(actual.get_point(), expected.get_point());
+                    assert_close(actual.get_normal(), expected.get_normal());
+                }
+                (None, None) => {}
+                (actual, expected) => panic!(
+                    "expected a hit at {:?}, found {:?}",
+                    expected.as_ref().map(|hit| hit.get_distance()),
+                    actual.map(|hit| hit.get_distance())
+                ),
+            }
+        }
+        hit_count += usize::from(expected.is_some());
+    }
+    assert!(hit_count > 500, "only {} rays hit the mesh", hit_count);
+}
+
+#[test]
+fn finds_the_same_occluders_with_every_build_method() {
+    let mesh = build_mesh();
+    let midpoint = build_bvh(BVHBuildMethod::Midpoint);
+    let sah = build_bvh(BVHBuildMethod::SurfaceAreaHeuristic);
+
+    for ray in build_rays() {
+        let hit_distance = mesh.intersect(&ray).map(|hit| hit.get_distance());
+        let mut max_distances = vec![f64::INFINITY, 0.5, 5.];
+        if let Some(distance) = hit_distance {
+            max_distances.extend([distance - 1E-3, distance + 1E-3]);
+        }
+        for max_distance in max_distances {
+            let expected = mesh.occluded(&ray, max_distance);
+            assert_eq!(
+                expected,
+                hit_distance.is_some_and(|distance| distance < max_distance)
+            );
+            for bvh in [&midpoint, &sah] {
+                assert_eq!(bvh.occluded(&ray, max_distance), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn reports_the_quality_of_trees() {
+    let midpoint = build_bvh(BVHBuildMethod::Midpoint).calculate_quality_report(TRAVERSAL_COST);
+    let sah =
+        build_bvh(BVHBuildMethod::SurfaceAreaHeuristic).calculate_quality_report(TRAVERSAL_COST);
+    let triangle_count = build_mesh().get_triangles().len();
+
+    for report in [&midpoint, &sah] {
+        let histogram = report.get_leaf_size_histogram();
+        assert_eq!(report.get_triangle_count(), triangle_count);
+        assert_eq!(histogram.iter().sum::<usize>(), report.get_leaf_count());
+        assert_eq!(
+            histogram
+                .iter()
+                .enumerate()
+                .map(|(size, count)| size * count)
+                .sum::<usize>(),
+            triangle_count
+        );
+        assert_eq!(histogram[0], 0);
+
+        // A binary tree, at least as deep as a balanced one
+        assert_eq!(report.get_node_count(), 2 * report.get_leaf_count() - 1);
+        assert!(1 << (report.get_depth() - 1) >= report.get_leaf_count());
+
+        // The root is always visited, and every triangle is in a leaf
+        assert!(report.get_sah_cost() >= TRAVERSAL_COST + 1.);
+        assert!(report.get_sah_cost() < triangle_count as f64);
+    }
+    // The midpoint method makes larger leaves when all the triangle centers
+    // are on one side of the midpoint, the SAH never does
+    assert!(
+        sah.get_leaf_size_histogram().len() <= LEAF_SIZE + 1,
+        "{}",
+        sah
+    );
+    assert!(
+        sah.get_sah_cost() < midpoint.get_sah_cost(),
+        "the SAH tree costs {}, the midpoint one {}",
+        sah.get_sah_cost(),
+        midpoint.get_sah_cost()
+    );
+}
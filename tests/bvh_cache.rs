@@ -0,0 +1,92 @@
+mod common;
+
+use {
+    common::{fixture, output_file, EPSILON},
+    raytracing_rust::{BVHTree, Intersectable, Ray, RenderSettings, Vector3},
+    std::{f64::consts::PI, fs},
+};
+
+/// Offset of the node count in the cache header
+const NODE_COUNT_OFFSET: usize = 61;
+
+/// A copy of a fixture in the temporary directory, unique to this test
+/// process, so that its cache file is written there.
+fn copy_fixture(name: &str, copy_name: &str) -> String {
+    let copy = output_file(copy_name);
+    fs::copy(fixture(name), &copy).unwrap();
+    copy
+}
+
+fn assert_hits_octahedron(bvh: &BVHTree) {
+    let ray = Ray::new(Vector3::new(0.1, 0.1, 5.), Vector3::new(0., 0., -1.));
+    let intersection = bvh.intersect(&ray).unwrap();
+    assert!((intersection.get_distance() - 4.2).abs() < EPSILON);
+}
+
+#[test]
+fn ignores_corrupted_caches() {
+    let mut settings = RenderSettings::preview();
+    settings.with_bvh_cache(true);
+
+    for (name, corrupt) in [
+        (
+            "huge_node_count.obj",
+            (|bytes: &mut Vec<u8>| {
+                bytes[NODE_COUNT_OFFSET..NODE_COUNT_OFFSET + 8]
+                    .copy_from_slice(&u64::MAX.to_le_bytes())
+            }) as fn(&mut Vec<u8>),
+        ),
+        ("truncated_cache.obj", |bytes| {
+            bytes.truncate(bytes.len() - 1)
+        }),
+        ("truncated_header.obj", |bytes| {
+            bytes.truncate(NODE_COUNT_OFFSET + 4)
+        }),
+        ("extra_bytes.obj", |bytes| bytes.push(0)),
+    ] {
+        let mesh_file = copy_fixture("octahedron.obj", name);
+        let cache_file = format!("{}.bvh", mesh_file);
+        assert_hits_octahedron(&BVHTree::new_from_file(&mesh_file, &settings).unwrap());
+        let cache = fs::read(&cache_file).unwrap();
+
+        // The cache is used as is
+        assert_hits_octahedron(&BVHTree::new_from_file(&mesh_file, &settings).unwrap());
+
+        // A corrupted cache is rebuilt
+        let mut corrupted_cache = cache.clone();
+        corrupt(&mut corrupted_cache);
+        fs::write(&cache_file, &corrupted_cache).unwrap();
+        assert_hits_octahedron(&BVHTree::new_from_file(&mesh_file, &settings).unwrap());
+        assert_eq!(fs::read(&cache_file).unwrap(), cache, "{}", name);
+
+        fs::remove_file(&mesh_file).unwrap();
+        fs::remove_file(&cache_file).unwrap();
+    }
+}
+
+#[test]
+fn caches_subdivided_meshes() {
+    let mesh_file = copy_fixture("octahedron.obj", "subdivided.obj");
+    let cache_file = format!("{}.bvh", mesh_file);
+    let mut settings = RenderSettings::preview();
+    // Smooth across the edges of the octahedron
+    settings
+        .with_bvh_cache(true)
+        .with_crease_angle(2. * PI / 3.);
+
+    for subdivision_levels in [1, 0, 1, 1] {
+        settings.with_subdivision_levels(subdivision_levels);
+        let bvh = BVHTree::new_from_file(&mesh_file, &settings).unwrap();
+        assert_eq!(
+            bvh.get_mesh().get_triangles().len(),
+            8 << (2 * subdivision_levels)
+        );
+        // Subdivision pulls the faces in
+        let ray = Ray::new(Vector3::new(0.1, 0.1, 5.), Vector3::new(0., 0., -1.));
+        let distance = bvh.intersect(&ray).unwrap().get_distance();
+        assert_eq!((distance - 4.2).abs() < EPSILON, subdivision_levels == 0);
+    }
+
+    fs::remove_file(&mesh_file).unwrap();
+    fs::remove_file(&cache_file).unwrap();
+}
@@ -0,0 +1,39 @@
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_raytracing-rust"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_fails_with(args: &[&str], code: i32, message: &str) {
+    let output = run(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(code), "{}", stderr);
+    assert!(stderr.contains(message), "{}", stderr);
+}
+
+#[test]
+fn lists_the_demos() {
+    let output = run(&["list-demos"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("cacodemon"));
+}
+
+#[test]
+fn exits_with_2_for_invalid_command_lines() {
+    assert_fails_with(&[], 2, "error: missing command");
+    assert_fails_with(&["draw"], 2, "error: unknown command `draw`");
+    assert_fails_with(
+        &["render", "scene.scene", "--spp", "0"],
+        2,
+        "error: `--spp` expects a positive number, found `0`",
+    );
+    assert_fails_with(&["demo", "teapot"], 2, "error: unknown demo `teapot`");
+}
+
+#[test]
+fn exits_with_1_for_scenes_that_cannot_be_loaded() {
+    assert_fails_with(&["render", "missing.scene"], 1, "error: missing.scene");
+}
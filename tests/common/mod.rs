@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests. Each test file uses a part of
+//! them only.
+#![allow(dead_code)]
+
+use {raytracing_rust::Vector3, std::env};
+
+pub const EPSILON: f64 = 1E-6;
+
+pub fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+/// A path in the temporary directory, unique to this test process.
+pub fn output_file(name: &str) -> String {
+    env::temp_dir()
+        .join(format!("raytracing_rust_{}_{}", std::process::id(), name))
+        .display()
+        .to_string()
+}
+
+pub fn assert_close(actual: &Vector3, expected: &Vector3) {
+    assert!(
+        (*actual - *expected).norm() < EPSILON,
+        "expected {}, found {}",
+        expected,
+        actual
+    );
+}
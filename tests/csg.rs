@@ -0,0 +1,150 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::{Csg, Intersectable, Intersection, Ray, SphereBuilder, Vector3},
+};
+
+const RED: Vector3 = Vector3::new(1., 0., 0.);
+const GREEN: Vector3 = Vector3::new(0., 1., 0.);
+
+/// Two spheres of radius 2 along the X axis, overlapping between 0 and 2: a
+/// red one around the origin and a green one around (2, 0, 0).
+fn build_spheres() -> (Box<dyn Intersectable>, Box<dyn Intersectable>) {
+    let left = SphereBuilder::new(Vector3::new(0., 0., 0.), 2.)
+        .with_color(RED)
+        .build();
+    let right = SphereBuilder::new(Vector3::new(2., 0., 0.), 2.)
+        .with_color(GREEN)
+        .build();
+    (Box::new(left), Box::new(right))
+}
+
+/// Check the distance, normal, side and color of each crossing.
+fn assert_crossings(crossings: &[Intersection], expected: &[(f64, Vector3, bool, Vector3)]) {
+    assert_eq!(crossings.len(), expected.len());
+    for (crossing, (distance, normal, exterior, color)) in crossings.iter().zip(expected) {
+        assert!(
+            (crossing.get_distance() - distance).abs() < EPSILON,
+            "expected the distance {}, found {}",
+            distance,
+            crossing.get_distance()
+        );
+        assert_close(crossing.get_normal(), normal);
+        assert_eq!(crossing.is_exterior(), *exterior);
+        assert_close(crossing.get_object().get_color(), color);
+    }
+}
+
+fn build_ray(x: f64) -> Ray {
+    Ray::new(Vector3::new(x, 0., 0.), Vector3::new(1., 0., 0.))
+}
+
+#[test]
+fn merges_the_intervals_of_unions() {
+    let (left, right) = build_spheres();
+    let union = Csg::union(left, right);
+    let (left_normal, right_normal) = (Vector3::new(-1., 0., 0.), Vector3::new(1., 0., 0.));
+
+    assert_crossings(
+        &union.intersect_all(&build_ray(-10.)),
+        &[
+            (8., left_normal, true, RED),
+            (14., right_normal, false, GREEN),
+        ],
+    );
+    assert_eq!(
+        union.intersect(&build_ray(-10.)).unwrap().get_distance(),
+        8.
+    );
+
+    // From inside both spheres
+    assert_crossings(
+        &union.intersect_all(&build_ray(1.)),
+        &[(3., right_normal, false, GREEN)],
+    );
+}
+
+#[test]
+fn keeps_the_overlap_of_intersections() {
+    let (left, right) = build_spheres();
+    let intersection = Csg::intersection(left, right);
+    let (left_normal, right_normal) = (Vector3::new(-1., 0., 0.), Vector3::new(1., 0., 0.));
+
+    assert_crossings(
+        &intersection.intersect_all(&build_ray(-10.)),
+        &[
+            (10., left_normal, true, GREEN),
+            (12., right_normal, false, RED),
+        ],
+    );
+
+    // From inside both spheres
+    assert_crossings(
+        &intersection.intersect_all(&build_ray(1.)),
+        &[(1., right_normal, false, RED)],
+    );
+
+    // Apart, the spheres have no overlap
+    let left = SphereBuilder::new(Vector3::new(0., 0., 0.), 1.).build();
+    let right = SphereBuilder::new(Vector3::new(3., 0., 0.), 1.).build();
+    let apart = Csg::intersection(Box::new(left), Box::new(right));
+    assert!(apart.intersect(&build_ray(-10.)).is_none());
+}
+
+#[test]
+fn flips_the_subtracted_surfaces_of_differences() {
+    let (left, right) = build_spheres();
+    let difference = Csg::difference(left, right);
+    let (left_normal, right_normal) = (Vector3::new(-1., 0., 0.), Vector3::new(1., 0., 0.));
+
+    // The green surface faces into the green sphere
+    assert_crossings(
+        &difference.intersect_all(&build_ray(-10.)),
+        &[
+            (8., left_normal, true, RED),
+            (10., right_normal, false, GREEN),
+        ],
+    );
+
+    // From inside the red sphere only
+    assert_crossings(
+        &difference.intersect_all(&build_ray(-1.)),
+        &[(1., right_normal, false, GREEN)],
+    );
+
+    // From inside both spheres, the ray never enters the difference
+    assert!(difference.intersect_all(&build_ray(1.)).is_empty());
+
+    // Backwards, into the subtracted sphere first
+    let ray = Ray::new(Vector3::new(10., 0., 0.), Vector3::new(-1., 0., 0.));
+    assert_crossings(
+        &difference.intersect_all(&ray),
+        &[
+            (10., right_normal, true, GREEN),
+            (12., left_normal, false, RED),
+        ],
+    );
+}
+
+#[test]
+fn nests_operations() {
+    let (left, right) = build_spheres();
+    let middle = SphereBuilder::new(Vector3::new(1., 0., 0.), 0.5).build();
+    // The lens shared by both spheres, hollowed out in its middle
+    let hollow_lens = Csg::difference(Box::new(Csg::intersection(left, right)), Box::new(middle));
+    let (left_normal, right_normal) = (Vector3::new(-1., 0., 0.), Vector3::new(1., 0., 0.));
+
+    let crossings = hollow_lens.intersect_all(&build_ray(-10.));
+    assert_eq!(crossings.len(), 4);
+    for (crossing, (distance, normal, exterior)) in crossings.iter().zip([
+        (10., left_normal, true),
+        (10.5, right_normal, false),
+        (11.5, left_normal, true),
+        (12., right_normal, false),
+    ]) {
+        assert!((crossing.get_distance() - distance).abs() < EPSILON);
+        assert_close(crossing.get_normal(), &normal);
+        assert_eq!(crossing.is_exterior(), exterior);
+    }
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use {
+    common::{assert_close, fixture, output_file, EPSILON},
+    raytracing_rust::{
+        Error, GltfScene, Intersectable, Object, Ray, RenderSettings, SceneDescription, Transform,
+        Vector3,
+    },
+    std::{fs, sync::Arc},
+};
+
+fn load_scene() -> GltfScene {
+    GltfScene::from_glb_file(&fixture("textured_scene.glb"), &RenderSettings::default()).unwrap()
+}
+
+#[test]
+fn places_meshes_by_their_node_hierarchy() {
+    let objects = load_scene().build_objects();
+
+    // The unused node is not part of the default scene
+    assert_eq!(objects.len(), 3);
+    // Scaled by its node, then moved by its parent
+    assert_close(
+        &objects[0]
+            .get_transform()
+            .transform_point(&Vector3::new(1., 0., 0.)),
+        &Vector3::new(2., 0., -5.),
+    );
+    // Rotated around Y by its node
+    assert_close(
+        &objects[1]
+            .get_transform()
+            .transform_point(&Vector3::new(1., 0., 0.)),
+        &Vector3::new(0., 0., -6.),
+    );
+    // Placed by a column major matrix
+    assert_close(
+        &objects[2]
+            .get_transform()
+            .transform_point(&Vector3::new(0., 0., 0.)),
+        &Vector3::new(10., 0., 0.),
+    );
+    assert!(Arc::ptr_eq(objects[0].get_bvh(), objects[2].get_bvh()));
+}
+
+#[test]
+fn applies_base_color_textures_and_factors() {
+    let objects = load_scene().build_objects();
+
+    // The top left texel of the texture is red, and the bottom right one white
+    for (origin, expected_color) in [
+        (Vector3::new(-1., 1., 0.), Vector3::new(1., 0., 0.)),
+        (Vector3::new(1., -1., 0.), Vector3::new(1., 1., 1.)),
+    ] {
+        let ray = Ray::new(origin, Vector3::new(0., 0., -1.));
+        let intersection = objects[0]
+            .intersect(&ray)
+            .expect("the ray should hit the quad");
+        assert!((intersection.get_distance() - 5.).abs() < EPSILON);
+        assert_close(&objects[0].calculate_color(&intersection), &expected_color);
+    }
+
+    assert!(objects[1].is_opaque());
+    assert_close(objects[1].get_color(), &Vector3::new(0., 0., 1.));
+}
+
+#[test]
+fn converts_the_perspective_camera() {
+    let mut scene = load_scene();
+
+    let camera = scene.calculate_camera(100, 100).unwrap();
+    assert_close(camera.get_position(), &Vector3::new(0., 0., 10.));
+    // Square images have the same horizontal and vertical field of view
+    assert!((camera.get_fov() - 0.8).abs() < EPSILON);
+    let wide_camera = scene.calculate_camera(200, 100).unwrap();
+    assert!((wide_camera.get_fov() - 2. * (2. * 0.4_f64.tan()).atan()).abs() < EPSILON);
+
+    // glTF cameras look along -Z, while default cameras look along Y
+    let forward = *camera.get_rotation_matrix() * Vector3::new(0., 1., 0.);
+    assert_close(&forward, &Vector3::new(0., 0., -1.));
+
+    scene.with_transform(&Transform::new_translation(Vector3::new(1., 2., 3.)));
+    let moved_camera = scene.calculate_camera(100, 100).unwrap();
+    assert_close(moved_camera.get_position(), &Vector3::new(1., 2., 13.));
+}
+
+#[test]
+fn fits_the_camera_of_scene_files_to_the_image_size() {
+    let filename = output_file("gltf_camera.scene");
+    let contents = format!(
+        "[image]\nwidth = 100\nheight = 100\n\n[gltf]\nfile = {}\n",
+        fixture("textured_scene.glb")
+    );
+    fs::write(&filename, contents).unwrap();
+    let mut description = SceneDescription::from_file(&filename).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    assert!((description.get_camera().get_fov() - 0.8).abs() < EPSILON);
+    description.set_size(200, 100);
+    let wide_fov = 2. * (2. * 0.4_f64.tan()).atan();
+    assert!((description.get_camera().get_fov() - wide_fov).abs() < EPSILON);
+}
+
+#[test]
+fn reports_unsupported_features() {
+    let scene = load_scene();
+
+    let warnings = scene.get_warnings();
+    assert_eq!(warnings.len(), 2, "unexpected warnings {:?}", warnings);
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("`KHR_materials_emissive_strength`")));
+    assert!(warnings.iter().any(|warning| warning.contains("`COLOR_0`")));
+}
+
+#[test]
+fn rejects_truncated_files() {
+    match GltfScene::from_glb_file(&fixture("truncated.glb"), &RenderSettings::default()) {
+        Err(Error::Parse(parse_error)) => {
+            assert!(parse_error.get_message().contains("truncated"))
+        }
+        Err(error) => panic!("expected a parse error, found {}", error),
+        Ok(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn rejects_huge_accessors_without_buffer_view() {
+    for (name, expected_message) in [
+        ("huge_empty_positions.glb", "has no buffer view"),
+        (
+            "huge_empty_normals.glb",
+            "4294967295 elements for 3 vertices",
+        ),
+    ] {
+        match GltfScene::from_glb_file(&fixture(name), &RenderSettings::default()) {
+            Err(Error::Parse(parse_error)) => {
+                assert!(
+                    parse_error.get_message().contains(expected_message),
+                    "unexpected error {}",
+                    parse_error
+                )
+            }
+            Err(error) => panic!("expected a parse error, found {}", error),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+}
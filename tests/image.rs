@@ -0,0 +1,53 @@
+use {
+    raytracing_rust::{
+        Camera, Image, PointLightSource, RenderSettings, Scene, SphereBuilder, Vector3,
+    },
+    std::f64::consts::PI,
+};
+
+/// A small render of a sphere, noisy enough that two renders with different
+/// random draws differ.
+fn render(seed: Option<u64>) -> Vec<u8> {
+    let sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 10.)
+        .with_color(Vector3::new(1., 1., 1.))
+        .build();
+    let light = PointLightSource::new(Vector3::new(20., 20., 20.), 5E9);
+    let mut scene = Scene::new();
+    scene
+        .add_object(Box::new(sphere))
+        .add_light_source(Box::new(light));
+    let camera = Camera::new(
+        Vector3::new(0., 55., 0.),
+        Vector3::new(0., 0., PI),
+        20_f64.to_radians(),
+    );
+
+    let mut settings = RenderSettings::preview();
+    settings.with_samples_per_pixel(1);
+    if let Some(seed) = seed {
+        settings.with_seed(seed);
+    }
+    Image::new(16, 16, camera, scene, settings).calculate_pixel_values()
+}
+
+#[test]
+fn seeded_renders_are_reproducible() {
+    assert!(render(Some(7)) == render(Some(7)));
+    assert!(render(Some(7)) != render(Some(8)));
+}
+
+#[test]
+fn unseeded_renders_stay_random_after_seeded_ones() {
+    // One thread, so that it renders every row of both images
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let [first, second] = [(), ()].map(|_| {
+        pool.install(|| {
+            render(Some(7));
+            render(None)
+        })
+    });
+    assert!(first != second);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use {
+    common::{assert_close, fixture, output_file, EPSILON},
+    raytracing_rust::{
+        core::TriangleIndices, Intersectable, Mesh, MeshObjectBuilder, Ray, Scene, SphereBuilder,
+        Transform, Vector3,
+    },
+    std::fs,
+};
+
+/// The position, normal and texture coordinates of each corner of a
+/// triangle.
+fn calculate_corners(mesh: &Mesh, triangle: &TriangleIndices) -> [Vector3; 9] {
+    let (va, vb, vc) = triangle.get_vertex_indices();
+    let (na, nb, nc) = triangle.get_normal_indices();
+    let (ua, ub, uc) = triangle.get_uv_indices();
+    let (vertices, normals, uvs) = (mesh.get_vertices(), mesh.get_normals(), mesh.get_uvs());
+    [
+        vertices[va],
+        vertices[vb],
+        vertices[vc],
+        normals[na],
+        normals[nb],
+        normals[nc],
+        uvs[ua],
+        uvs[ub],
+        uvs[uc],
+    ]
+}
+
+/// Compare the corners of every triangle, whatever the indices their
+/// attributes are stored at.
+fn assert_same_corners(mesh: &Mesh, expected: &Mesh) {
+    assert_eq!(mesh.get_triangles().len(), expected.get_triangles().len());
+    for (triangle, expected_triangle) in mesh.get_triangles().iter().zip(expected.get_triangles()) {
+        let corners = calculate_corners(mesh, triangle);
+        let expected_corners = calculate_corners(expected, expected_triangle);
+        for (corner, expected_corner) in corners.iter().zip(&expected_corners) {
+            assert_close(corner, expected_corner);
+        }
+    }
+}
+
+#[test]
+fn round_trips_obj_files() {
+    let mesh = Mesh::from_obj_file(&fixture("materials.obj")).unwrap();
+    let output = output_file("materials.obj");
+
+    mesh.write_obj_file(&output).unwrap();
+    let written = Mesh::from_obj_file(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert_same_corners(&written, &mesh);
+    assert_eq!(written.get_groups(), mesh.get_groups());
+    assert_eq!(written.get_materials(), mesh.get_materials());
+    let material_indices = |mesh: &Mesh| {
+        mesh.get_triangles()
+            .iter()
+            .map(|triangle| triangle.get_material_index())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(material_indices(&written), material_indices(&mesh));
+    // The library is referenced by its absolute path from another directory
+    assert_eq!(
+        fs::canonicalize(&written.get_material_libraries()[0]).unwrap(),
+        fs::canonicalize(fixture("materials.mtl")).unwrap()
+    );
+}
+
+#[test]
+fn round_trips_ply_files() {
+    let mesh = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+    let output = output_file("quad.ply");
+
+    mesh.write_file(&output).unwrap();
+    let written = Mesh::from_file(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert_same_corners(&written, &mesh);
+    for (triangle, expected_triangle) in written.get_triangles().iter().zip(mesh.get_triangles()) {
+        let (a, b, c) = triangle.get_vertex_indices();
+        let (expected_a, expected_b, expected_c) = expected_triangle.get_vertex_indices();
+        for (index, expected_index) in [(a, expected_a), (b, expected_b), (c, expected_c)] {
+            let color = written.get_vertex_colors()[index];
+            let expected_color = mesh.get_vertex_colors()[expected_index];
+            // Colors are written as bytes
+            assert!((color - expected_color).norm() < 1. / 255.);
+        }
+    }
+}
+
+#[test]
+fn rejects_unknown_output_formats() {
+    let mesh = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+    assert!(mesh.write_file(&output_file("quad.xyz")).is_err());
+}
+
+#[test]
+fn exports_scenes_in_world_space() {
+    let mesh = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+    let mut mesh_builder = MeshObjectBuilder::new(&mesh);
+    mesh_builder
+        .with_scale(2.)
+        .with_translation(Vector3::new(10., 0., 0.));
+    let mut sphere_builder = SphereBuilder::new(Vector3::new(0., 0., 0.), 1.);
+    sphere_builder.with_transform(&Transform::new_scale(Vector3::new(1., 1., 3.)));
+
+    let mut scene = Scene::new();
+    scene
+        .add_object(Box::new(mesh_builder.build()))
+        .add_object(Box::new(sphere_builder.build()));
+    let exported = scene.calculate_mesh();
+
+    assert_eq!(
+        exported.get_groups(),
+        &vec!["object_0_default", "object_1_sphere"]
+    );
+    for (triangle, exported_triangle) in mesh.get_triangles().iter().zip(exported.get_triangles()) {
+        let (a, _, _) = triangle.get_vertex_indices();
+        let (exported_a, _, _) = exported_triangle.get_vertex_indices();
+        assert_close(
+            &exported.get_vertices()[exported_a],
+            &(mesh.get_vertices()[a] * 2. + Vector3::new(10., 0., 0.)),
+        );
+    }
+
+    let sphere_vertices = &exported.get_vertices()[mesh.get_vertices().len()..];
+    let top = sphere_vertices
+        .iter()
+        .map(|vertex| vertex.z())
+        .fold(f64::NEG_INFINITY, f64::max);
+    assert!((top - 3.).abs() < EPSILON);
+    for vertex in sphere_vertices {
+        let unscaled = Vector3::new(vertex.x(), vertex.y(), vertex.z() / 3.);
+        assert!((unscaled.norm() - 1.).abs() < EPSILON);
+    }
+    // The triangles of the sphere face outwards
+    let ray = Ray::new(Vector3::new(0., -10., 0.), Vector3::new(0., 1., 0.));
+    let intersection = exported
+        .intersect(&ray)
+        .expect("the ray should hit the sphere");
+    assert!(intersection.is_exterior());
+}
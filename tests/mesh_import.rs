@@ -0,0 +1,561 @@
+mod common;
+
+use {
+    common::{assert_close, fixture, EPSILON},
+    raytracing_rust::{
+        core::TriangleIndices, Error, Intersectable, MaterialLibrary, Mesh, MeshObjectBuilder, Ray,
+        Texture, Vector3,
+    },
+    std::f64::consts::PI,
+};
+
+fn assert_same_mesh(mesh: &Mesh, expected: &Mesh) {
+    assert_eq!(mesh.get_vertices().len(), expected.get_vertices().len());
+    for (vertex, expected_vertex) in mesh.get_vertices().iter().zip(expected.get_vertices()) {
+        assert_close(vertex, expected_vertex);
+    }
+    assert_eq!(mesh.get_triangles().len(), expected.get_triangles().len());
+    for (triangle, expected_triangle) in mesh.get_triangles().iter().zip(expected.get_triangles()) {
+        assert_eq!(
+            triangle.get_vertex_indices(),
+            expected_triangle.get_vertex_indices()
+        );
+    }
+}
+
+#[test]
+fn loads_ascii_ply_with_vertex_attributes() {
+    let mesh = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+
+    assert_eq!(mesh.get_vertices().len(), 4);
+    assert_close(&mesh.get_vertices()[2], &Vector3::new(2., 2., 0.));
+    // The quad is split in two triangles
+    assert_eq!(mesh.get_triangles().len(), 2);
+    for normal in mesh.get_normals() {
+        assert_close(normal, &Vector3::new(0., 0., 1.));
+    }
+    assert_close(&mesh.get_uvs()[1], &Vector3::new(1., 0., 0.));
+    assert_close(&mesh.get_vertex_colors()[0], &Vector3::new(1., 0., 0.));
+    assert_close(&mesh.get_vertex_colors()[3], &Vector3::new(1., 1., 1.));
+    assert_eq!(mesh.get_groups().len(), 1);
+}
+
+#[test]
+fn reports_unsupported_ply_elements_and_properties() {
+    let mesh = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+
+    let warnings = mesh.get_warnings();
+    assert_eq!(warnings.len(), 2, "unexpected warnings {:?}", warnings);
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("`confidence`")));
+    assert!(warnings.iter().any(|warning| warning.contains("`edge`")));
+}
+
+#[test]
+fn loads_binary_ply_in_both_byte_orders() {
+    let ascii = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+
+    for name in ["quad_binary_le.ply", "quad_binary_be.ply"] {
+        let mesh = Mesh::from_ply_file(&fixture(name)).unwrap();
+
+        assert_same_mesh(&mesh, &ascii);
+        for (uv, expected_uv) in mesh.get_uvs().iter().zip(ascii.get_uvs()) {
+            assert_close(uv, expected_uv);
+        }
+        for (color, expected_color) in mesh
+            .get_vertex_colors()
+            .iter()
+            .zip(ascii.get_vertex_colors())
+        {
+            assert_close(color, expected_color);
+        }
+    }
+}
+
+#[test]
+fn rejects_ply_faces_with_out_of_range_indices() {
+    match Mesh::from_ply_file(&fixture("bad_index.ply")) {
+        Err(Error::Parse(parse_error)) => {
+            assert!(parse_error.get_message().contains("out of range"))
+        }
+        Err(error) => panic!("expected a parse error, found {}", error),
+        Ok(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn rejects_ply_faces_with_fractional_indices() {
+    match Mesh::from_ply_file(&fixture("fractional_index.ply")) {
+        Err(Error::Parse(parse_error)) => {
+            assert!(parse_error.get_message().contains("1.5 is not an integer"))
+        }
+        Err(error) => panic!("expected a parse error, found {}", error),
+        Ok(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn loads_ascii_stl_with_merged_vertices() {
+    let mesh = Mesh::from_stl_file(&fixture("cube_ascii.stl")).unwrap();
+
+    assert_eq!(mesh.get_vertices().len(), 8);
+    assert_eq!(mesh.get_triangles().len(), 12);
+    assert_eq!(mesh.get_groups(), &vec!["cube".to_string()]);
+    // The faces of the cube meet at right angles, beyond the default crease
+    // angle, so each corner keeps the normal of its face
+    assert_eq!(mesh.get_normals().len(), 24);
+    for normal in mesh.get_normals() {
+        let axis_component = normal.x().abs() + normal.y().abs() + normal.z().abs();
+        assert!((axis_component - 1.).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn loads_binary_stl_starting_with_solid() {
+    let ascii = Mesh::from_stl_file(&fixture("cube_ascii.stl")).unwrap();
+    let binary = Mesh::from_stl_file(&fixture("cube_binary.stl")).unwrap();
+
+    assert_same_mesh(&binary, &ascii);
+}
+
+#[test]
+fn detects_formats_from_extension_or_contents() {
+    let ply = Mesh::from_file(&fixture("quad_ascii.ply")).unwrap();
+    let detected_ply = Mesh::from_file(&fixture("quad_ply_data")).unwrap();
+    assert_same_mesh(&detected_ply, &ply);
+
+    let stl = Mesh::from_file(&fixture("cube_binary.stl")).unwrap();
+    let detected_stl = Mesh::from_file(&fixture("cube_stl_data")).unwrap();
+    assert_same_mesh(&detected_stl, &stl);
+}
+
+#[test]
+fn intersects_loaded_stl() {
+    let mesh = Mesh::from_stl_file(&fixture("cube_binary.stl")).unwrap();
+    let ray = Ray::new(Vector3::new(0.2, 0.3, 10.), Vector3::new(0., 0., -1.));
+
+    let intersection = mesh.intersect(&ray).expect("the ray should hit the cube");
+
+    assert!((intersection.get_distance() - 9.).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+    assert!(intersection.is_exterior());
+}
+
+#[test]
+fn triangulates_concave_polygons() {
+    let mesh = Mesh::from_obj_file(&fixture("faces.obj")).unwrap();
+
+    // The pentagon is split in three triangles, covering its area once
+    let pentagon = &mesh.get_triangles()[..3];
+    let mut area = 0.;
+    for triangle in pentagon {
+        let (a, b, c) = triangle.get_vertex_indices();
+        let vertices = mesh.get_vertices();
+        let normal = (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a]));
+        // In the winding order of the polygon
+        assert!(normal.z() > 0.);
+        area += normal.norm() / 2.;
+    }
+    assert!((area - 10.).abs() < EPSILON);
+
+    let hits_at = |x: f64, y: f64| {
+        let ray = Ray::new(Vector3::new(x, y, 10.), Vector3::new(0., 0., -1.));
+        mesh.intersect(&ray).is_some()
+    };
+    assert!(hits_at(0.5, 3.));
+    assert!(hits_at(3.5, 3.));
+    // The notch is not filled
+    assert!(!hits_at(2., 3.));
+}
+
+#[test]
+fn reads_every_obj_face_form() {
+    let mesh = Mesh::from_obj_file(&fixture("faces.obj")).unwrap();
+    let uvs = |triangle: &TriangleIndices| {
+        let (a, b, c) = triangle.get_uv_indices();
+        [a, b, c].map(|index| mesh.get_uvs()[index])
+    };
+    let normals = |triangle: &TriangleIndices| {
+        let (a, b, c) = triangle.get_normal_indices();
+        [a, b, c].map(|index| mesh.get_normals()[index])
+    };
+    let file_uvs = [
+        Vector3::new(0., 0., 0.),
+        Vector3::new(1., 0., 0.),
+        Vector3::new(1., 1., 0.),
+    ];
+    let file_normal = Vector3::new(0., 0.6, 0.8);
+    let generated_normal = Vector3::new(0., 0., 1.);
+
+    let forms = &mesh.get_triangles()[3..7];
+    for triangle in forms {
+        assert_eq!(triangle.get_vertex_indices(), (5, 6, 7));
+    }
+
+    // `v`: generated normals, and the corner of the texture
+    for (uv, normal) in uvs(&forms[0]).iter().zip(normals(&forms[0])) {
+        assert_close(uv, &Vector3::new(0., 0., 0.));
+        assert_close(&normal, &generated_normal);
+    }
+    // `v/vt`
+    for ((uv, expected_uv), normal) in uvs(&forms[1]).iter().zip(&file_uvs).zip(normals(&forms[1]))
+    {
+        assert_close(uv, expected_uv);
+        assert_close(&normal, &generated_normal);
+    }
+    // `v//vn`
+    for (uv, normal) in uvs(&forms[2]).iter().zip(normals(&forms[2])) {
+        assert_close(uv, &Vector3::new(0., 0., 0.));
+        assert_close(&normal, &file_normal);
+    }
+    // `v/vt/vn`, with negative indices
+    for ((uv, expected_uv), normal) in uvs(&forms[3]).iter().zip(&file_uvs).zip(normals(&forms[3]))
+    {
+        assert_close(uv, expected_uv);
+        assert_close(&normal, &file_normal);
+    }
+}
+
+#[test]
+fn keeps_the_group_of_each_triangle() {
+    let mesh = Mesh::from_obj_file(&fixture("faces.obj")).unwrap();
+
+    assert_eq!(mesh.get_groups(), &vec!["arrow", "forms"]);
+    let group_indices: Vec<usize> = mesh
+        .get_triangles()
+        .iter()
+        .map(|triangle| triangle.get_group_index())
+        .collect();
+    // Going back to a group appends to it
+    assert_eq!(group_indices, vec![0, 0, 0, 1, 1, 1, 1, 0]);
+
+    let arrow = mesh.extract_group(0);
+    assert_eq!(arrow.get_groups(), &vec!["arrow"]);
+    assert_eq!(arrow.get_triangles().len(), 4);
+    assert_eq!(arrow.get_vertices().len(), 5);
+    assert!(arrow.validate().get_invalid_index_triangles().is_empty());
+
+    let forms = mesh.extract_group(1);
+    assert_eq!(forms.get_groups(), &vec!["forms"]);
+    assert_eq!(forms.get_triangles().len(), 4);
+    assert_eq!(forms.get_vertices().len(), 3);
+    for triangle in forms.get_triangles() {
+        assert_eq!(triangle.get_vertex_indices(), (0, 1, 2));
+        assert_eq!(triangle.get_group_index(), 0);
+    }
+    assert_close(&forms.get_vertices()[0], &Vector3::new(10., 0., 0.));
+}
+
+#[test]
+fn reports_unsupported_obj_directives() {
+    let filename = fixture("faces.obj");
+    let mesh = Mesh::from_obj_file(&filename).unwrap();
+
+    // One warning per directive, at its first line
+    assert_eq!(
+        mesh.get_warnings(),
+        &vec![
+            format!("{}:2: `vp` is not supported, 2 lines ignored", filename),
+            format!("{}:20: `cstype` is not supported, 1 line ignored", filename),
+        ]
+    );
+}
+
+/// The normals of the corners of a triangle.
+fn calculate_corner_normals(mesh: &Mesh, triangle_index: usize) -> [Vector3; 3] {
+    let (a, b, c) = mesh.get_triangle(triangle_index).get_normal_indices();
+    [a, b, c].map(|index| mesh.get_normals()[index])
+}
+
+#[test]
+fn generates_area_weighted_normals() {
+    let mesh = Mesh::from_obj_file_with_crease_angle(&fixture("roofs.obj"), PI).unwrap();
+    let left = Vector3::new(-1., 0., 1.).normalized();
+    let right = Vector3::new(1., 0., 1.).normalized();
+    // The larger right side weighs twice as much as the left side
+    let ridge = Vector3::new(1., 0., 3.).normalized();
+
+    let [a, b, c] = calculate_corner_normals(&mesh, 0);
+    assert_close(&a, &ridge);
+    assert_close(&b, &ridge);
+    assert_close(&c, &left);
+    let [a, b, c] = calculate_corner_normals(&mesh, 1);
+    assert_close(&a, &ridge);
+    assert_close(&b, &right);
+    assert_close(&c, &ridge);
+}
+
+#[test]
+fn generates_flat_normals_across_smoothing_groups() {
+    let mesh = Mesh::from_obj_file_with_crease_angle(&fixture("roofs.obj"), PI).unwrap();
+    let left = Vector3::new(-1., 0., 1.).normalized();
+    let right = Vector3::new(1., 0., 1.).normalized();
+
+    // Different groups, `s off` and `s 0`
+    for roof in 1..4 {
+        for normal in calculate_corner_normals(&mesh, 2 * roof) {
+            assert_close(&normal, &left);
+        }
+        for normal in calculate_corner_normals(&mesh, 2 * roof + 1) {
+            assert_close(&normal, &right);
+        }
+    }
+}
+
+#[test]
+fn generates_normals_within_the_crease_angle() {
+    let left = Vector3::new(-1., 0., 1.).normalized();
+    let ridge = Vector3::new(1., 0., 3.).normalized();
+
+    let smooth = Mesh::from_obj_file_with_crease_angle(&fixture("roofs.obj"), 0.6 * PI).unwrap();
+    assert_close(&calculate_corner_normals(&smooth, 0)[0], &ridge);
+
+    // The sides meet at a right angle, beyond this crease angle
+    let sharp = Mesh::from_obj_file_with_crease_angle(&fixture("roofs.obj"), 0.4 * PI).unwrap();
+    for normal in calculate_corner_normals(&sharp, 0) {
+        assert_close(&normal, &left);
+    }
+    // Corners with the same vertex and normal share it
+    assert_eq!(sharp.get_normals().len(), smooth.get_normals().len() + 2);
+}
+
+#[test]
+fn intersects_obj_without_normals_or_uvs() {
+    let mesh = Mesh::from_obj_file(&fixture("roofs.obj")).unwrap();
+    let ray = Ray::new(Vector3::new(0.5, 0.5, 5.), Vector3::new(0., 0., -1.));
+
+    let intersection = mesh.intersect(&ray).expect("the ray should hit the roof");
+
+    assert!((intersection.get_distance() - 5.5).abs() < EPSILON);
+    assert!(intersection.is_exterior());
+    assert_close(
+        intersection.get_normal(),
+        &Vector3::new(1., 0., 1.).normalized(),
+    );
+    assert_close(intersection.get_mapping_point(), &Vector3::new(0., 0., 0.));
+}
+
+#[test]
+fn keeps_the_usemtl_material_of_each_triangle() {
+    let mesh = Mesh::from_obj_file(&fixture("materials.obj")).unwrap();
+
+    assert_eq!(mesh.get_materials(), &vec!["red paint", "glass", "lamp"]);
+    assert_eq!(
+        mesh.get_material_libraries(),
+        &vec![fixture("materials.mtl")]
+    );
+    let material_indices: Vec<Option<usize>> = mesh
+        .get_triangles()
+        .iter()
+        .map(|triangle| triangle.get_material_index())
+        .collect();
+    assert_eq!(
+        material_indices,
+        vec![
+            None,
+            None,
+            Some(0),
+            Some(0),
+            Some(1),
+            Some(1),
+            Some(2),
+            Some(2)
+        ]
+    );
+}
+
+#[test]
+fn parses_every_mtl_material() {
+    let mesh = Mesh::from_obj_file(&fixture("materials.obj")).unwrap();
+    let library = MaterialLibrary::from_mesh(&mesh).unwrap();
+    assert_eq!(library.get_materials().len(), 3);
+
+    let red = library.get_material("red paint").unwrap();
+    assert_close(red.get_diffuse_color(), &Vector3::new(1., 0., 0.));
+    assert_close(red.get_specular_color(), &Vector3::new(0.5, 0.5, 0.5));
+    assert_eq!(red.get_specular_exponent(), 10.);
+    assert!(!red.is_transparent() && !red.is_mirror() && !red.is_emissive());
+
+    let glass = library.get_material("glass").unwrap();
+    assert_eq!(glass.get_opacity(), 0.75);
+    assert_eq!(glass.get_refractive_index(), 1.5);
+    assert_eq!(glass.get_illumination_model(), 7);
+    assert!(glass.is_transparent());
+    assert!(glass.get_opacity_texture().is_none());
+
+    let lamp = library.get_material("lamp").unwrap();
+    assert_eq!(lamp.get_opacity(), 1.);
+    assert!(lamp.is_emissive());
+
+    let warnings = library.get_warnings();
+    assert_eq!(warnings.len(), 2, "unexpected warnings {:?}", warnings);
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("missing alpha.png")));
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("`Pr` is not supported, 2 lines ignored")));
+}
+
+#[test]
+fn ignores_spectral_and_xyz_colors() {
+    let filename = fixture("color_spaces.mtl");
+    let library = MaterialLibrary::from_mtl_file(&filename).unwrap();
+    assert_eq!(library.get_materials().len(), 2);
+
+    // Ignored colors keep the previous value of the material
+    let spectral = library.get_material("spectral").unwrap();
+    assert_close(spectral.get_diffuse_color(), &Vector3::new(1., 1., 1.));
+    let xyz = library.get_material("xyz").unwrap();
+    assert_close(xyz.get_diffuse_color(), &Vector3::new(0.5, 0.5, 0.5));
+    assert!(!xyz.is_emissive());
+
+    assert_eq!(
+        library.get_warnings(),
+        &vec![
+            format!(
+                "{}:3: `Kd spectral` is not supported, 1 line ignored",
+                filename
+            ),
+            format!(
+                "{}:4: `Ks spectral` is not supported, 1 line ignored",
+                filename
+            ),
+            format!("{}:8: `Kd xyz` is not supported, 1 line ignored", filename),
+            format!("{}:9: `Ke xyz` is not supported, 1 line ignored", filename),
+        ]
+    );
+}
+
+#[test]
+fn loads_the_diffuse_texture_of_material_libraries() {
+    for texture in [
+        Texture::from_mtl_file(&fixture("textured.mtl")).unwrap(),
+        Texture::from_obj_file(&fixture("textured.obj")).unwrap(),
+    ] {
+        // The texture of the second material, despite its options
+        assert_close(
+            &texture.get_color(&Vector3::new(0.25, 0.75, 0.)),
+            &Vector3::new(1., 0., 0.),
+        );
+        assert_close(
+            &texture.get_color(&Vector3::new(0.75, 0.25, 0.)),
+            &Vector3::new(1., 1., 1.),
+        );
+    }
+
+    match Texture::from_mtl_file(&fixture("materials.mtl")) {
+        Err(Error::MissingMaterial { message, .. }) => {
+            assert!(message.contains("missing alpha.png"), "{}", message)
+        }
+        Err(error) => panic!("expected a missing material error, found {}", error),
+        Ok(_) => panic!("expected a missing material error"),
+    }
+}
+
+#[test]
+fn shades_each_face_with_its_material() {
+    let mesh = Mesh::from_obj_file(&fixture("materials.obj")).unwrap();
+    let library = MaterialLibrary::from_mesh(&mesh).unwrap();
+    let mut builder = MeshObjectBuilder::new(&mesh);
+    builder
+        .with_color(Vector3::new(0., 1., 0.))
+        .with_material_library(&library);
+    let object = builder.build();
+
+    let intersect_at = |x: f64| {
+        let ray = Ray::new(Vector3::new(x, 0., 10.), Vector3::new(0., 0., -1.));
+        object.intersect(&ray).expect("the ray should hit the mesh")
+    };
+
+    let red = intersect_at(-2.);
+    assert!(red.get_object().is_opaque());
+    assert_close(&red.calculate_color(), &Vector3::new(1., 0., 0.));
+
+    let glass = intersect_at(0.);
+    assert!(glass.get_object().is_transparent());
+    assert_eq!(glass.get_object().get_refractive_index(), 1.5);
+
+    let lamp = intersect_at(2.);
+    assert_eq!(lamp.get_material_index(), Some(2));
+    assert!(lamp.get_object().is_light_source());
+
+    // Faces before any `usemtl` keep the material of the object
+    let plain = intersect_at(6.);
+    assert_eq!(plain.get_material_index(), None);
+    assert_close(&plain.calculate_color(), &Vector3::new(0., 1., 0.));
+}
+
+#[test]
+fn reports_mesh_problems() {
+    let mesh = Mesh::from_obj_file(&fixture("broken_cube.obj")).unwrap();
+
+    let report = mesh.validate();
+    assert!(!report.is_valid());
+    assert_eq!(report.get_triangle_count(), 15);
+    assert_eq!(report.get_invalid_vertices(), &[10]);
+    assert!(report.get_invalid_index_triangles().is_empty());
+    assert_eq!(report.get_degenerate_triangles(), &[13]);
+    assert_eq!(report.get_duplicate_triangles(), &[12]);
+    // The bottom front edge is shared by the flat triangle
+    assert!(report.get_non_manifold_edges().contains(&(0, 1)));
+    // The bottom is wound the other way from its sides
+    assert!(report.get_inconsistent_winding_edges().contains(&(0, 3)));
+    // The top is not connected to the sides through the duplicated vertex
+    assert!(report.get_boundary_edge_count() > 0);
+}
+
+#[test]
+fn repairs_mesh_problems() {
+    let mut mesh = Mesh::from_obj_file(&fixture("broken_cube.obj")).unwrap();
+
+    mesh.repair();
+    let report = mesh.validate();
+    assert!(
+        report.is_valid(),
+        "the repaired mesh has problems:\n{}",
+        report
+    );
+    assert_eq!(report.get_boundary_edge_count(), 0);
+    assert_eq!(mesh.get_vertices().len(), 8);
+    assert_eq!(mesh.get_triangles().len(), 12);
+
+    // Every face is seen from the outside
+    let center = Vector3::new(0.5, 0.5, 0.5);
+    for axis in [
+        Vector3::new(1., 0., 0.),
+        Vector3::new(0., 1., 0.),
+        Vector3::new(0., 0., 1.),
+    ] {
+        for direction in [axis, -axis] {
+            let ray = Ray::new(center + direction * 5., -direction);
+            let intersection = mesh.intersect(&ray).expect("the ray should hit the cube");
+            assert!(intersection.is_exterior());
+            assert!(intersection.get_normal().dot(&direction) > 0.);
+        }
+    }
+}
+
+#[test]
+fn repairs_open_meshes_in_their_majority_orientation() {
+    let mut mesh = Mesh::from_obj_file(&fixture("valley.obj")).unwrap();
+    assert!(!mesh.validate().is_valid());
+
+    mesh.repair();
+    let report = mesh.validate();
+    assert!(
+        report.is_valid(),
+        "the repaired mesh has problems:\n{}",
+        report
+    );
+    assert_eq!(report.get_boundary_edge_count(), 6);
+
+    // Every triangle still faces up, although the valley is concave
+    for (x, y) in [(-0.25, 0.25), (-0.75, 0.75), (0.75, 0.25), (0.25, 0.75)] {
+        let ray = Ray::new(Vector3::new(x, y, 5.), Vector3::new(0., 0., -1.));
+        let intersection = mesh.intersect(&ray).expect("the ray should hit the valley");
+        assert!(intersection.is_exterior());
+        assert!(intersection.get_normal().z() > 0.);
+    }
+}
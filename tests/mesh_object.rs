@@ -0,0 +1,61 @@
+mod common;
+
+use {
+    common::{assert_close, fixture, EPSILON},
+    raytracing_rust::{BVHTree, Intersectable, Mesh, MeshObjectBuilder, Ray, Vector3},
+    std::sync::Arc,
+};
+
+#[test]
+fn shares_the_bvh_of_instances() {
+    let mesh = Mesh::from_obj_file(&fixture("octahedron.obj")).unwrap();
+    let bvh = Arc::new(BVHTree::new_from_mesh(mesh, 1));
+
+    let mut builder = MeshObjectBuilder::new_instance(&bvh);
+    builder
+        .with_scale(2.)
+        .with_translation(Vector3::new(5., 0., 0.))
+        .with_color(Vector3::new(1., 0., 0.));
+    let big = builder.build();
+    let mut builder = MeshObjectBuilder::new_instance(big.get_bvh());
+    builder
+        .with_scale(0.5)
+        .with_translation(Vector3::new(0., 0., -5.))
+        .with_color(Vector3::new(0., 1., 0.));
+    let small = builder.build();
+    assert_eq!(Arc::strong_count(&bvh), 3);
+    assert!(Arc::ptr_eq(big.get_bvh(), small.get_bvh()));
+
+    // Hit points and distances are in world space
+    let down = Vector3::new(0., 0., -1.);
+    let intersection = big
+        .intersect(&Ray::new(Vector3::new(5.2, 0.2, 10.), down))
+        .expect("the ray should hit the big instance");
+    assert!((intersection.get_distance() - 8.4).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(5.2, 0.2, 1.6));
+    assert_close(
+        intersection.get_object().get_color(),
+        &Vector3::new(1., 0., 0.),
+    );
+
+    let intersection = small
+        .intersect(&Ray::new(Vector3::new(0.05, 0.05, 10.), down))
+        .expect("the ray should hit the small instance");
+    assert!((intersection.get_distance() - 14.6).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(0.05, 0.05, -4.6));
+    assert_close(
+        intersection.get_object().get_color(),
+        &Vector3::new(0., 1., 0.),
+    );
+
+    // Each instance is only where its transform puts it
+    assert!(big
+        .intersect(&Ray::new(Vector3::new(0.05, 0.05, 10.), down))
+        .is_none());
+    assert!(small
+        .intersect(&Ray::new(Vector3::new(5.2, 0.2, 10.), down))
+        .is_none());
+
+    drop(big);
+    assert_eq!(Arc::strong_count(&bvh), 2);
+}
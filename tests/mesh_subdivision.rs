@@ -0,0 +1,128 @@
+mod common;
+
+use {
+    common::{fixture, EPSILON},
+    raytracing_rust::{Mesh, MeshObjectBuilder, Vector3},
+    std::f64::consts::PI,
+};
+
+/// Every triangle corner, with its position, normal and texture coordinates.
+fn calculate_corners(mesh: &Mesh) -> Vec<(Vector3, Vector3, Vector3)> {
+    mesh.get_triangles()
+        .iter()
+        .flat_map(|triangle| {
+            let (va, vb, vc) = triangle.get_vertex_indices();
+            let (na, nb, nc) = triangle.get_normal_indices();
+            let (ua, ub, uc) = triangle.get_uv_indices();
+            [(va, na, ua), (vb, nb, ub), (vc, nc, uc)]
+        })
+        .map(|(vertex, normal, uv)| {
+            (
+                mesh.get_vertices()[vertex],
+                mesh.get_normals()[normal],
+                mesh.get_uvs()[uv],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn subdivides_smooth_meshes() {
+    let mut mesh = Mesh::from_obj_file(&fixture("octahedron.obj")).unwrap();
+    mesh.smooth_normals(PI).subdivide(2);
+
+    assert_eq!(mesh.get_triangles().len(), 8 * 16);
+    assert_eq!(mesh.get_vertices().len(), 66);
+    let report = mesh.validate();
+    assert!(report.is_valid(), "{}", report);
+    assert_eq!(report.get_boundary_edge_count(), 0);
+
+    // The tips are pulled in more than the faces, rounding the octahedron,
+    // whose radius varies by a factor of sqrt(3)
+    let radii: Vec<f64> = mesh.get_vertices().iter().map(Vector3::norm).collect();
+    let max_radius = radii.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min_radius = radii.iter().copied().fold(f64::INFINITY, f64::min);
+    assert!(max_radius < 0.9, "max radius {}", max_radius);
+    assert!(
+        max_radius / min_radius < 1.2,
+        "radii from {} to {}",
+        min_radius,
+        max_radius
+    );
+
+    for (vertex, normal, _) in calculate_corners(&mesh) {
+        assert!((normal.norm() - 1.).abs() < EPSILON);
+        assert!(normal.dot(&vertex) > 0., "normal {} at {}", normal, vertex);
+    }
+}
+
+#[test]
+fn keeps_creases_and_corners() {
+    // The faces of an octahedron meet at more than the default crease angle
+    let mut mesh = Mesh::from_obj_file(&fixture("octahedron.obj")).unwrap();
+    mesh.subdivide(2);
+
+    assert_eq!(mesh.get_triangles().len(), 8 * 16);
+    for vertex in mesh.get_vertices() {
+        let distance = vertex.x().abs() + vertex.y().abs() + vertex.z().abs();
+        assert!(
+            (distance - 1.).abs() < EPSILON,
+            "{} is off the surface",
+            vertex
+        );
+    }
+    for tip in [Vector3::new(1., 0., 0.), Vector3::new(0., 0., -1.)] {
+        assert!(mesh
+            .get_vertices()
+            .iter()
+            .any(|vertex| (*vertex - tip).norm() < EPSILON));
+    }
+    // Each face stays flat
+    for (vertex, normal, _) in calculate_corners(&mesh) {
+        let expected = Vector3::new(
+            vertex.x().signum(),
+            vertex.y().signum(),
+            vertex.z().signum(),
+        );
+        if vertex.x() != 0. && vertex.y() != 0. && vertex.z() != 0. {
+            assert!((normal - expected.normalized()).norm() < EPSILON);
+        }
+    }
+}
+
+#[test]
+fn subdivides_boundaries_and_texture_coordinates() {
+    let mut mesh = Mesh::from_ply_file(&fixture("quad_ascii.ply")).unwrap();
+    mesh.subdivide(1);
+
+    assert_eq!(mesh.get_triangles().len(), 8);
+    assert_eq!(mesh.validate().get_boundary_edge_count(), 8);
+    assert_eq!(mesh.get_vertex_colors().len(), mesh.get_vertices().len());
+    for (vertex, normal, uv) in calculate_corners(&mesh) {
+        assert!(vertex.z().abs() < EPSILON);
+        assert!((normal - Vector3::new(0., 0., 1.)).norm() < EPSILON);
+        // The texture coordinates of the quad are half its positions, and
+        // new vertices on its sides stay halfway along them
+        let on_side = [vertex.x(), vertex.y()]
+            .iter()
+            .any(|coordinate| coordinate.abs() < EPSILON || (coordinate - 2.).abs() < EPSILON);
+        if on_side {
+            assert!((uv * 2. - vertex).norm() < EPSILON, "{} at {}", uv, vertex);
+        }
+    }
+    // The corners follow the boundary curve
+    assert!(mesh
+        .get_vertices()
+        .iter()
+        .any(|vertex| (*vertex - Vector3::new(0.25, 0.25, 0.)).norm() < EPSILON));
+}
+
+#[test]
+fn subdivides_mesh_objects_before_building_their_bvh() {
+    let mesh = Mesh::from_obj_file(&fixture("octahedron.obj")).unwrap();
+    let mut builder = MeshObjectBuilder::new(&mesh);
+    builder.with_subdivision(3);
+    let object = builder.build();
+
+    assert_eq!(object.get_bvh().get_mesh().get_triangles().len(), 8 * 64);
+}
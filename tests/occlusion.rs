@@ -0,0 +1,101 @@
+mod common;
+
+use {
+    common::fixture,
+    raytracing_rust::{
+        BVHTree, Intersectable, Mesh, MeshObjectBuilder, PlaneBuilder, Ray, Scene, SphereBuilder,
+        Vector3,
+    },
+    std::sync::Arc,
+};
+
+/// How far from the hit `occluded` is asked about
+const MARGIN: f64 = 1E-3;
+
+/// Whether `occluded` agrees with the distance `intersect` finds along the
+/// ray, and returns that distance.
+fn assert_occludes_at_hit(object: &dyn Intersectable, ray: &Ray) -> f64 {
+    let distance = object
+        .intersect(ray)
+        .expect("the ray should hit the object")
+        .get_distance();
+    assert!(object.occluded(ray, distance + MARGIN));
+    assert!(!object.occluded(ray, distance - MARGIN));
+    distance
+}
+
+fn build_octahedron_bvh() -> Arc<BVHTree> {
+    let mesh = Mesh::from_obj_file(&fixture("octahedron.obj")).unwrap();
+    Arc::new(BVHTree::new_from_mesh(mesh, 1))
+}
+
+fn build_down_ray(x: f64, y: f64, z: f64) -> Ray {
+    Ray::new(Vector3::new(x, y, z), Vector3::new(0., 0., -1.))
+}
+
+#[test]
+fn occludes_with_spheres() {
+    let sphere = SphereBuilder::new(Vector3::new(0., 0., 0.), 1.).build();
+
+    let distance = assert_occludes_at_hit(&sphere, &build_down_ray(0., 0., 5.));
+    assert!((distance - 4.).abs() < MARGIN);
+    // From inside, the far side occludes
+    let distance = assert_occludes_at_hit(&sphere, &build_down_ray(0., 0., 0.5));
+    assert!((distance - 1.5).abs() < MARGIN);
+
+    assert!(!sphere.occluded(&build_down_ray(2., 0., 5.), f64::INFINITY));
+    // Behind the ray
+    assert!(!sphere.occluded(&build_down_ray(0., 0., -5.), f64::INFINITY));
+}
+
+#[test]
+fn occludes_with_mesh_bvhs() {
+    let bvh = build_octahedron_bvh();
+
+    let distance = assert_occludes_at_hit(bvh.as_ref(), &build_down_ray(0.1, 0.1, 5.));
+    assert!((distance - 4.2).abs() < MARGIN);
+    let distance = assert_occludes_at_hit(bvh.as_ref(), &build_down_ray(-0.3, 0.5, 5.));
+    assert!((distance - 4.8).abs() < MARGIN);
+
+    assert!(!bvh.occluded(&build_down_ray(1., 1., 5.), f64::INFINITY));
+}
+
+#[test]
+fn occludes_with_transformed_instances() {
+    let mut builder = MeshObjectBuilder::new_instance(&build_octahedron_bvh());
+    builder
+        .with_scale(2.)
+        .with_translation(Vector3::new(5., 0., 0.));
+    let instance = builder.build();
+
+    // Distances are in world space
+    let distance = assert_occludes_at_hit(&instance, &build_down_ray(5.2, 0.2, 10.));
+    assert!((distance - 8.4).abs() < MARGIN);
+
+    // Where the untransformed octahedron is
+    assert!(!instance.occluded(&build_down_ray(0.1, 0.1, 5.), f64::INFINITY));
+}
+
+#[test]
+fn occludes_with_scenes() {
+    let mut scene = Scene::new();
+    scene
+        .add_object(Box::new(
+            SphereBuilder::new(Vector3::new(0., 0., 0.), 1.).build(),
+        ))
+        .add_object(Box::new(
+            PlaneBuilder::new(Vector3::new(0., 0., -3.), Vector3::new(0., 0., 1.)).build(),
+        ));
+
+    // The sphere, in front of the plane
+    let distance = assert_occludes_at_hit(&scene, &build_down_ray(0., 0., 5.));
+    assert!((distance - 4.).abs() < MARGIN);
+
+    // The plane, beside the sphere
+    let distance = assert_occludes_at_hit(&scene, &build_down_ray(10., 0., 5.));
+    assert!((distance - 8.).abs() < MARGIN);
+
+    // Away from both
+    let up_ray = Ray::new(Vector3::new(0., 0., 5.), Vector3::new(0., 0., 1.));
+    assert!(!scene.occluded(&up_ray, f64::INFINITY));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use {
+    common::{assert_close, fixture, EPSILON},
+    raytracing_rust::{
+        Intersectable, Object, Plane, PlaneBuilder, Ray, SurfaceBuilder, Texture, Vector3,
+    },
+};
+
+/// The plane z = -1, facing up.
+fn build_plane(tile_size: f64) -> Plane {
+    PlaneBuilder::new(Vector3::new(0., 0., -1.), Vector3::new(0., 0., 2.))
+        .with_tile_size(tile_size)
+        .build()
+}
+
+/// Texture coordinates where a ray going down hits the plane.
+fn calculate_mapping_point(plane: &Plane, x: f64, y: f64) -> Vector3 {
+    let ray = Ray::new(Vector3::new(x, y, 5.), Vector3::new(0., 0., -1.));
+    *plane
+        .intersect(&ray)
+        .expect("the ray should hit the plane")
+        .get_mapping_point()
+}
+
+#[test]
+fn hits_planes_from_both_sides() {
+    let plane = build_plane(1.);
+    assert_close(plane.get_normal(), &Vector3::new(0., 0., 1.));
+
+    let ray = Ray::new(Vector3::new(1., 2., 3.), Vector3::new(0., 0.6, -0.8));
+    let intersection = plane.intersect(&ray).expect("the ray should hit the plane");
+    assert!((intersection.get_distance() - 5.).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(1., 5., -1.));
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+    assert!(intersection.is_exterior());
+
+    let ray = Ray::new(Vector3::new(1., 2., -3.), Vector3::new(0., 0., 1.));
+    let intersection = plane.intersect(&ray).expect("the ray should hit the plane");
+    assert!((intersection.get_distance() - 2.).abs() < EPSILON);
+    assert!(!intersection.is_exterior());
+}
+
+#[test]
+fn misses_planes_behind_or_parallel_to_rays() {
+    let plane = build_plane(1.);
+
+    let away = Ray::new(Vector3::new(1., 2., 3.), Vector3::new(0., 0.6, 0.8));
+    assert!(plane.intersect(&away).is_none());
+    assert!(!plane.occluded(&away, f64::INFINITY));
+
+    for height in [3., -1., -3.] {
+        let parallel = Ray::new(Vector3::new(1., 2., height), Vector3::new(0.6, 0.8, 0.));
+        assert!(plane.intersect(&parallel).is_none());
+    }
+}
+
+#[test]
+fn tiles_texture_coordinates() {
+    for tile_size in [1., 2.5] {
+        let plane = build_plane(tile_size);
+        let origin = calculate_mapping_point(&plane, 0.3, -0.4);
+        for coordinate in [origin.x(), origin.y()] {
+            assert!((0. ..1.).contains(&coordinate));
+        }
+
+        // The texture repeats every tile
+        for (x, y) in [(1., 0.), (0., -1.), (3., 2.)] {
+            let mapping_point =
+                calculate_mapping_point(&plane, 0.3 + x * tile_size, -0.4 + y * tile_size);
+            assert_close(&mapping_point, &origin);
+        }
+
+        // A tenth of a tile away is a tenth of the texture away
+        for (x, y) in [(0.1, 0.), (0., 0.1)] {
+            let mapping_point =
+                calculate_mapping_point(&plane, 0.3 + x * tile_size, -0.4 + y * tile_size);
+            assert!(((mapping_point - origin).norm() - 0.1).abs() < EPSILON);
+        }
+    }
+}
+
+#[test]
+fn builds_textured_planes_repeatedly() {
+    let mut builder = PlaneBuilder::new(Vector3::new(0., 0., -1.), Vector3::new(0., 0., 1.));
+    builder
+        .with_texture(Texture::from_img_file(&fixture("checker.png")).unwrap())
+        .with_color(Vector3::new(0., 0., 0.));
+    let planes = [builder.build(), builder.build()];
+
+    // Untextured planes would be black
+    let mut textured = false;
+    for (x, y) in [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+        let ray = Ray::new(Vector3::new(x, y, 5.), Vector3::new(0., 0., -1.));
+        let colors = planes.each_ref().map(|plane| {
+            let intersection = plane.intersect(&ray).expect("the ray should hit the plane");
+            plane.calculate_color(&intersection)
+        });
+        assert_close(&colors[1], &colors[0]);
+        textured |= colors[0].norm() > EPSILON;
+    }
+    assert!(textured);
+}
+
+#[test]
+#[should_panic(expected = "tile size")]
+fn rejects_empty_tiles() {
+    build_plane(0.);
+}
+
+#[test]
+#[should_panic(expected = "normal")]
+fn rejects_zero_normals() {
+    PlaneBuilder::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 0.));
+}
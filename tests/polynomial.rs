@@ -0,0 +1,102 @@
+use raytracing_rust::utils::{solve_cubic, solve_quadratic, solve_quartic};
+
+const EPSILON: f64 = 1E-9;
+
+fn assert_roots(roots: &[f64], expected: &[f64]) {
+    assert_eq!(
+        roots.len(),
+        expected.len(),
+        "expected roots {:?}, found {:?}",
+        expected,
+        roots
+    );
+    for (root, expected_root) in roots.iter().zip(expected) {
+        assert!(
+            (root - expected_root).abs() < EPSILON,
+            "expected roots {:?}, found {:?}",
+            expected,
+            roots
+        );
+    }
+}
+
+#[test]
+fn quadratic_roots() {
+    assert_roots(&solve_quadratic(1., -3., 2.), &[1., 2.]);
+    assert_roots(&solve_quadratic(2., 0., -8.), &[-2., 2.]);
+    assert_roots(&solve_quadratic(1., 0., 1.), &[]);
+    // Degenerate leading coefficient
+    assert_roots(&solve_quadratic(0., 2., -4.), &[2.]);
+}
+
+#[test]
+fn quadratic_roots_without_cancellation() {
+    // Roots 1e-8 and 1e8: the small one is lost with the textbook formula
+    let roots = solve_quadratic(1., -(1E8 + 1E-8), 1.);
+    assert_eq!(roots.len(), 2);
+    assert!((roots[0] - 1E-8).abs() < 1E-20);
+    assert!((roots[1] - 1E8).abs() < 1E-6);
+}
+
+#[test]
+fn cubic_roots() {
+    // (t - 1) (t - 2) (t - 3)
+    assert_roots(&solve_cubic(1., -6., 11., -6.), &[1., 2., 3.]);
+    // (t + 2) (t² + 1)
+    assert_roots(&solve_cubic(1., 2., 1., 2.), &[-2.]);
+    // 2 t³
+    assert_roots(&solve_cubic(2., 0., 0., 0.), &[0.]);
+}
+
+#[test]
+fn quartic_distinct_roots() {
+    // (t - 1) (t - 2) (t - 3) (t - 4)
+    assert_roots(&solve_quartic(1., -10., 35., -50., 24.), &[1., 2., 3., 4.]);
+    // Same roots, with a scaled polynomial
+    assert_roots(
+        &solve_quartic(-3., 30., -105., 150., -72.),
+        &[1., 2., 3., 4.],
+    );
+}
+
+#[test]
+fn quartic_biquadratic_roots() {
+    // (t² - 1) (t² - 4)
+    assert_roots(&solve_quartic(1., 0., -5., 0., 4.), &[-2., -1., 1., 2.]);
+}
+
+#[test]
+fn quartic_without_real_roots() {
+    assert_roots(&solve_quartic(1., 0., 0., 0., 1.), &[]);
+    // (t² + 1) (t² + 2 t + 5)
+    assert_roots(&solve_quartic(1., 2., 6., 2., 5.), &[]);
+}
+
+#[test]
+fn quartic_two_real_roots() {
+    // (t - 1) (t + 3) (t² + 1)
+    assert_roots(&solve_quartic(1., 2., -2., 2., -3.), &[-3., 1.]);
+}
+
+#[test]
+fn quartic_repeated_roots() {
+    // (t - 1)² (t - 2)²
+    let roots = solve_quartic(1., -6., 13., -12., 4.);
+    assert!(!roots.is_empty());
+    for root in &roots {
+        assert!(
+            (root - 1.).abs() < 1E-6 || (root - 2.).abs() < 1E-6,
+            "unexpected root {} in {:?}",
+            root,
+            roots
+        );
+    }
+    assert!(roots.iter().any(|root| (root - 1.).abs() < 1E-6));
+    assert!(roots.iter().any(|root| (root - 2.).abs() < 1E-6));
+}
+
+#[test]
+fn quartic_degenerate_leading_coefficient() {
+    // Actually the cubic (t - 1) (t - 2) (t - 3)
+    assert_roots(&solve_quartic(0., 1., -6., 11., -6.), &[1., 2., 3.]);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::{Intersectable, Intersection, QuadricBuilder, Ray, Vector3},
+};
+
+fn assert_finite(intersection: &Intersection) {
+    for vector in [
+        intersection.get_point(),
+        intersection.get_normal(),
+        intersection.get_mapping_point(),
+    ] {
+        assert!(
+            vector.x().is_finite() && vector.y().is_finite() && vector.z().is_finite(),
+            "{} is not finite",
+            vector
+        );
+    }
+}
+
+#[test]
+fn hits_boxes() {
+    let cube =
+        QuadricBuilder::new_box(Vector3::new(1., 1., 1.), Vector3::new(-1., -1., -1.)).build();
+    let ray = Ray::new(Vector3::new(-5., 0.5, 0.), Vector3::new(1., 0., 0.));
+
+    let intersection = cube.intersect(&ray).expect("the ray should hit the box");
+
+    assert!((intersection.get_distance() - 4.).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(-1., 0., 0.));
+    assert_close(
+        intersection.get_mapping_point(),
+        &Vector3::new(0.75, 0.5, 0.),
+    );
+    assert!(intersection.is_exterior());
+}
+
+#[test]
+fn hits_cylinders() {
+    let cylinder =
+        QuadricBuilder::new_cylinder(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 4.), 1.)
+            .build();
+
+    let side = Ray::new(Vector3::new(-5., 0., 1.), Vector3::new(1., 0., 0.));
+    let intersection = cylinder
+        .intersect(&side)
+        .expect("the ray should hit the side");
+    assert!((intersection.get_distance() - 4.).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(-1., 0., 0.));
+    assert!((intersection.get_mapping_point().y() - 0.25).abs() < EPSILON);
+    assert!(intersection.is_exterior());
+
+    let top = Ray::new(Vector3::new(0.5, 0., 10.), Vector3::new(0., 0., -1.));
+    let intersection = cylinder
+        .intersect(&top)
+        .expect("the ray should hit the top");
+    assert!((intersection.get_distance() - 6.).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+    assert_finite(&intersection);
+}
+
+#[test]
+fn hits_cones() {
+    let cone =
+        QuadricBuilder::new_cone(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 2.), 1.).build();
+
+    // Halfway up, the radius of the cone is 0.5
+    let side = Ray::new(Vector3::new(-5., 0., 1.), Vector3::new(1., 0., 0.));
+    let intersection = cone.intersect(&side).expect("the ray should hit the side");
+    assert!((intersection.get_distance() - 4.5).abs() < EPSILON);
+    assert_close(
+        intersection.get_normal(),
+        &(Vector3::new(-2., 0., 1.) / 5_f64.sqrt()),
+    );
+    assert!((intersection.get_mapping_point().y() - 0.5).abs() < EPSILON);
+    assert!(intersection.is_exterior());
+
+    let base = Ray::new(Vector3::new(0.5, 0., -10.), Vector3::new(0., 0., 1.));
+    let intersection = cone.intersect(&base).expect("the ray should hit the base");
+    assert!((intersection.get_distance() - 10.).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., -1.));
+    assert_finite(&intersection);
+}
+
+#[test]
+fn hits_the_apex_of_cones() {
+    let cone =
+        QuadricBuilder::new_cone(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 2.), 1.).build();
+    let ray = Ray::new(Vector3::new(-5., 0., 7.), Vector3::new(1., 0., -1.));
+
+    let intersection = cone.intersect(&ray).expect("the ray should hit the apex");
+
+    assert_close(intersection.get_point(), &Vector3::new(0., 0., 2.));
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+    assert_finite(&intersection);
+}
+
+#[test]
+fn hits_disks() {
+    let disk =
+        QuadricBuilder::new_disk(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 2.).build();
+
+    let ray = Ray::new(Vector3::new(1., 0., 5.), Vector3::new(0., 0., -1.));
+    let intersection = disk.intersect(&ray).expect("the ray should hit the disk");
+    assert!((intersection.get_distance() - 5.).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+    assert!(intersection.is_exterior());
+    assert_finite(&intersection);
+    let uv = intersection.get_mapping_point();
+    assert!((0. ..1.).contains(&uv.x()) && (0. ..1.).contains(&uv.y()));
+
+    let beside = Ray::new(Vector3::new(3., 0., 5.), Vector3::new(0., 0., -1.));
+    assert!(disk.intersect(&beside).is_none());
+}
+
+#[test]
+fn handles_degenerate_shapes() {
+    let point = Vector3::new(0., 0., 0.);
+    let from_above = Ray::new(Vector3::new(0.5, 0., 5.), Vector3::new(0., 0., -1.));
+    let from_side = Ray::new(Vector3::new(-5., 0., 0.), Vector3::new(1., 0., 0.));
+
+    // Flat cylinders and cones are disks facing the Z axis
+    for quadric in [
+        QuadricBuilder::new_cylinder(point, point, 1.).build(),
+        QuadricBuilder::new_cone(point, point, -1.).build(),
+        QuadricBuilder::new_disk(point, point, 1.).build(),
+    ] {
+        let intersection = quadric
+            .intersect(&from_above)
+            .expect("the ray should hit the flat shape");
+        assert!((intersection.get_distance() - 5.).abs() < EPSILON);
+        assert!(intersection.get_normal().x().abs() < EPSILON);
+        assert_finite(&intersection);
+    }
+
+    let flat_box = QuadricBuilder::new_box(point, point).build();
+    let intersection = flat_box
+        .intersect(&from_side)
+        .expect("the ray should hit the flat box");
+    assert!((intersection.get_distance() - 5.).abs() < EPSILON);
+    assert_finite(&intersection);
+
+    let thin_cylinder = QuadricBuilder::new_cylinder(point, Vector3::new(0., 0., 1.), 0.).build();
+    if let Some(intersection) = thin_cylinder.intersect(&from_side) {
+        assert_finite(&intersection);
+    }
+}
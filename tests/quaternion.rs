@@ -0,0 +1,156 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::utils::{calculate_rotation_matrix, Quaternion, Vector3},
+    std::f64::consts::PI,
+};
+
+/// Whether two quaternions are the same rotation, `q` and `-q` included.
+fn assert_same_rotation(actual: &Quaternion, expected: &Quaternion) {
+    let (actual, mut expected) = (actual.normalized(), expected.normalized());
+    if actual.dot(&expected) < 0. {
+        expected = -expected;
+    }
+    assert!(
+        [
+            actual.w() - expected.w(),
+            actual.x() - expected.x(),
+            actual.y() - expected.y(),
+            actual.z() - expected.z(),
+        ]
+        .iter()
+        .all(|difference| difference.abs() < EPSILON),
+        "expected {}, found {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn rotates_vectors_with_non_unit_quaternions() {
+    let rotation = Quaternion::new_from_axis_angle(Vector3::new(0., 0., 1.), PI / 2.);
+    let scaled = Quaternion::new(
+        rotation.w() * 3.,
+        rotation.x() * 3.,
+        rotation.y() * 3.,
+        rotation.z() * 3.,
+    );
+    let vector = Vector3::new(1., 2., 3.);
+
+    assert_close(&rotation.rotate_vector(&vector), &Vector3::new(-2., 1., 3.));
+    assert_close(&scaled.rotate_vector(&vector), &Vector3::new(-2., 1., 3.));
+    assert_close(
+        &(scaled.calculate_matrix() * vector),
+        &scaled.rotate_vector(&vector),
+    );
+}
+
+#[test]
+fn round_trips_axis_angles() {
+    for (axis, angle) in [
+        (Vector3::new(1., 0., 0.), 0.5),
+        (Vector3::new(0., 1., 1.).normalized(), 2.),
+        (Vector3::new(-1., 2., 3.).normalized(), PI),
+        (Vector3::new(0.2, -0.4, 1.).normalized(), 1.5 * PI),
+    ] {
+        let (actual_axis, actual_angle) =
+            Quaternion::new_from_axis_angle(axis, angle).calculate_axis_angle();
+        assert_close(&actual_axis, &axis);
+        assert!(
+            (actual_angle - angle).abs() < EPSILON,
+            "expected the angle {}, found {}",
+            angle,
+            actual_angle
+        );
+    }
+
+    let (_, angle) = Quaternion::IDENTITY.calculate_axis_angle();
+    assert_eq!(angle, 0.);
+}
+
+#[test]
+fn matches_euler_angles() {
+    let vector = Vector3::new(1., -2., 0.5);
+    for rotation in [
+        Vector3::new(PI / 2., 0., 0.),
+        Vector3::new(0., PI / 2., 0.),
+        Vector3::new(0.3, -1.2, 2.5),
+        Vector3::new(PI, 0.7, -PI / 3.),
+    ] {
+        let quaternion = Quaternion::new_from_euler_angles(rotation);
+        let matrix = calculate_rotation_matrix(rotation);
+        assert_close(&quaternion.rotate_vector(&vector), &(matrix * vector));
+        assert_same_rotation(&Quaternion::new_from_matrix(&matrix), &quaternion);
+    }
+}
+
+#[test]
+fn looks_towards_directions() {
+    let forward = Vector3::new(1., 1., 0.);
+    let rotation = Quaternion::new_look_rotation(forward, Vector3::new(0., 0., 1.));
+    assert_close(
+        &rotation.rotate_vector(&Vector3::new(0., 1., 0.)),
+        &forward.normalized(),
+    );
+    assert_close(
+        &rotation.rotate_vector(&Vector3::new(0., 0., 1.)),
+        &Vector3::new(0., 0., 1.),
+    );
+
+    // The up direction is made perpendicular to the forward direction
+    let forward = Vector3::new(0., 1., -1.);
+    let rotation = Quaternion::new_look_rotation(forward, Vector3::new(0., 0., 1.));
+    assert_close(
+        &rotation.rotate_vector(&Vector3::new(0., 1., 0.)),
+        &forward.normalized(),
+    );
+    assert_close(
+        &rotation.rotate_vector(&Vector3::new(0., 0., 1.)),
+        &Vector3::new(0., 1., 1.).normalized(),
+    );
+
+    // Any perpendicular up direction, when it is parallel to forward
+    let rotation =
+        Quaternion::new_look_rotation(Vector3::new(0., 0., 2.), Vector3::new(0., 0., 1.));
+    let up = rotation.rotate_vector(&Vector3::new(0., 0., 1.));
+    assert_close(
+        &rotation.rotate_vector(&Vector3::new(0., 1., 0.)),
+        &Vector3::new(0., 0., 1.),
+    );
+    assert!(up.z().abs() < EPSILON && (up.norm() - 1.).abs() < EPSILON);
+}
+
+#[test]
+fn interpolates_rotations() {
+    let axis = Vector3::new(0., 0., 1.);
+    let start = Quaternion::new_from_axis_angle(axis, 0.2);
+    let end = Quaternion::new_from_axis_angle(axis, 1.4);
+
+    assert_same_rotation(&start.slerp(&end, 0.), &start);
+    assert_same_rotation(&start.slerp(&end, 1.), &end);
+    // Constant angular speed
+    assert_same_rotation(
+        &start.slerp(&end, 0.25),
+        &Quaternion::new_from_axis_angle(axis, 0.5),
+    );
+
+    // The shortest path, even from the opposite quaternion
+    assert_same_rotation(
+        &(-start).slerp(&end, 0.5),
+        &Quaternion::new_from_axis_angle(axis, 0.8),
+    );
+
+    // Nearly equal rotations
+    let close = Quaternion::new_from_axis_angle(axis, 0.2001);
+    assert_same_rotation(
+        &start.slerp(&close, 0.5),
+        &Quaternion::new_from_axis_angle(axis, 0.20005),
+    );
+
+    let slerp = start.slerp(
+        &Quaternion::new_from_axis_angle(Vector3::new(1., 0., 0.), 2.),
+        0.3,
+    );
+    assert!((slerp.norm() - 1.).abs() < EPSILON);
+}
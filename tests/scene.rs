@@ -0,0 +1,171 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::{
+        Intersectable, Plane, PlaneBuilder, Ray, Scene, Sphere, SphereBuilder, Vector3,
+    },
+};
+
+/// A 10 by 10 by 4 grid of spheres of various sizes, some overlapping.
+fn build_spheres() -> Vec<Sphere> {
+    let mut spheres = Vec::new();
+    for x in 0..10 {
+        for y in 0..10 {
+            for z in 0..4 {
+                let radius = 0.2 + 0.1 * ((x * 7 + y * 3 + z * 5) % 6) as f64;
+                let center = Vector3::new(x as f64, y as f64, 1.5 * z as f64);
+                spheres.push(SphereBuilder::new(center, radius).build());
+            }
+        }
+    }
+    spheres
+}
+
+/// A floor under the grid and a wall beside it.
+fn build_planes() -> [Plane; 2] {
+    [
+        PlaneBuilder::new(Vector3::new(0., 0., -1.), Vector3::new(0., 0., 1.)).build(),
+        PlaneBuilder::new(Vector3::new(0., -2., 0.), Vector3::new(0., 1., 0.)).build(),
+    ]
+}
+
+/// Rays across the grid, from every side.
+fn build_rays() -> Vec<Ray> {
+    let mut rays = Vec::new();
+    for i in 0..40 {
+        let a = i as f64 * 0.25 - 0.3;
+        let b = (i * 7 % 40) as f64 * 0.25 - 0.3;
+        rays.push(Ray::new(
+            Vector3::new(a, b, 20.),
+            Vector3::new(0.01, -0.02, -1.).normalized(),
+        ));
+        rays.push(Ray::new(
+            Vector3::new(-5., a, b * 0.6),
+            Vector3::new(1., 0.1, 0.03).normalized(),
+        ));
+        rays.push(Ray::new(
+            Vector3::new(a, 15., b * 0.6),
+            Vector3::new(-0.05, -1., 0.02).normalized(),
+        ));
+        // From inside the grid
+        rays.push(Ray::new(
+            Vector3::new(4.5, 4.5, 2.),
+            Vector3::new(a - 4.5, b - 4.5, (i % 5) as f64 - 2.).normalized(),
+        ));
+    }
+    rays
+}
+
+/// The distance and normal of the closest hit, testing every object.
+fn intersect_linearly(objects: &[&dyn Intersectable], ray: &Ray) -> Option<(f64, Vector3)> {
+    objects
+        .iter()
+        .filter_map(|object| object.intersect(ray))
+        .map(|intersection| (intersection.get_distance(), *intersection.get_normal()))
+        .min_by(|(first, _), (second, _)| first.total_cmp(second))
+}
+
+fn assert_same_hits(scene: &Scene, objects: &[&dyn Intersectable], rays: &[Ray]) {
+    for ray in rays {
+        let expected = intersect_linearly(objects, ray);
+        let actual = scene.intersect(ray);
+        match (actual, expected) {
+            (Some(actual), Some((distance, normal))) => {
+                assert!((actual.get_distance() - distance).abs() < EPSILON);
+                assert_close(actual.get_normal(), &normal);
+                assert!(scene.occluded(ray, distance + EPSILON));
+                assert!(!scene.occluded(ray, distance - EPSILON));
+            }
+            (None, None) => assert!(!scene.occluded(ray, f64::INFINITY)),
+            (actual, expected) => panic!(
+                "expected a hit at {:?}, found {:?}",
+                expected.map(|(distance, _)| distance),
+                actual.map(|actual| actual.get_distance())
+            ),
+        }
+    }
+}
+
+#[test]
+fn finds_the_closest_of_many_objects() {
+    let spheres = build_spheres();
+    let mut scene = Scene::new();
+    for sphere in &spheres {
+        scene.add_object(Box::new(sphere.clone()));
+    }
+    let objects: Vec<&dyn Intersectable> = spheres
+        .iter()
+        .map(|sphere| sphere as &dyn Intersectable)
+        .collect();
+
+    let rays = build_rays();
+    assert!(
+        rays.iter()
+            .filter(|ray| scene.intersect(ray).is_some())
+            .count()
+            > rays.len() / 2
+    );
+    assert_same_hits(&scene, &objects, &rays);
+}
+
+#[test]
+fn tests_unbounded_objects_with_every_ray() {
+    let spheres = build_spheres();
+    let mut scene = Scene::new();
+    for sphere in &spheres {
+        scene.add_object(Box::new(sphere.clone()));
+    }
+    assert!(scene.calculate_bounding_box().is_some());
+
+    let floor_hit = Ray::new(Vector3::new(50., 50., 5.), Vector3::new(0., 0., -1.));
+    assert!(scene.intersect(&floor_hit).is_none());
+
+    // The scene is rebuilt with the planes, though it has been queried
+    for plane in build_planes() {
+        scene.add_object(Box::new(plane));
+    }
+    assert!(scene.calculate_bounding_box().is_none());
+    let intersection = scene
+        .intersect(&floor_hit)
+        .expect("the ray should hit the floor");
+    assert!((intersection.get_distance() - 6.).abs() < EPSILON);
+
+    let planes = build_planes();
+    let objects: Vec<&dyn Intersectable> = spheres
+        .iter()
+        .map(|sphere| sphere as &dyn Intersectable)
+        .chain(planes.iter().map(|plane| plane as &dyn Intersectable))
+        .collect();
+    let mut rays = build_rays();
+    rays.push(floor_hit);
+    assert_same_hits(&scene, &objects, &rays);
+}
+
+#[test]
+fn rebuilds_after_adding_objects() {
+    let mut scene = Scene::new();
+    let ray = Ray::new(Vector3::new(0., 0., 10.), Vector3::new(0., 0., -1.));
+    assert!(scene.intersect(&ray).is_none());
+    assert!(scene.calculate_bounding_box().is_none());
+
+    scene.add_object(Box::new(
+        SphereBuilder::new(Vector3::new(0., 0., 0.), 1.).build(),
+    ));
+    let intersection = scene
+        .intersect(&ray)
+        .expect("the ray should hit the sphere");
+    assert!((intersection.get_distance() - 9.).abs() < EPSILON);
+
+    // A closer sphere, added after the scene was queried
+    scene.add_object(Box::new(
+        SphereBuilder::new(Vector3::new(0., 0., 5.), 1.).build(),
+    ));
+    let intersection = scene
+        .intersect(&ray)
+        .expect("the ray should hit the sphere");
+    assert!((intersection.get_distance() - 4.).abs() < EPSILON);
+    assert!(scene.occluded(&ray, 4.5));
+    let bounding_box = scene.calculate_bounding_box().unwrap();
+    assert_close(bounding_box.get_max(), &Vector3::new(1., 1., 6.));
+}
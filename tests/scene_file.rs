@@ -0,0 +1,247 @@
+mod common;
+
+use {
+    common::{fixture, output_file},
+    raytracing_rust::{BVHBuildMethod, RenderSettings, Result, SceneDescription},
+    std::fs,
+};
+
+/// Load a scene file with a camera and the given sections.
+fn load_scene_description(name: &str, sections: &str) -> Result<SceneDescription> {
+    let filename = output_file(&format!("{}.scene", name));
+    let contents = format!("[camera]\nposition = 0 -10 0\nfov = 60\n\n{}", sections);
+    fs::write(&filename, contents).unwrap();
+    let result = SceneDescription::from_file(&filename);
+    fs::remove_file(&filename).unwrap();
+    result
+}
+
+/// The error message of loading a scene file, if it fails.
+fn load_scene(name: &str, sections: &str) -> Option<String> {
+    load_scene_description(name, sections)
+        .err()
+        .map(|error| error.to_string())
+}
+
+#[test]
+fn rejects_non_positive_tile_sizes() {
+    let plane = "[plane]\npoint = 0 0 0\nnormal = 0 0 1\n";
+    assert_eq!(
+        load_scene("tile_size", &format!("{}tile_size = 2\n", plane)),
+        None
+    );
+    for tile_size in ["0", "-1"] {
+        let error = load_scene(
+            "tile_size",
+            &format!("{}tile_size = {}\n", plane, tile_size),
+        )
+        .expect("the tile size should be rejected");
+        assert!(error.contains("tile_size"), "{}", error);
+        assert!(error.contains("positive"), "{}", error);
+    }
+}
+
+#[test]
+fn rejects_non_positive_sphere_radii() {
+    for section in [
+        "[sphere]\ncenter = 0 0 0\nradius = 0\n",
+        "[sphere]\ncenter = 0 0 0\nradius = -1\n",
+        "[spherical_light]\ncenter = 0 0 5\nradius = 0\nintensity = 1E6\n",
+    ] {
+        let error = load_scene("radius", section).expect("the radius should be rejected");
+        assert!(error.contains("`radius`"), "{}", error);
+        assert!(error.contains("positive"), "{}", error);
+    }
+    assert_eq!(
+        load_scene("radius", "[sphere]\ncenter = 0 0 0\nradius = 0.5\n"),
+        None
+    );
+}
+
+#[test]
+fn rejects_recursion_depths_out_of_range() {
+    let render = |depth: u64| format!("[render]\nmax_recursion_depth = {}\n", depth);
+    assert_eq!(load_scene("depth", &render(i32::MAX as u64)), None);
+    for depth in [0, i32::MAX as u64 + 1, u32::MAX as u64, u32::MAX as u64 + 1] {
+        let error = load_scene("depth", &render(depth)).expect("the depth should be rejected");
+        assert!(error.contains("`max_recursion_depth`"), "{}", error);
+    }
+}
+
+#[test]
+fn rejects_degenerate_quadrics() {
+    for (section, key) in [
+        "[box]\nmin = 0 0 0\nmax = 1 0 1\n",
+        "[cylinder]\nbase = 0 0 0\ntop = 0 0 0\nradius = 1\n",
+        "[cylinder]\nbase = 0 0 0\ntop = 0 0 1\nradius = 0\n",
+        "[cone]\nbase = 1 2 3\napex = 1 2 3\nradius = 1\n",
+        "[cone]\nbase = 0 0 0\napex = 0 0 1\nradius = -1\n",
+        "[disk]\ncenter = 0 0 0\nnormal = 0 0 0\nradius = 1\n",
+        "[disk]\ncenter = 0 0 0\nnormal = 0 0 1\nradius = 0\n",
+    ]
+    .iter()
+    .zip(["max", "top", "radius", "apex", "radius", "normal", "radius"])
+    {
+        let error = load_scene("quadric", section).expect("the quadric should be rejected");
+        assert!(error.contains(&format!("`{}`", key)), "{}", error);
+    }
+    assert_eq!(
+        load_scene(
+            "quadric",
+            "[cone]\nbase = 0 0 0\napex = 0 0 1\nradius = 1\n"
+        ),
+        None
+    );
+}
+
+#[test]
+fn rejects_degenerate_tori() {
+    for (section, key) in [
+        "[torus]\ncenter = 0 0 0\naxis = 0 0 0\nmajor_radius = 2\nminor_radius = 0.5\n",
+        "[torus]\ncenter = 0 0 0\nmajor_radius = 0\nminor_radius = 0.5\n",
+        "[torus]\ncenter = 0 0 0\nmajor_radius = 2\nminor_radius = -0.5\n",
+    ]
+    .iter()
+    .zip(["axis", "major_radius", "minor_radius"])
+    {
+        let error = load_scene("torus", section).expect("the torus should be rejected");
+        assert!(error.contains(&format!("`{}`", key)), "{}", error);
+    }
+    assert_eq!(
+        load_scene(
+            "torus",
+            "[torus]\ncenter = 0 0 0\naxis = 1 0 0\nmajor_radius = 2\nminor_radius = 0.5\n"
+        ),
+        None
+    );
+}
+
+#[test]
+fn textures_meshes_with_their_material_libraries() {
+    let mesh = |file: &str, texture: &str| {
+        format!("[mesh]\nfile = {}\ntexture = {}\n", fixture(file), texture)
+    };
+    let mtl_file = fixture("textured.mtl");
+
+    assert_eq!(load_scene("texture", &mesh("textured.obj", "mtl")), None);
+    assert_eq!(
+        load_scene("texture", &mesh("octahedron.obj", &mtl_file)),
+        None
+    );
+    let error = load_scene("texture", &mesh("octahedron.obj", "mtl"))
+        .expect("the mesh has no material library");
+    assert!(error.contains("no material library"), "{}", error);
+}
+
+#[test]
+fn subdivides_meshes() {
+    let count_triangles = |sections: &str| {
+        let description = load_scene_description("subdivision", sections).unwrap();
+        description
+            .get_scene()
+            .calculate_mesh()
+            .get_triangles()
+            .len()
+    };
+    let mesh = |subdivision: &str| {
+        format!(
+            "[mesh]\nfile = {}\n{}\n",
+            fixture("octahedron.obj"),
+            subdivision
+        )
+    };
+
+    assert_eq!(
+        count_triangles(&(mesh("subdivision = 1") + &mesh(""))),
+        32 + 8
+    );
+    assert_eq!(
+        count_triangles(&format!("[render]\nsubdivision = 2\n\n{}", mesh(""))),
+        128
+    );
+    let error = load_scene("subdivision", &mesh("subdivision = -1"))
+        .expect("the subdivision should be rejected");
+    assert!(error.contains("`subdivision`"), "{}", error);
+}
+
+#[test]
+fn rejects_zero_sizes_and_counts() {
+    for (section, key) in [
+        "[image]\nwidth = 0\n",
+        "[image]\nheight = 0\n",
+        "[render]\nsamples_per_pixel = 0\n",
+        "[render]\nbvh_leaf_size = 0\n",
+    ]
+    .iter()
+    .zip(["width", "height", "samples_per_pixel", "bvh_leaf_size"])
+    {
+        let error = load_scene("zero", section).expect("zero should be rejected");
+        assert!(error.contains(&format!("`{}`", key)), "{}", error);
+        assert!(error.contains("positive"), "{}", error);
+        assert!(error.contains(".scene:6:"), "{}", error);
+    }
+    assert_eq!(
+        load_scene(
+            "zero",
+            "[image]\nwidth = 1\nheight = 1\n\n[render]\nsamples_per_pixel = 1\nbvh_leaf_size = 1\n"
+        ),
+        None
+    );
+    assert_eq!(
+        load_scene("zero", "[render]\nbvh_traversal_cost = 0\n"),
+        None
+    );
+    let error = load_scene("zero", "[render]\nbvh_traversal_cost = -1\n")
+        .expect("negative costs should be rejected");
+    assert!(error.contains("`bvh_traversal_cost`"), "{}", error);
+}
+
+#[test]
+#[should_panic(expected = "samples per pixel")]
+fn settings_reject_zero_samples() {
+    RenderSettings::default().with_samples_per_pixel(0);
+}
+
+#[test]
+fn overrides_the_render_section_before_building_meshes() {
+    let filename = output_file("overrides.scene");
+    let contents = format!(
+        "[render]\nsamples_per_pixel = 4\nbvh_builder = sah\nsubdivision = 0\n\n\
+         [camera]\nposition = 0 -10 0\nfov = 60\n\n[mesh]\nfile = {}\n",
+        fixture("octahedron.obj")
+    );
+    fs::write(&filename, contents).unwrap();
+    let description = SceneDescription::from_file_with_overrides(
+        &filename,
+        RenderSettings::default(),
+        |settings| {
+            settings
+                .with_bvh_build_method(BVHBuildMethod::Midpoint)
+                .with_subdivision_levels(1);
+        },
+    )
+    .unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    let settings = description.get_settings();
+    assert_eq!(settings.get_samples_per_pixel(), 4);
+    assert_eq!(settings.get_bvh_build_method(), BVHBuildMethod::Midpoint);
+    let mesh = description.get_scene().calculate_mesh();
+    assert_eq!(mesh.get_triangles().len(), 32);
+}
+
+#[test]
+fn rejects_fields_of_view_out_of_range() {
+    let camera = |fov: &str| format!("[camera]\nposition = 0 -10 0\nfov = {}\n", fov);
+    for fov in ["0", "180", "-30", "200", "nan"] {
+        let filename = output_file("fov.scene");
+        fs::write(&filename, camera(fov)).unwrap();
+        let error = SceneDescription::from_file(&filename)
+            .err()
+            .expect("the field of view should be rejected")
+            .to_string();
+        fs::remove_file(&filename).unwrap();
+        assert!(error.contains(".scene:3: `fov`"), "{}", error);
+    }
+    assert_eq!(load_scene("fov", ""), None);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::{Intersectable, Ray, SurfaceBuilder, Torus, TorusBuilder, Vector3},
+};
+
+/// Torus around the Z axis, with a tube of radius 0.5 centered 2 units away
+/// from the origin.
+fn build_torus() -> Torus {
+    TorusBuilder::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 2., 0.5).build()
+}
+
+#[test]
+fn hits_outer_side_from_outside() {
+    let torus = build_torus();
+    let ray = Ray::new(Vector3::new(-10., 0., 0.), Vector3::new(1., 0., 0.));
+
+    let intersection = torus.intersect(&ray).expect("the ray should hit the torus");
+
+    assert!((intersection.get_distance() - 7.5).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(-2.5, 0., 0.));
+    assert_close(intersection.get_normal(), &Vector3::new(-1., 0., 0.));
+    assert!(intersection.is_exterior());
+}
+
+#[test]
+fn hits_inner_side_from_the_hole() {
+    let torus = build_torus();
+    let ray = Ray::new(Vector3::new(0., 0., 0.), Vector3::new(0., 1., 0.));
+
+    let intersection = torus.intersect(&ray).expect("the ray should hit the torus");
+
+    assert!((intersection.get_distance() - 1.5).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(0., 1.5, 0.));
+    assert_close(intersection.get_normal(), &Vector3::new(0., -1., 0.));
+    assert!(intersection.is_exterior());
+}
+
+#[test]
+fn hits_top_of_the_tube() {
+    let torus = build_torus();
+    let ray = Ray::new(Vector3::new(2., 0., 10.), Vector3::new(0., 0., -1.));
+
+    let intersection = torus.intersect(&ray).expect("the ray should hit the torus");
+
+    assert!((intersection.get_distance() - 9.5).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+    assert!(intersection.is_exterior());
+}
+
+#[test]
+fn hits_from_inside_the_tube() {
+    let torus = build_torus();
+    let ray = Ray::new(Vector3::new(2., 0., 0.), Vector3::new(1., 0., 0.));
+
+    let intersection = torus.intersect(&ray).expect("the ray should hit the torus");
+
+    assert!((intersection.get_distance() - 0.5).abs() < EPSILON);
+    assert_close(intersection.get_normal(), &Vector3::new(1., 0., 0.));
+    assert!(!intersection.is_exterior());
+}
+
+#[test]
+fn misses_through_the_hole_and_beside() {
+    let torus = build_torus();
+
+    let through_hole = Ray::new(Vector3::new(0., 0., 10.), Vector3::new(0., 0., -1.));
+    assert!(torus.intersect(&through_hole).is_none());
+
+    let beside = Ray::new(Vector3::new(-10., 0., 1.), Vector3::new(1., 0., 0.));
+    assert!(torus.intersect(&beside).is_none());
+
+    let behind = Ray::new(Vector3::new(-10., 0., 0.), Vector3::new(-1., 0., 0.));
+    assert!(torus.intersect(&behind).is_none());
+}
+
+#[test]
+fn hits_oriented_torus() {
+    let torus =
+        TorusBuilder::new(Vector3::new(1., 2., 3.), Vector3::new(1., 0., 0.), 2., 0.5).build();
+    let ray = Ray::new(Vector3::new(1., 2., 13.), Vector3::new(0., 0., -1.));
+
+    let intersection = torus.intersect(&ray).expect("the ray should hit the torus");
+
+    assert!((intersection.get_distance() - 7.5).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(1., 2., 5.5));
+    assert_close(intersection.get_normal(), &Vector3::new(0., 0., 1.));
+}
+
+#[test]
+fn hits_from_far_away() {
+    let torus = build_torus();
+    let ray = Ray::new(Vector3::new(-1E6, 0., 0.), Vector3::new(1., 0., 0.));
+
+    let intersection = torus.intersect(&ray).expect("the ray should hit the torus");
+
+    assert_close(intersection.get_point(), &Vector3::new(-2.5, 0., 0.));
+}
+
+#[test]
+fn glass_torus_refracts_in_and_out_of_the_tube() {
+    let torus = TorusBuilder::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 2., 0.5)
+        .with_refractive_index(1.5)
+        .build();
+    let ray = Ray::new(
+        Vector3::new(-10., 0., 0.2),
+        Vector3::new(1., 0., -0.02).normalized(),
+    );
+
+    let entry = torus
+        .intersect(&ray)
+        .expect("the ray should enter the tube");
+    assert!(entry.is_exterior());
+
+    let refracted_ray = entry.calculate_refracted_ray();
+    assert!((refracted_ray.get_refractive_index() - 1.5).abs() < EPSILON);
+    // The ray bends towards the inward normal
+    assert!(
+        refracted_ray.get_direction().dot(entry.get_normal())
+            < ray.get_direction().dot(entry.get_normal())
+    );
+
+    let exit = torus
+        .intersect(&refracted_ray)
+        .expect("the refracted ray should leave the tube");
+    assert!(!exit.is_exterior());
+    assert!(exit.get_point().x() > entry.get_point().x());
+
+    let exit_ray = exit.calculate_refracted_ray();
+    assert!((exit_ray.get_refractive_index() - 1.).abs() < EPSILON);
+}
+
+#[test]
+#[should_panic(expected = "axis")]
+fn rejects_zero_axes() {
+    TorusBuilder::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 0.), 2., 0.5);
+}
+
+#[test]
+#[should_panic(expected = "radii")]
+fn rejects_non_positive_radii() {
+    TorusBuilder::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 2., 0.);
+}
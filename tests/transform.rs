@@ -0,0 +1,142 @@
+mod common;
+
+use {
+    common::{assert_close, EPSILON},
+    raytracing_rust::{
+        utils::{Matrix4, Transform, Vector3},
+        Intersectable, Ray, SphereBuilder,
+    },
+};
+
+/// A transform with a bit of everything.
+fn build_transform() -> Transform {
+    Transform::new_shear(0, 2, 0.7)
+        .then(&Transform::new_rotation(Vector3::new(0.3, -1.1, 2.)))
+        .then(&Transform::new_scale(Vector3::new(2., 0.5, -3.)))
+        .then(&Transform::new_translation(Vector3::new(1., -2., 4.)))
+}
+
+fn assert_identity(matrix: &Matrix4) {
+    for (row, rows) in matrix.get_rows().iter().enumerate() {
+        for (column, value) in rows.iter().enumerate() {
+            let expected = if row == column { 1. } else { 0. };
+            assert!(
+                (value - expected).abs() < EPSILON,
+                "expected the identity, found\n{}",
+                matrix
+            );
+        }
+    }
+}
+
+#[test]
+fn composes_with_the_inverse_into_the_identity() {
+    let transform = build_transform();
+    let point = Vector3::new(0.5, -1.5, 2.);
+
+    assert_identity(transform.then(&transform.inverse()).get_matrix());
+    assert_identity(transform.inverse().then(&transform).get_matrix());
+    assert_identity(&(*transform.get_matrix() * *transform.get_inverse_matrix()));
+    assert_close(
+        &transform
+            .inverse()
+            .transform_point(&transform.transform_point(&point)),
+        &point,
+    );
+
+    let inverse = Transform::new(*transform.get_matrix()).unwrap().inverse();
+    assert_close(
+        &inverse.transform_point(&point),
+        &transform.inverse().transform_point(&point),
+    );
+}
+
+#[test]
+fn composes_in_order() {
+    let scale = Transform::new_uniform_scale(2.);
+    let translation = Transform::new_translation(Vector3::new(1., 0., 0.));
+    let point = Vector3::new(1., 1., 1.);
+
+    assert_close(
+        &scale.then(&translation).transform_point(&point),
+        &Vector3::new(3., 2., 2.),
+    );
+    assert_close(
+        &translation.then(&scale).transform_point(&point),
+        &Vector3::new(4., 2., 2.),
+    );
+    // Like matrices, the right-hand side applies first
+    assert_close(
+        &(translation * scale).transform_point(&point),
+        &Vector3::new(3., 2., 2.),
+    );
+
+    // Directions are not translated
+    assert_close(
+        &scale.then(&translation).transform_direction(&point),
+        &Vector3::new(2., 2., 2.),
+    );
+}
+
+#[test]
+fn shears_along_an_axis() {
+    let shear = Transform::new_shear(0, 1, 0.5);
+    assert_close(
+        &shear.transform_point(&Vector3::new(1., 2., 3.)),
+        &Vector3::new(2., 2., 3.),
+    );
+    assert_close(
+        &shear.inverse().transform_point(&Vector3::new(2., 2., 3.)),
+        &Vector3::new(1., 2., 3.),
+    );
+
+    let shear = Transform::new_shear(2, 0, -2.);
+    assert_close(
+        &shear.transform_point(&Vector3::new(1., 2., 3.)),
+        &Vector3::new(1., 2., 1.),
+    );
+
+    // The plane y = x is sheared to y = 1.5 x, with its normal
+    let shear = Transform::new_shear(1, 0, 0.5);
+    assert_close(
+        &shear.transform_normal(&Vector3::new(-1., 1., 0.)),
+        &Vector3::new(-1.5, 1., 0.).normalized(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "shear axes")]
+fn rejects_shears_along_the_same_axis() {
+    Transform::new_shear(1, 1, 0.5);
+}
+
+#[test]
+fn rejects_singular_matrices() {
+    let mut rows = Matrix4::IDENTITY.get_rows().to_owned();
+    // The second row is twice the first one
+    rows[0] = [1., 1., 0., 0.];
+    rows[1] = [2., 2., 0., 0.];
+    assert!(Transform::new(Matrix4::new(rows)).is_none());
+}
+
+#[test]
+fn hits_non_uniformly_scaled_spheres() {
+    // The ellipsoid x² / 4 + y² + z² = 1
+    let mut builder = SphereBuilder::new(Vector3::new(0., 0., 0.), 1.);
+    builder.with_transform(&Transform::new_scale(Vector3::new(2., 1., 1.)));
+    let ellipsoid = builder.build();
+
+    let ray = Ray::new(Vector3::new(1., 0., 10.), Vector3::new(0., 0., -1.));
+    let intersection = ellipsoid
+        .intersect(&ray)
+        .expect("the ray should hit the ellipsoid");
+    let z = 0.75_f64.sqrt();
+    assert!((intersection.get_distance() - (10. - z)).abs() < EPSILON);
+    assert_close(intersection.get_point(), &Vector3::new(1., 0., z));
+    // Along the gradient (x / 4, y, z) of the ellipsoid
+    assert_close(
+        intersection.get_normal(),
+        &Vector3::new(0.25, 0., z).normalized(),
+    );
+    assert!(intersection.is_exterior());
+}